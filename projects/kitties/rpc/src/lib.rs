@@ -0,0 +1,165 @@
+//! JSON-RPC methods of the kitties module, calling the `KittiesApi` runtime API.
+//!
+//! Results are JSON friendly: DNA is `0x` prefixed hex and balances are decimal strings,
+//! so 128 bit balances survive JavaScript clients.
+
+use std::{fmt::Display, sync::Arc};
+
+use client::blockchain::HeaderBackend;
+use codec::Codec;
+use jsonrpc_core::{Error, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use kitties_runtime_api::KittyDetails;
+use serde::{Serialize, Deserialize};
+use sr_primitives::{generic::BlockId, traits::{Block as BlockT, ProvideRuntimeApi}};
+
+pub use kitties_runtime_api::KittiesApi as KittiesRuntimeApi;
+
+/// A kitty as returned by the RPC methods
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Kitty<AccountId, KittyIndex> {
+	pub id: KittyIndex,
+	pub owner: AccountId,
+	/// `0x` prefixed hex
+	pub dna: String,
+	/// Decimal, "0" when not for sale
+	pub price: String,
+	pub generation: u32,
+}
+
+/// A kitty for sale
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Listing<KittyIndex> {
+	pub id: KittyIndex,
+	/// Decimal
+	pub price: String,
+}
+
+/// A page of listings and the cursor of the next page, `None` if this is the last one
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Listings<KittyIndex> {
+	pub listings: Vec<Listing<KittyIndex>>,
+	pub next: Option<KittyIndex>,
+}
+
+#[rpc]
+pub trait KittiesApi<BlockHash, AccountId, KittyIndex> {
+	/// The kitties owned by `account`, oldest first
+	#[rpc(name = "kitties_getOwned")]
+	fn owned(&self, account: AccountId, at: Option<BlockHash>) -> Result<Vec<Kitty<AccountId, KittyIndex>>>;
+
+	/// A page of at most `limit` kitties for sale following the `start_after` cursor
+	#[rpc(name = "kitties_getListings")]
+	fn listings(&self, start_after: Option<KittyIndex>, limit: u32, at: Option<BlockHash>) -> Result<Listings<KittyIndex>>;
+
+	/// A kitty, `null` if it does not exist
+	#[rpc(name = "kitties_getKitty")]
+	fn kitty(&self, kitty_id: KittyIndex, at: Option<BlockHash>) -> Result<Option<Kitty<AccountId, KittyIndex>>>;
+}
+
+/// Implements the kitties RPC methods on top of a client
+pub struct Kitties<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Kitties<C, B> {
+	pub fn new(client: Arc<C>) -> Self {
+		Kitties { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, AccountId, KittyIndex, Balance> KittiesApi<<Block as BlockT>::Hash, AccountId, KittyIndex> for Kitties<C, Block> where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi + HeaderBackend<Block>,
+	C::Api: KittiesRuntimeApi<Block, AccountId, KittyIndex, Balance>,
+	AccountId: Codec,
+	KittyIndex: Codec + Copy,
+	Balance: Codec + Display,
+{
+	fn owned(&self, account: AccountId, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<Kitty<AccountId, KittyIndex>>> {
+		let kitties = self.client.runtime_api().kitties_of(&self.block_id(at), account).map_err(runtime_error)?;
+		Ok(kitties.into_iter().map(|(kitty_id, details)| to_json(kitty_id, details)).collect())
+	}
+
+	fn listings(
+		&self,
+		start_after: Option<KittyIndex>,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Listings<KittyIndex>> {
+		let (listings, next) = self.client.runtime_api()
+			.market_listings(&self.block_id(at), start_after, limit)
+			.map_err(runtime_error)?;
+		Ok(Listings {
+			listings: listings.into_iter()
+				.map(|(id, price)| Listing { id, price: price.to_string() })
+				.collect(),
+			next,
+		})
+	}
+
+	fn kitty(&self, kitty_id: KittyIndex, at: Option<<Block as BlockT>::Hash>) -> Result<Option<Kitty<AccountId, KittyIndex>>> {
+		let details = self.client.runtime_api().kitty_details(&self.block_id(at), kitty_id).map_err(runtime_error)?;
+		Ok(details.map(|details| to_json(kitty_id, details)))
+	}
+}
+
+impl<C, Block> Kitties<C, Block> where
+	Block: BlockT,
+	C: HeaderBackend<Block>,
+{
+	/// The requested block, the best one by default
+	fn block_id(&self, at: Option<<Block as BlockT>::Hash>) -> BlockId<Block> {
+		BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash))
+	}
+}
+
+fn to_json<AccountId, KittyIndex, Balance: Display>(
+	kitty_id: KittyIndex,
+	details: KittyDetails<AccountId, Balance>,
+) -> Kitty<AccountId, KittyIndex> {
+	Kitty {
+		id: kitty_id,
+		owner: details.owner,
+		dna: to_hex(&details.dna),
+		price: details.price.to_string(),
+		generation: details.generation,
+	}
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	let mut hex = String::with_capacity(2 + bytes.len() * 2);
+	hex.push_str("0x");
+	for byte in bytes {
+		hex.push_str(&format!("{:02x}", byte));
+	}
+	hex
+}
+
+fn runtime_error<E: std::fmt::Debug>(e: E) -> Error {
+	Error {
+		code: ErrorCode::ServerError(1),
+		message: "Unable to query kitties.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn kitty_details_become_json_friendly() {
+		let details = KittyDetails { owner: 7u64, dna: [0xab; 16], price: u128::max_value(), generation: 2 };
+
+		let kitty = to_json(3u32, details);
+
+		assert_eq!(kitty.dna, format!("0x{}", "ab".repeat(16)));
+		assert_eq!(kitty.price, "340282366920938463463374607431768211455");
+		assert_eq!(serde_json::to_value(&kitty).unwrap()["generation"], 2);
+	}
+}
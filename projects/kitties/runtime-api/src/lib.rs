@@ -0,0 +1,89 @@
+//! Runtime API of the kitties module, so light clients and RPC nodes can query kitties
+//! without decoding raw storage.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::{Codec, Encode, Decode};
+use rstd::prelude::*;
+#[cfg(feature = "std")]
+use primitives::{Blake2Hasher, H256};
+
+/// A kitty with its owner
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct KittyDetails<AccountId, Balance> {
+	pub owner: AccountId,
+	pub dna: [u8; 16],
+	/// 0 when not for sale
+	pub price: Balance,
+	pub generation: u32,
+}
+
+/// What a renderer draws for a kitty, derived from its DNA
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct KittyArt {
+	/// Part ids of the body, pattern, eye color and eye shape
+	pub parts: [u8; 4],
+	/// Palette indexes of the base, highlight and accent colors
+	pub palette: [u8; 3],
+}
+
+client::decl_runtime_apis! {
+	pub trait KittiesApi<AccountId, KittyIndex, Balance> where
+		AccountId: Codec,
+		KittyIndex: Codec,
+		Balance: Codec,
+	{
+		/// The kitties owned by `account`, oldest first
+		fn kitties_of(account: AccountId) -> Vec<(KittyIndex, KittyDetails<AccountId, Balance>)>;
+
+		/// A kitty, `None` if it does not exist
+		fn kitty_details(kitty_id: KittyIndex) -> Option<KittyDetails<AccountId, Balance>>;
+
+		/// Art of a kitty, `None` if it does not exist or is not revealed yet
+		fn kitty_art(kitty_id: KittyIndex) -> Option<KittyArt>;
+
+		/// The kitty with this name, `None` if no kitty has it
+		fn kitty_by_name(name: Vec<u8>) -> Option<KittyIndex>;
+
+		/// A page of at most `limit` kitties for sale with their price, following the `start_after` cursor.
+		/// Returns the cursor of the next page, `None` if this is the last one
+		fn market_listings(start_after: Option<KittyIndex>, limit: u32) -> (Vec<(KittyIndex, Balance)>, Option<KittyIndex>);
+
+		/// A page of at most `limit` kitties with their owner and price, following the `start_after` cursor.
+		/// Walking every page at one block gives a deterministic snapshot of the module, in creation order.
+		/// Returns the cursor of the next page, `None` if this is the last one
+		fn kitties_snapshot(start_after: Option<KittyIndex>, limit: u32) -> (Vec<(KittyIndex, KittyDetails<AccountId, Balance>)>, Option<KittyIndex>);
+
+		/// The kitties whose trait `trait_kind` has `value`, `trait_kind` indexes the kitties module
+		/// `TraitKind::ALL`: 0 body, 1 pattern, 2 eye color, 3 eye shape. Empty for an unknown kind
+		fn kitties_with_trait(trait_kind: u8, value: u8) -> Vec<KittyIndex>;
+
+		/// Number of live kitties of `generation`
+		fn kitties_per_generation(generation: u32) -> u64;
+
+		/// Storage key of the owner of `kitty_id`. A read proof of it at a block, from the
+		/// `state_getReadProof` RPC, proves the owner at that block with `verify_ownership_proof`
+		fn kitty_owner_key(kitty_id: KittyIndex) -> Vec<u8>;
+	}
+}
+
+/// Checks that `proof`, a read proof of `owner_key` from `kitty_owner_key`, proves that `owner`
+/// owned the kitty in the state of `state_root`
+#[cfg(feature = "std")]
+pub fn verify_ownership_proof<AccountId: Decode + PartialEq>(
+	state_root: H256,
+	proof: Vec<Vec<u8>>,
+	owner_key: &[u8],
+	owner: &AccountId,
+) -> bool {
+	let values = match state_machine::read_proof_check::<Blake2Hasher, _>(state_root, proof, ::std::iter::once(owner_key)) {
+		Ok(values) => values,
+		Err(_) => return false,
+	};
+	values.get(owner_key)
+		.and_then(|value| value.as_ref())
+		.and_then(|value| AccountId::decode(&mut &value[..]).ok())
+		.map_or(false, |proven| proven == *owner)
+}
@@ -0,0 +1,114 @@
+//! Benchmarks of the kitties dispatchables, used to size their `#[weight]` annotations.
+//!
+//! Each benchmark builds the worst state it can reach (long owned kitties lists, a full watcher list)
+//! and prints the average time of one call. They are ignored by default, run them with
+//! `cargo test -p substrate-kitties-module benchmark -- --ignored --nocapture`.
+
+use super::*;
+use super::tests::{Origin, Test, KittiesModule, ExtBuilder};
+use runtime_io::with_externalities;
+use support::assert_ok;
+use std::time::Instant;
+
+/// Number of kitties owned by each benchmarked account
+const COLLECTION_SIZE: u32 = 1_000;
+/// Number of calls averaged by each benchmark
+const RUNS: u32 = 100;
+
+fn seed_collection(owner: u64, count: u32) {
+	for _ in 0..count {
+		assert_ok!(KittiesModule::create(Origin::signed(owner)));
+	}
+}
+
+fn seed_watchers(kitty_id: u32) {
+	for watcher in 0..<Test as Trait>::MaxWatchersPerKitty::get() as u64 {
+		assert_ok!(KittiesModule::watch(Origin::signed(1_000 + watcher), kitty_id));
+	}
+}
+
+fn report(name: &str, started: Instant) {
+	println!("{}: {:?} per call", name, started.elapsed() / RUNS);
+}
+
+#[test]
+#[ignore]
+fn benchmark_create() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		seed_collection(1, COLLECTION_SIZE);
+
+		let started = Instant::now();
+		for _ in 0..RUNS {
+			assert_ok!(KittiesModule::create(Origin::signed(1)));
+		}
+		report("create", started);
+	});
+}
+
+#[test]
+#[ignore]
+fn benchmark_breed() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		seed_collection(1, COLLECTION_SIZE);
+
+		let started = Instant::now();
+		for _ in 0..RUNS {
+			assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+		}
+		report("breed", started);
+	});
+}
+
+#[test]
+#[ignore]
+fn benchmark_transfer() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		seed_collection(1, COLLECTION_SIZE);
+		seed_collection(2, COLLECTION_SIZE);
+
+		// Unlink from the middle of one long list and link at the end of another
+		let started = Instant::now();
+		for i in 0..RUNS {
+			assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, COLLECTION_SIZE / 2 + i));
+		}
+		report("transfer", started);
+	});
+}
+
+#[test]
+#[ignore]
+fn benchmark_set_price() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		seed_collection(1, COLLECTION_SIZE);
+		seed_watchers(0);
+
+		let started = Instant::now();
+		for i in 0..RUNS {
+			assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 1 + i as u64));
+		}
+		report("set_price", started);
+	});
+}
+
+#[test]
+#[ignore]
+fn benchmark_buy_kitty() {
+	with_externalities(&mut ExtBuilder::default().build(), || {
+		seed_collection(1, COLLECTION_SIZE);
+		seed_collection(2, COLLECTION_SIZE);
+		<balances::Module<Test> as Currency<_>>::make_free_balance_be(&1, 1);
+		<balances::Module<Test> as Currency<_>>::make_free_balance_be(&2, 1_000_000);
+
+		let first = COLLECTION_SIZE / 2;
+		for kitty_id in first..first + RUNS {
+			assert_ok!(KittiesModule::set_price(Origin::signed(1), kitty_id, 100));
+			seed_watchers(kitty_id);
+		}
+
+		let started = Instant::now();
+		for kitty_id in first..first + RUNS {
+			assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), kitty_id, 100));
+		}
+		report("buy_kitty", started);
+	});
+}
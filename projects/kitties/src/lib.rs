@@ -0,0 +1,8817 @@
+//! The Substrate Kitties module, shared by the lesson runtimes.
+//!
+//! Kitty ownership is kept in linked lists per account, the marketplace (`set_price`, `buy_kitty`)
+//! is only available when the `market` feature is enabled. The off-chain worker mirrors the changed
+//! kitties of each block to an HTTP indexer and reports the gen-0 price, see `offchain.rs`.
+//!
+//! The module is instantiable, so a runtime can host several independent collections, e.g. kitties and
+//! puppies, each with its own storage, configuration and events. The default instance keeps the storage
+//! layout of the single instance module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+mod linked_item;
+mod offchain;
+
+use support::{decl_module, decl_storage, decl_event, ensure, StorageValue, StorageMap, StorageDoubleMap,
+              dispatch::Result, Parameter, traits::{Currency, ReservableCurrency, Imbalance, Get, WithdrawReason, ExistenceRequirement, EnsureOrigin, Time}};
+use sr_primitives::traits::{SimpleArithmetic, Bounded, Member, Zero, Hash, UniqueSaturatedInto, UniqueSaturatedFrom, CheckedAdd, Saturating, Verify,
+                            AccountIdConversion};
+use sr_primitives::transaction_validity::{TransactionValidity, ValidTransaction, InvalidTransaction, TransactionLongevity};
+use primitives::sr25519;
+use codec::{Encode, Decode, HasCompact};
+use runtime_io::{blake2_128, blake2_256, twox_128};
+use system::{ensure_signed, ensure_none, offchain::SubmitUnsignedTransaction};
+use rstd::result;
+use sr_primitives::weights::{SimpleDispatchInfo, Weight, WeighData, ClassifyDispatch, DispatchClass};
+use sr_primitives::{Permill, ModuleId};
+use rstd::prelude::*;
+use crate::linked_item::{LinkedList, LinkedItem, ChildTrieItems};
+use nft::NonFungible;
+
+pub trait Trait<I: Instance = DefaultInstance>: system::Trait {
+    type Event: From<Event<Self, I>> + Into<<Self as system::Trait>::Event>;
+    type KittyIndex: Parameter + Member + SimpleArithmetic + Bounded + Default + Copy;
+    /// The currency kitties are bought and sold with, purchase funds are held in reserve during settlement
+    type Currency: ReservableCurrency<Self::AccountId>;
+    /// Whether the marketplace (`set_price`, `buy_kitty`) is open. It can also be compiled out with the `market` feature
+    type MarketEnabled: Get<bool>;
+    /// The runtime call, for the off-chain worker to submit `submit_gen0_price`
+    type Call: From<Call<Self, I>>;
+    /// Submits the unsigned gen-0 price reports of the off-chain worker
+    type SubmitTransaction: SubmitUnsignedTransaction<Self, <Self as Trait<I>>::Call>;
+    /// Signature of the kitty owners over transfer vouchers
+    type Signature: Verify<Signer = Self::AccountId> + Parameter;
+    /// Called after a kitty is created or bred
+    type OnKittyCreated: OnKittyCreated<Self::AccountId, Self::KittyIndex>;
+    /// Called after a kitty changes owner
+    type OnKittyTransfer: OnKittyTransfer<Self::AccountId, Self::KittyIndex>;
+    /// Called after a kitty is burned
+    type OnKittyBurned: OnKittyBurned<Self::AccountId, Self::KittyIndex>;
+    /// Origin allowed to tune the economic parameters with `set_parameter`
+    type GovernanceOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin allowed to pause and resume subsystems with `set_paused`
+    type EmergencyOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin allowed to grant and revoke verified breeder badges with `set_verified_breeder`
+    type RegistrarOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin resolving disputed escrowed trades with `resolve_dispute`
+    type ArbiterOrigin: EnsureOrigin<Self::Origin>;
+    /// Origin blacklisting kitties and barring accounts from the marketplace
+    type ModeratorOrigin: EnsureOrigin<Self::Origin>;
+    /// Blocks an escrowed trade can be disputed for before it settles
+    type DisputeWindow: Get<Self::BlockNumber>;
+    /// Origin issuing and burning soulbound kitties
+    type SoulboundIssuerOrigin: EnsureOrigin<Self::Origin>;
+    /// Whether unlocking an achievement also mints a soulbound badge kitty to the account
+    type AchievementBadges: Get<bool>;
+    /// Blocks before the DNA of a created kitty is revealed, 0 reveals it at once.
+    /// Other delays are at least `MIN_REVEAL_DELAY`
+    type RevealDelay: Get<Self::BlockNumber>;
+    /// Origin of kitties imported from another chain, it checks the proof of their export
+    type BridgeOrigin: EnsureOrigin<Self::Origin>;
+    /// Most pairs bred by one `breed_batch`, and the most pairs its weight is charged for
+    type MaxBreedBatch: Get<u32>;
+    /// Most kitties priced by one `set_price_many`, and the most kitties its weight is charged for
+    type MaxPriceBatch: Get<u32>;
+    /// Keyless account holding the kitties wrapped into index tokens
+    type IndexPoolAccount: Get<Self::AccountId>;
+    /// Time in milliseconds, e.g. the timestamp module
+    type Time: Time;
+    /// Count the breeding and fusion cooldowns in milliseconds of `Time` instead of blocks,
+    /// so they don't drift with the block time
+    type TimestampCooldowns: Get<bool>;
+    /// Most accounts watching one kitty
+    type MaxWatchersPerKitty: Get<u32>;
+    /// Most heirs an account can nominate
+    type MaxHeirs: Get<u32>;
+    /// Most beneficiaries sharing the royalty of a kitty
+    type MaxRoyaltyBeneficiaries: Get<u32>;
+    /// Highest royalty rate, in percent
+    type MaxRoyaltyPercent: Get<u32>;
+    /// Loyalty points earned by both sides of a sale
+    type LoyaltyPointsPerTrade: Get<u64>;
+    /// Most kitties one side of an over-the-counter trade gives
+    type MaxTradeKitties: Get<u32>;
+}
+
+/// Lets other modules react to new kitties
+pub trait OnKittyCreated<AccountId, KittyIndex> {
+    fn on_kitty_created(owner: &AccountId, kitty_id: KittyIndex);
+}
+
+impl<AccountId, KittyIndex> OnKittyCreated<AccountId, KittyIndex> for () {
+    fn on_kitty_created(_: &AccountId, _: KittyIndex) {}
+}
+
+/// Lets other modules react to kitties changing owner, e.g. to invalidate stakes on them
+pub trait OnKittyTransfer<AccountId, KittyIndex> {
+    fn on_kitty_transfer(from: &AccountId, to: &AccountId, kitty_id: KittyIndex);
+}
+
+impl<AccountId, KittyIndex> OnKittyTransfer<AccountId, KittyIndex> for () {
+    fn on_kitty_transfer(_: &AccountId, _: &AccountId, _: KittyIndex) {}
+}
+
+/// Lets other modules react to burned kitties
+pub trait OnKittyBurned<AccountId, KittyIndex> {
+    fn on_kitty_burned(owner: &AccountId, kitty_id: KittyIndex);
+}
+
+impl<AccountId, KittyIndex> OnKittyBurned<AccountId, KittyIndex> for () {
+    fn on_kitty_burned(_: &AccountId, _: KittyIndex) {}
+}
+
+/// Weight of a batch call: the weight of one item times the items of the batch, at least one and at most
+/// the largest batch `Max` lets through. A short batch is charged for what it holds
+pub struct BatchWeight<Max> {
+    per_item: Weight,
+    _max: rstd::marker::PhantomData<Max>,
+}
+
+impl<Max> BatchWeight<Max> {
+    pub fn new(per_item: Weight) -> Self {
+        BatchWeight { per_item, _max: rstd::marker::PhantomData }
+    }
+}
+
+impl<'a, Max: Get<u32>, Item> WeighData<(&'a Vec<Item>,)> for BatchWeight<Max> {
+    fn weigh_data(&self, (items,): (&'a Vec<Item>,)) -> Weight {
+        let items = (items.len() as u32).max(1).min(Max::get());
+        self.per_item.saturating_mul(items)
+    }
+}
+
+impl<Max, Args> ClassifyDispatch<Args> for BatchWeight<Max> {
+    fn classify_dispatch(&self, _: Args) -> DispatchClass {
+        DispatchClass::Normal
+    }
+}
+
+/// Voting weight of an account, so a collective or democracy instance can weigh votes by holdings
+pub trait VoteWeight<AccountId> {
+    fn vote_weight(who: &AccountId) -> u64;
+}
+
+type BalanceOf<T, I = DefaultInstance> = <<T as Trait<I>>::Currency as Currency<<T as system::Trait>::AccountId>>::Balance;
+
+#[derive(Encode, Decode)]
+pub struct Kitty<Balance> {
+    dna: [u8; 16],
+    #[codec(compact)]
+    price: Balance,
+    /// 0 for created kitties, one more than the older parent for bred ones
+    #[codec(compact)]
+    generation: u32,
+    /// Soulbound kitties never move nor go on sale, only their issuer can burn them
+    soulbound: bool,
+    /// Season the kitty was created in, if any
+    season: Option<u32>,
+    /// Species of the creature, `KITTY_SPECIES` for kitties
+    species: SpeciesId,
+}
+
+impl<Balance: Copy> Kitty<Balance> {
+    pub fn dna(&self) -> [u8; 16] {
+        self.dna
+    }
+
+    /// 0 when not for sale
+    pub fn price(&self) -> Balance {
+        self.price
+    }
+
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn is_soulbound(&self) -> bool {
+        self.soulbound
+    }
+
+    pub fn season(&self) -> Option<u32> {
+        self.season
+    }
+
+    pub fn species(&self) -> SpeciesId {
+        self.species
+    }
+}
+
+/// Layout of `Kitty` up to storage version 2, kept to translate the stored records.
+/// Adding a field to `Kitty` works the same way: keep the old struct, bump `STORAGE_VERSION`
+/// and translate the records with `translate_kitties` in `migrate_storage`
+#[derive(Encode, Decode)]
+struct KittyV1<Balance> {
+    dna: [u8; 16],
+    price: Balance,
+}
+
+/// Layout of `Kitty` in storage versions 3 and 4, before its numbers were compact encoded
+#[derive(Encode, Decode)]
+struct KittyV2<Balance> {
+    dna: [u8; 16],
+    price: Balance,
+    generation: u32,
+}
+
+/// Layout of `Kitty` in storage version 5, before soulbound kitties
+#[derive(Encode, Decode)]
+struct KittyV3<Balance: HasCompact> {
+    dna: [u8; 16],
+    #[codec(compact)]
+    price: Balance,
+    #[codec(compact)]
+    generation: u32,
+}
+
+/// Layout of `Kitty` in storage version 6, before seasons
+#[derive(Encode, Decode)]
+struct KittyV4<Balance: HasCompact> {
+    dna: [u8; 16],
+    #[codec(compact)]
+    price: Balance,
+    #[codec(compact)]
+    generation: u32,
+    soulbound: bool,
+}
+
+/// Layout of `Kitty` in storage versions 7 to 10, before species
+#[derive(Encode, Decode)]
+struct KittyV5<Balance: HasCompact> {
+    dna: [u8; 16],
+    #[codec(compact)]
+    price: Balance,
+    #[codec(compact)]
+    generation: u32,
+    soulbound: bool,
+    season: Option<u32>,
+}
+
+type KittyLinkedItem<T, I = DefaultInstance> = LinkedItem<<T as Trait<I>>::KittyIndex>;
+type OwnedKittiesList<T, I = DefaultInstance> = LinkedList<OwnedKittiesItems<I>, <T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex>;
+type LegacyOwnedKittiesList<T, I = DefaultInstance> = LinkedList<OwnedKitties<T, I>, <T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex>;
+type AllKittiesList<T, I = DefaultInstance> = LinkedList<AllKitties<T, I>, (), <T as Trait<I>>::KittyIndex>;
+
+/// A kitty owned by a group of accounts, `threshold` of them must approve any action on it
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode)]
+pub struct CoOwnership<AccountId> {
+    pub owners: Vec<AccountId>,
+    pub threshold: u32,
+}
+
+/// Actions on a co-owned kitty which require approvals
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum KittyAction<AccountId, Balance, KittyIndex> {
+    /// Transfer the kitty to a new (single) owner
+    Transfer(AccountId),
+    /// List the kitty for sale, 0 to delist
+    SetPrice(Balance),
+    /// Breed the kitty with another kitty
+    Breed(KittyIndex),
+}
+
+/// Economic parameters tuned by governance with `set_parameter`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum KittiesParameter<Balance, BlockNumber> {
+    /// Fee burned from the account breeding a kitty
+    BreedingFee(Balance),
+    /// Part of every sale price burned instead of paid to the seller
+    MarketFee(Permill),
+    /// Blocks, or milliseconds with `TimestampCooldowns`, a parent waits before it can breed again
+    BreedingCooldown(BlockNumber),
+    /// Most kitties an account can own, `None` for no limit
+    MaxKittiesPerAccount(Option<u32>),
+    /// Fee burned from the account fusing two kitties
+    FusionFee(Balance),
+    /// Blocks, or milliseconds with `TimestampCooldowns`, a fused kitty waits before it can breed or fuse
+    FusionCooldown(BlockNumber),
+    /// Most gen-0 kitties created in a block, `None` for no limit
+    MaxMintsPerBlock(Option<u32>),
+    /// Most gen-0 kitties an account creates in a mint era, `None` for no limit
+    MaxMintsPerEra(Option<u32>),
+    /// Blocks in a mint era, 0 for an era which never ends
+    MintEraLength(BlockNumber),
+    /// Creation price when no kitty exists yet
+    CreationBasePrice(Balance),
+    /// Creation price added for every kitty in `KittiesCount`
+    CreationPriceSlope(Balance),
+    /// Part of the creation price kept in the reserve and paid back on burns
+    CreationReserveShare(Permill),
+    /// Highest price the market treasury buys kitties back at
+    BuybackFloor(Balance),
+    /// Fee burned for each block a listing is featured
+    FeaturedListingFee(Balance),
+    /// Fee burned from the account naming a kitty
+    NamingFee(Balance),
+    /// Longest kitty name, in bytes
+    MaxNameLength(u32),
+    /// Longest kitty metadata, in bytes
+    MaxMetadataLength(u32),
+    /// Fee burned for each block, or millisecond, of breeding cooldown skipped with `boost_cooldown`
+    CooldownBoostFee(Balance),
+    /// Part of every sale price burned for good, unlike the market fee which funds buybacks
+    SaleBurnRate(Permill),
+    /// Market fee waived for each loyalty point redeemed
+    LoyaltyPointValue(Balance),
+    /// Paid out of the maintenance pot for each stale entry removed by `cleanup` or `cleanup_account`
+    CleanupReward(Balance),
+    /// Reserved from a sponsor for each operation of a sponsorship, and burned when the operation is dispatched
+    SponsoredCallFee(Balance),
+    /// Sale volume of a market era which pauses settlement, `None` for no limit
+    MarketVolumeLimit(Option<Balance>),
+    /// Distance from the moving average price making a sale abnormal, `None` for no limit
+    MarketDeviationLimit(Option<Permill>),
+    /// Most kitty calls an account makes in a block, `None` for no limit
+    MaxOperationsPerBlock(Option<u32>),
+    /// Reserved from the creator of a gen-0 kitty on top of its price, paid to the owner burning it
+    CreationDeposit(Balance),
+    /// Reserved from the account opening an over-the-counter trade until it is executed or cancelled
+    TradeDeposit(Balance),
+}
+
+/// A listing price falling linearly from the listed price at `start` to `end_price` at `end`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct PriceDecay<Balance, BlockNumber> {
+    pub end_price: Balance,
+    pub start: BlockNumber,
+    pub end: BlockNumber,
+}
+
+/// Sales of the current market era, watched by the circuit breaker
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct MarketEraStats<Balance> {
+    pub era: u32,
+    pub volume: Balance,
+    /// Sales further from the moving average price than `MarketDeviationLimit`
+    pub abnormal_sales: u32,
+}
+
+/// A sale held by the module: the kitty is locked and the payment reserved until the dispute window ends
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct EscrowedTrade<AccountId, Balance, BlockNumber> {
+    pub seller: AccountId,
+    pub buyer: AccountId,
+    pub price: Balance,
+    /// Block the trade settles at unless disputed
+    pub release_at: BlockNumber,
+    /// A disputed trade waits for the arbiter
+    pub disputed: bool,
+}
+
+/// A DNA byte a season keeps within `min..=max`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct GeneRange {
+    pub byte: u8,
+    pub min: u8,
+    pub max: u8,
+}
+
+/// A season: kitties created or bred from `start` to `end` are tagged with it and get its genes,
+/// until `max_supply` of them exist
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Season<BlockNumber> {
+    pub start: BlockNumber,
+    pub end: BlockNumber,
+    pub genes: Vec<GeneRange>,
+    pub max_supply: Option<u32>,
+}
+
+/// What an account must do to complete a quest
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum QuestObjective {
+    /// Breed a kitty whose DNA byte `byte` has the bits of `mask` set to `value`
+    BreedWithTrait { byte: u8, mask: u8, value: u8 },
+    /// Breed this many kitties
+    Breeds(u32),
+    /// Sell this many kitties
+    Sales(u32),
+}
+
+/// What a completed quest pays
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum QuestReward<Balance> {
+    /// Funds out of the quest pool account
+    Balance(Balance),
+    /// A new gen-0 kitty, free of the gen-0 price
+    Kitty,
+}
+
+/// A quest defined by governance, progress only counts until `ends_at`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Quest<Balance, BlockNumber> {
+    pub objective: QuestObjective,
+    pub reward: QuestReward<Balance>,
+    pub ends_at: BlockNumber,
+}
+
+impl QuestObjective {
+    /// Progress needed to complete the objective
+    fn target(&self) -> u32 {
+        match self {
+            QuestObjective::BreedWithTrait { .. } => 1,
+            QuestObjective::Breeds(count) | QuestObjective::Sales(count) => *count,
+        }
+    }
+}
+
+/// Parts of the module which can be paused on their own in an emergency
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    /// Creating gen-0 kitties
+    Minting,
+    /// Breeding kitties
+    Breeding,
+    /// Every change of owner, sales included
+    Transfers,
+    /// Listing kitties and settling sales
+    Market,
+    /// Settling sales only, paused by the market circuit breaker
+    Settlement,
+}
+
+/// Traits decoded from the DNA, each is the low 4 bits of one DNA byte so it takes 16 values
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum TraitKind {
+    Body,
+    Pattern,
+    EyeColor,
+    EyeShape,
+}
+
+impl TraitKind {
+    pub const ALL: [TraitKind; 4] = [TraitKind::Body, TraitKind::Pattern, TraitKind::EyeColor, TraitKind::EyeShape];
+
+    /// Value of this trait in the DNA of a kitty
+    pub fn value_in(self, dna: &[u8; 16]) -> u8 {
+        dna[self.index()] & 0x0f
+    }
+
+    /// Position of this trait in `ALL`, also the DNA byte it is read from for kitties
+    pub fn index(self) -> usize {
+        match self {
+            TraitKind::Body => 0,
+            TraitKind::Pattern => 1,
+            TraitKind::EyeColor => 2,
+            TraitKind::EyeShape => 3,
+        }
+    }
+}
+
+/// Id of a species in the registry
+pub type SpeciesId = u32;
+
+/// Kitties, the species every creature had before the registry. It is not stored in the registry
+pub const KITTY_SPECIES: SpeciesId = 0;
+
+/// A kind of creature: how much of the DNA it uses and where its traits are read from
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Species {
+    pub name: Vec<u8>,
+    /// DNA bytes the creatures have, the bytes past it are always 0
+    pub dna_length: u8,
+    /// DNA byte each trait of `TraitKind::ALL` is read from
+    pub trait_bytes: [u8; 4],
+}
+
+impl Species {
+    pub fn kitties() -> Self {
+        Species { name: b"Kitty".to_vec(), dna_length: 16, trait_bytes: [0, 1, 2, 3] }
+    }
+
+    /// Value of a trait in the DNA of a creature of this species, the low 4 bits of its byte
+    pub fn trait_value(&self, kind: TraitKind, dna: &[u8; 16]) -> u8 {
+        dna[self.trait_bytes[kind.index()] as usize % 16] & 0x0f
+    }
+
+    /// Clear the bytes of `dna` this species does not use
+    pub fn mask(&self, dna: &mut [u8; 16]) {
+        for byte in dna.iter_mut().skip(self.dna_length as usize) {
+            *byte = 0;
+        }
+    }
+}
+
+/// Number of colors a renderer has in its palette
+pub const PALETTE_SIZE: u8 = 32;
+
+/// What a renderer draws for a DNA, so every renderer draws the same kitty
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub struct ArtDescriptor {
+    /// Part ids, in the order of `TraitKind::ALL`
+    pub parts: [u8; 4],
+    /// Palette indexes of the base, highlight and accent colors
+    pub palette: [u8; 3],
+}
+
+impl ArtDescriptor {
+    pub fn from_dna(dna: &[u8; 16]) -> Self {
+        let mut parts = [0u8; 4];
+        for (part, kind) in parts.iter_mut().zip(TraitKind::ALL.iter()) {
+            *part = kind.value_in(dna);
+        }
+        let mut palette = [0u8; 3];
+        for (color, byte) in palette.iter_mut().zip(dna[4..7].iter()) {
+            *color = byte % PALETTE_SIZE;
+        }
+        ArtDescriptor { parts, palette }
+    }
+}
+
+/// A kitty leaving the chain, `export_id` is unique on the exporting chain. Its hash is kept in
+/// `ExportCommitments` so the other side can check a storage proof of it
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct KittyExport {
+    pub export_id: u64,
+    pub dna: [u8; 16],
+    pub generation: u32,
+    /// Encoded account of the recipient on the target chain
+    pub recipient: Vec<u8>,
+}
+
+/// How an owner got a kitty
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum Acquisition {
+    /// Created as a gen-0 kitty, a badge or a reward
+    Mint,
+    /// Bred or fused from kitties of the owner
+    Breed,
+    /// Given by the previous owner, directly, by voucher, on schedule or by redeeming its shares
+    Transfer,
+    /// Bought on the marketplace
+    Sale,
+    /// Claimed as an heir
+    Inheritance,
+}
+
+/// One change of owner in the provenance of a kitty
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct ProvenanceRecord<AccountId, BlockNumber> {
+    pub owner: AccountId,
+    pub previous_owner: Option<AccountId>,
+    pub at: BlockNumber,
+    pub acquisition: Acquisition,
+}
+
+/// What one party of an over-the-counter trade gives
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct TradeSide<AccountId, KittyIndex, Balance> {
+    pub account: AccountId,
+    pub kitties: Vec<KittyIndex>,
+    pub balance: Balance,
+    /// Confirmed sides have their balance reserved
+    pub confirmed: bool,
+}
+
+/// A barter between two accounts, executed once both sides confirm
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct OtcTrade<AccountId, KittyIndex, Balance> {
+    pub proposer: TradeSide<AccountId, KittyIndex, Balance>,
+    pub counterparty: TradeSide<AccountId, KittyIndex, Balance>,
+}
+
+/// Funds reserved by `buyer` for whoever knows the preimage of the voucher code hash
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Voucher<AccountId, Balance, BlockNumber> {
+    pub buyer: AccountId,
+    pub amount: Balance,
+    /// Refunded to the buyer from this block
+    pub expires_at: BlockNumber,
+}
+
+/// Part of every resale of a kitty paid to the beneficiaries chosen by its creator
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Royalty<AccountId> {
+    /// Part of the seller's proceeds paid as royalty
+    pub rate: Permill,
+    /// Beneficiaries with their per-mille share of the royalty, the shares sum to 1000
+    pub splits: Vec<(AccountId, u32)>,
+}
+
+/// Phase of the gen-0 drop, each with its own per-account mint limit
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum DropPhase {
+    /// Only whitelisted accounts and holders of a mint-pass kitty can buy
+    Presale,
+    /// Anyone can buy
+    Public,
+}
+
+impl Default for DropPhase {
+    fn default() -> Self {
+        DropPhase::Public
+    }
+}
+
+/// A transfer signed off-chain by the owner of the kitty, anyone can submit it with `transfer_with_signature`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct TransferVoucher<AccountId, KittyIndex, BlockNumber> {
+    pub kitty_id: KittyIndex,
+    pub to: AccountId,
+    /// Must be the owner's `TransferVoucher` nonce, so each voucher is used at most once
+    pub nonce: u64,
+    /// Last block the voucher can be used in
+    pub deadline: BlockNumber,
+}
+
+/// A sale signed off-chain by the owner of the kitty, any buyer can fill it with `fill_sale_offer`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct SaleOffer<KittyIndex, Balance, BlockNumber> {
+    pub kitty_id: KittyIndex,
+    /// Greater than zero, a signed offer is never a gift
+    pub price: Balance,
+    /// Must be the owner's `SaleOffer` nonce, `cancel_all_orders` withdraws every offer signed for it
+    pub nonce: u64,
+    /// Last block the offer can be filled in
+    pub deadline: BlockNumber,
+}
+
+/// What an off-chain signature with a nonce is for, each purpose has its own nonces
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum NoncePurpose {
+    TransferVoucher,
+    SponsoredCall,
+    SaleOffer,
+}
+
+impl NoncePurpose {
+    pub fn all() -> [NoncePurpose; 3] {
+        [NoncePurpose::TransferVoucher, NoncePurpose::SponsoredCall, NoncePurpose::SaleOffer]
+    }
+}
+
+/// A privileged call recorded in the audit log
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct AuditEntry<AccountId, BlockNumber> {
+    /// Signed caller, `None` for the root and collective origins
+    pub caller: Option<AccountId>,
+    /// The SCALE encoded `Call` of this module
+    pub call: Vec<u8>,
+    pub at: BlockNumber,
+}
+
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+#[derive(Encode, Decode)]
+pub struct PendingKittyAction<AccountId, Balance, KittyIndex> {
+    pub action: KittyAction<AccountId, Balance, KittyIndex>,
+    pub approvals: Vec<AccountId>,
+}
+
+/// Kitty operations a sponsor pays for with `sponsored_call`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum SponsoredOperation<AccountId, KittyIndex> {
+    /// Create a gen-0 kitty, the gen-0 price is still paid by the sponsored account
+    Create,
+    /// Transfer a kitty. (to, kitty_id)
+    Transfer(AccountId, KittyIndex),
+}
+
+/// An account's kitty operations paid for by a sponsor
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct Sponsorship<AccountId, Balance> {
+    pub sponsor: AccountId,
+    /// Operations left, their fees are reserved from the sponsor
+    pub remaining: u32,
+    /// Fee of each operation, at the time of sponsoring
+    pub fee: Balance,
+}
+
+/// Current version of the storage layout
+/// 0: `OwnedKitties` as a map keyed by `(AccountId, Option<KittyIndex>)`
+/// 1: `OwnedKitties` as a double map
+/// 2: `AllKitties` index of live kitties
+/// 3: `Kitty` records carry a `generation`
+/// 4: Owned kitties lists in one child trie per account
+/// 5: Compact encoded `Kitty` price and generation
+/// 6: `Kitty` records carry a `soulbound` flag
+/// 7: `Kitty` records carry a `season`
+/// 8: `ListingsAtPrice` index of the kitties for sale
+/// 9: `KittiesWithTrait` index of the kitty traits
+/// 10: `KittiesPerGeneration` counts
+/// 11: `Kitty` records carry a `species`
+/// 12: Listed prices split into `ListedPricesInRange` by magnitude
+/// 13: `KittiesPerTrait` counts
+/// Chains running the lesson-4 module also start at 0 and are migrated straight to 1
+const STORAGE_VERSION: u32 = 13;
+
+/// Most kitty ids `migrate_storage` walks in the block it runs in, larger chains are left on their version
+const MAX_MIGRATED_KITTIES: u64 = 10_000;
+
+/// Name of a module instance, which starts its storage keys: empty for the default instance, e.g. `Instance1` for others
+fn instance_name<I: Instance>() -> &'static [u8] {
+    let prefix = I::PREFIX_FOR_StorageVersion.as_bytes();
+    &prefix[..prefix.len() - b"Kitties StorageVersion".len()]
+}
+
+/// Prefix of the child tries holding each account's owned kitties list, `kitties_owned_` after the instance name
+pub struct OwnedKittiesChildPrefix<I>(rstd::marker::PhantomData<I>);
+
+impl<I: Instance> Get<Vec<u8>> for OwnedKittiesChildPrefix<I> {
+    fn get() -> Vec<u8> {
+        [instance_name::<I>(), &b"kitties_owned_"[..]].concat()
+    }
+}
+
+/// Items of the owned kitties lists, in one child trie per account
+pub type OwnedKittiesItems<I = DefaultInstance> = ChildTrieItems<OwnedKittiesChildPrefix<I>>;
+
+/// Maximum number of kitties returned by one page of a query
+const MAX_PAGE_SIZE: u32 = 100;
+
+/// Number of entries kept in each leaderboard
+const LEADERBOARD_SIZE: usize = 10;
+
+/// Number of recent sales the moving average gen-0 price is taken over
+const RECENT_SALES_SIZE: usize = 20;
+
+/// Blocks between two gen-0 price reports of the off-chain worker
+const GEN0_PRICE_UPDATE_PERIOD: u32 = 10;
+
+/// Blocks after which a gen-0 price report is stale and gen-0 minting stops until the next one
+const GEN0_PRICE_MAX_AGE: u32 = 100;
+
+/// Blocks after which a reference price quote is stale and reference listings can not be bought
+const REFERENCE_PRICE_MAX_AGE: u32 = 100;
+
+/// Shortest delay of a reveal: the DNA is drawn from the hash of the block after the creation block,
+/// which is only known two blocks after the creation
+const MIN_REVEAL_DELAY: u32 = 2;
+
+/// Kitties the garbage collecting off-chain worker looks at in each block
+const GC_SCAN_SIZE: u32 = 50;
+
+/// Most finished auctions settled by one `collect_garbage` transaction
+const MAX_GARBAGE_ITEMS: usize = 20;
+
+/// Blocks a `collect_garbage` transaction stays valid in the pool
+const GC_LONGEVITY: u32 = 5;
+
+/// Largest factor a new gen-0 price report may move the price by
+const GEN0_PRICE_MAX_CHANGE: u32 = 10;
+
+/// Every `GEN0_CURVE_STEP` gen-0 kitties minted raise the gen-0 price by one reported price
+const GEN0_CURVE_STEP: u32 = 100;
+
+type KittiesParameterOf<T, I = DefaultInstance> = KittiesParameter<BalanceOf<T, I>, <T as system::Trait>::BlockNumber>;
+type EscrowedTradeOf<T, I = DefaultInstance> = EscrowedTrade<<T as system::Trait>::AccountId, BalanceOf<T, I>, <T as system::Trait>::BlockNumber>;
+type ProvenanceRecordOf<T> = ProvenanceRecord<<T as system::Trait>::AccountId, <T as system::Trait>::BlockNumber>;
+type SeasonOf<T> = Season<<T as system::Trait>::BlockNumber>;
+type QuestOf<T, I = DefaultInstance> = Quest<BalanceOf<T, I>, <T as system::Trait>::BlockNumber>;
+type SaleOfferOf<T, I = DefaultInstance> = SaleOffer<<T as Trait<I>>::KittyIndex, BalanceOf<T, I>, <T as system::Trait>::BlockNumber>;
+type TransferVoucherOf<T, I = DefaultInstance> = TransferVoucher<<T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex, <T as system::Trait>::BlockNumber>;
+/// Most kitties of the owner's list one `complete_inheritance` call looks at, moved or not
+const INHERITANCE_BATCH: usize = 50;
+
+/// Achievement bits of `Achievements`: the first kitty bred
+pub const ACHIEVEMENT_FIRST_BREED: u32 = 1 << 0;
+/// Achievement bits of `Achievements`: owning `HUNDRED_KITTIES` kitties at once
+pub const ACHIEVEMENT_HUNDRED_KITTIES: u32 = 1 << 1;
+/// Achievement bits of `Achievements`: the first kitty of generation 5 or later bred
+pub const ACHIEVEMENT_FIRST_GEN5: u32 = 1 << 2;
+/// Achievement bits of `Achievements`: the first sale with a donation to the charity
+pub const ACHIEVEMENT_FIRST_DONATION: u32 = 1 << 3;
+
+const HUNDRED_KITTIES: u64 = 100;
+
+/// Most quests open at once, the breed and sale paths go through all of them
+const MAX_ACTIVE_QUESTS: usize = 10;
+
+/// Most kitties a buyback looks at, the cursor lets the next call go on
+const BUYBACK_SCAN_LIMIT: u32 = 100;
+
+/// Most listings featured at once
+const MAX_FEATURED_LISTINGS: usize = 20;
+
+/// Most kitties indexed under one trait value, later ones are left out of the bucket
+const MAX_TRAIT_BUCKET_SIZE: usize = 1_000;
+
+/// Most records in the provenance of a kitty, past it the oldest ones after the first are dropped
+const MAX_PROVENANCE_RECORDS: usize = 32;
+
+/// Longest memo of a transfer, in bytes
+const MAX_MEMO_LENGTH: usize = 64;
+
+/// Longest encoded account of an export recipient, in bytes
+const MAX_BRIDGE_ACCOUNT_LENGTH: usize = 64;
+
+/// Longest kitty name, in bytes, until governance sets `MaxNameLength`
+const DEFAULT_MAX_NAME_LENGTH: u32 = 32;
+/// Longest kitty metadata, in bytes, until governance sets `MaxMetadataLength`
+const DEFAULT_MAX_METADATA_LENGTH: u32 = 256;
+/// Most prefixes banned from kitty names
+const MAX_BANNED_NAME_PREFIXES: usize = 50;
+
+/// Most kitties counted in the vote weight of an account
+const MAX_VOTING_KITTIES: usize = 100;
+/// A trait is rare when at most one kitty in this many has its value
+const RARE_TRAIT_DIVISOR: u64 = 32;
+
+/// The limits fixed by the runtime, the same as the constants of the module metadata
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub struct ModuleConstants<BlockNumber> {
+    pub dispute_window: BlockNumber,
+    pub reveal_delay: BlockNumber,
+    pub timestamp_cooldowns: bool,
+    pub max_breed_batch: u32,
+    pub max_price_batch: u32,
+    pub max_watchers_per_kitty: u32,
+    pub max_heirs: u32,
+    pub max_royalty_beneficiaries: u32,
+    pub max_royalty_percent: u32,
+    pub loyalty_points_per_trade: u64,
+    pub max_trade_kitties: u32,
+    pub max_page_size: u32,
+}
+
+type AuditEntryOf<T> = AuditEntry<<T as system::Trait>::AccountId, <T as system::Trait>::BlockNumber>;
+/// Privileged calls kept in the audit log, older ones are overwritten
+const AUDIT_LOG_SIZE: u32 = 100;
+
+type OtcTradeOf<T, I = DefaultInstance> = OtcTrade<<T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex, BalanceOf<T, I>>;
+
+
+/// Most entries of an owner's list `cleanup_account` looks at
+const MAX_CLEANUP_STEPS: usize = 100;
+
+type SponsoredOperationOf<T, I = DefaultInstance> = SponsoredOperation<<T as system::Trait>::AccountId, <T as Trait<I>>::KittyIndex>;
+type SponsorshipOf<T, I = DefaultInstance> = Sponsorship<<T as system::Trait>::AccountId, BalanceOf<T, I>>;
+/// Most operations one sponsorship pays for
+const MAX_SPONSORED_OPERATIONS: u32 = 100;
+/// Blocks a sponsored call stays in the pool
+const SPONSORED_CALL_LONGEVITY: u32 = 64;
+
+/// Blocks in a market era of the circuit breaker
+const MARKET_ERA_LENGTH: u32 = 600;
+/// Abnormal sales in a market era which pause settlement
+const MAX_ABNORMAL_SALES: u32 = 5;
+
+/// Module account holding the market fees until buybacks spend them
+const TREASURY_ID: ModuleId = ModuleId(*b"kt/trsry");
+/// Module account holding the creation reserve
+const CREATION_RESERVE_ID: ModuleId = ModuleId(*b"kt/rsrve");
+/// Module account paying the cleanup rewards
+const MAINTENANCE_POT_ID: ModuleId = ModuleId(*b"kt/maint");
+/// Module account paying the balance rewards of quests
+const QUEST_POOL_ID: ModuleId = ModuleId(*b"kt/quest");
+
+type KittyActionOf<T, I = DefaultInstance> = KittyAction<<T as system::Trait>::AccountId, BalanceOf<T, I>, <T as Trait<I>>::KittyIndex>;
+type PendingKittyActionOf<T, I = DefaultInstance> = PendingKittyAction<<T as system::Trait>::AccountId, BalanceOf<T, I>, <T as Trait<I>>::KittyIndex>;
+
+decl_storage! {
+	trait Store for Module<T: Trait<I>, I: Instance = DefaultInstance> as Kitties {
+		/// Stores all the kitties, key is the kitty id / index
+		pub Kitties get(kitty): map T::KittyIndex => Option<Kitty<BalanceOf<T, I>>>;
+
+		/// The owner of a kitty. For a co-owned kitty this is the account the co-ownership was set up from,
+		/// and it can only act on the kitty through approved actions
+		pub KittyOwner get(owner_of): map T::KittyIndex => Option<T::AccountId>;
+
+		/// Stores the total number of kitties. i.e. the next kitty index
+		pub KittiesCount get(kitties_count): T::KittyIndex;
+
+		/// Linked list of the kitties owned by an account, the `None` entry is the head.
+		/// Only read to migrate to the child trie lists of `OwnedKittiesItems` (storage version 4)
+		pub OwnedKitties get(owned_kitties): double_map T::AccountId, blake2_256(Option<T::KittyIndex>) => Option<KittyLinkedItem<T, I>>;
+		/// Linked list of all live kitties, the `None` entry is the head
+		pub AllKitties get(all_kitties): double_map (), blake2_256(Option<T::KittyIndex>) => Option<KittyLinkedItem<T, I>>;
+
+		/// Total number of shares a fractionalized kitty was split into. None means not fractionalized
+		pub KittyTotalShares get(kitty_total_shares): map T::KittyIndex => Option<u32>;
+		/// Number of shares of a fractionalized kitty held by an account
+		pub KittyShares get(kitty_shares): map (T::KittyIndex, T::AccountId) => u32;
+
+		/// Co-owners and approval threshold of a kitty. None means it has a single owner
+		pub KittyCoOwners get(kitty_co_owners): map T::KittyIndex => Option<CoOwnership<T::AccountId>>;
+		/// The action on a co-owned kitty waiting for approvals
+		pub PendingKittyActions get(pending_kitty_action): map T::KittyIndex => Option<PendingKittyActionOf<T, I>>;
+
+		/// Account allowed to breed and battle with a kitty on behalf of its owner
+		pub KittyDelegate get(kitty_delegate): map T::KittyIndex => Option<T::AccountId>;
+
+		/// Accounts notified about market changes of a kitty
+		pub KittyWatchers get(kitty_watchers): map T::KittyIndex => Vec<T::AccountId>;
+
+		/// Number of kitties owned by an account
+		pub OwnedKittiesCount get(owned_kitties_count): map T::AccountId => u64;
+		/// Number of battles won by an account
+		pub BattleWins get(battle_wins): map T::AccountId => u64;
+
+		/// Leaderboard of accounts owning the most kitties, highest first
+		pub LargestCollections get(largest_collections): Vec<(T::AccountId, u64)>;
+		/// Leaderboard of the highest sales, highest first. (kitty_id, price)
+		pub HighestSales get(highest_sales): Vec<(T::KittyIndex, BalanceOf<T, I>)>;
+		/// Leaderboard of accounts with the most battle wins, highest first
+		pub MostBattleWins get(most_battle_wins): Vec<(T::AccountId, u64)>;
+
+		/// Version of the storage layout, used to run migrations once
+		pub StorageVersion get(storage_version): u32;
+
+		/// Kitties created, changed or burned in the current block, mirrored to the indexer by the off-chain worker
+		ChangedKitties get(changed_kitties): Vec<T::KittyIndex>;
+
+		/// Last gen-0 price reported by the off-chain worker, and the block it was reported at
+		pub Gen0PriceOracle get(gen0_price_oracle): Option<(BalanceOf<T, I>, T::BlockNumber)>;
+		/// Keys allowed to sign gen-0 price reports
+		pub Gen0PriceOracles get(gen0_price_oracles): Vec<sr25519::Public>;
+		/// Last reference price quote: the balance one reference unit, e.g. a USD cent, is worth,
+		/// and the block it was quoted at
+		pub ReferencePrice get(reference_price): Option<(BalanceOf<T, I>, T::BlockNumber)>;
+		/// Keys allowed to sign reference price quotes
+		pub ReferencePriceOracles get(reference_price_oracles): Vec<sr25519::Public>;
+		/// Price in reference units of the kitties listed with `set_reference_price_listing`,
+		/// converted with the reference price when they are bought
+		pub ReferenceListings get(reference_listing): map T::KittyIndex => Option<u64>;
+		/// Number of gen-0 kitties minted while the gen-0 price was set, moves the price along the minting curve
+		pub Gen0Minted get(gen0_minted): u32;
+		/// Prices of the latest sales, newest last
+		pub RecentSales get(recent_sales): Vec<BalanceOf<T, I>>;
+
+		/// Nonce of the next transfer voucher signed by an account, before `Nonces`.
+		/// Only read until the account's next voucher moves it over
+		pub TransferNonces get(transfer_nonce): map T::AccountId => u64;
+		/// Nonce of the next off-chain signature of an account for each purpose, use `nonce_of` to read it
+		pub Nonces get(nonce): double_map T::AccountId, blake2_256(NoncePurpose) => u64;
+
+		/// Fee burned from the account breeding a kitty
+		pub BreedingFee get(breeding_fee): BalanceOf<T, I>;
+		/// Part of every sale price burned instead of paid to the seller
+		pub MarketFee get(market_fee): Permill;
+		/// Blocks, or milliseconds with `TimestampCooldowns`, a parent waits before it can breed again
+		pub BreedingCooldown get(breeding_cooldown): T::BlockNumber;
+		/// Most kitties an account can own, no limit when unset
+		pub MaxKittiesPerAccount get(max_kitties_per_account): Option<u32>;
+		/// Fee burned from the account fusing two kitties
+		pub FusionFee get(fusion_fee): BalanceOf<T, I>;
+		/// Blocks, or milliseconds with `TimestampCooldowns`, a fused kitty waits before it can breed or fuse
+		pub FusionCooldown get(fusion_cooldown): T::BlockNumber;
+		/// Most gen-0 kitties created in a block, no limit when unset
+		pub MaxMintsPerBlock get(max_mints_per_block): Option<u32>;
+		/// Most gen-0 kitties an account creates in a mint era, no limit when unset
+		pub MaxMintsPerEra get(max_mints_per_era): Option<u32>;
+		/// Blocks in a mint era, 0 for an era which never ends
+		pub MintEraLength get(mint_era_length): T::BlockNumber;
+		/// Gen-0 kitties created in this block, cleared in `on_initialize`
+		pub BlockMints get(block_mints): u32;
+		/// Index of the current mint era, bumped in `on_initialize`
+		pub MintEra get(mint_era): u32;
+		/// Creation price when no kitty exists yet
+		pub CreationBasePrice get(creation_base_price): BalanceOf<T, I>;
+		/// Creation price added for every kitty in `KittiesCount`
+		pub CreationPriceSlope get(creation_price_slope): BalanceOf<T, I>;
+		/// Part of the creation price kept in the reserve and paid back on burns
+		pub CreationReserveShare get(creation_reserve_share): Permill;
+		/// Number of live kitties backed by the reserve
+		pub ReserveBackedKitties get(reserve_backed_kitties): u32;
+		/// Whether a kitty paid into the reserve and gets a refund when burned
+		pub ReserveBacked get(is_reserve_backed): map T::KittyIndex => bool;
+		/// Reserved from the creator of a gen-0 kitty on top of its price
+		pub CreationDeposit get(creation_deposit): BalanceOf<T, I>;
+		/// Account a kitty's creation deposit is reserved from, and the deposit
+		pub KittyDeposits get(kitty_deposit): map T::KittyIndex => Option<(T::AccountId, BalanceOf<T, I>)>;
+		/// Gen-0 kitties an account created in a mint era, only the count of the current era is kept
+		pub EraMints get(era_mints): map T::AccountId => (u32, u32);
+		/// First block, or millisecond with `TimestampCooldowns`, a kitty can breed again at, unset for kitties which never bred during a cooldown
+		pub BreedingReadyAt get(breeding_ready_at): map T::KittyIndex => T::BlockNumber;
+
+		/// Kitties barred by moderation from the marketplace and from breeding
+		pub BlacklistedKitties get(is_kitty_blacklisted): map T::KittyIndex => bool;
+		/// Accounts barred by moderation from the marketplace
+		pub BarredAccounts get(is_account_barred): map T::AccountId => bool;
+
+		/// Subsystems paused by the emergency origin
+		pub PausedSubsystems get(is_paused): map Subsystem => bool;
+
+		/// Accounts with a verified breeder badge, granted by the registrar
+		pub VerifiedBreeders get(is_verified_breeder): map T::AccountId => bool;
+		/// Gen-0 kitties minted by a verified breeder
+		pub MintedByVerifiedBreeder get(is_minted_by_verified_breeder): map T::KittyIndex => bool;
+
+		/// Escrowed trades by kitty, the kitty is locked while its trade is open
+		pub EscrowedTrades get(escrowed_trade): map T::KittyIndex => Option<EscrowedTradeOf<T, I>>;
+		/// Kitties whose escrowed trade settles at a block
+		EscrowReleases get(escrow_releases): map T::BlockNumber => Vec<T::KittyIndex>;
+
+		/// Transfers scheduled by the owners, as the recipient and the block the transfer runs at.
+		/// The kitty is locked until then
+		pub ScheduledTransfers get(scheduled_transfer): map T::KittyIndex => Option<(T::AccountId, T::BlockNumber)>;
+		/// Kitties scheduled to be transferred at a block
+		ScheduledTransferIndex get(scheduled_transfer_index): map T::BlockNumber => Vec<T::KittyIndex>;
+
+		/// Heirs nominated by an account, and how many blocks it must be inactive before they can claim
+		pub Heirs get(heirs_of): map T::AccountId => Option<(Vec<T::AccountId>, T::BlockNumber)>;
+		/// Last block an account with heirs called the module
+		pub LastActive get(last_active): map T::AccountId => T::BlockNumber;
+		/// Open claims on the collection of an inactive account, as the heir and the block the claim was made at
+		pub InheritanceClaims get(inheritance_claim): map T::AccountId => Option<(T::AccountId, T::BlockNumber)>;
+		/// Last kitty an unfinished inheritance left with the owner, the next batch starts after it
+		InheritanceCursor get(inheritance_cursor): map T::AccountId => Option<T::KittyIndex>;
+
+		/// Bitmap of the `ACHIEVEMENT_*` unlocked by an account
+		pub Achievements get(achievements_of): map T::AccountId => u32;
+
+		/// Number of quests ever defined, the id of the next one
+		pub QuestsCount get(quests_count): u32;
+		/// Quests by id
+		pub Quests get(quest): map u32 => Option<QuestOf<T, I>>;
+		/// Ids of the quests which did not end yet
+		pub ActiveQuests get(active_quests): Vec<u32>;
+		/// Progress of an account on a quest
+		pub QuestProgress get(quest_progress): double_map u32, blake2_256(T::AccountId) => u32;
+		/// Whether an account claimed the reward of a quest
+		pub QuestRewardClaimed get(quest_reward_claimed): double_map u32, blake2_256(T::AccountId) => bool;
+
+		/// Number of seasons ever added, the id of the next one. Seasons follow each other without overlapping
+		pub SeasonsCount get(seasons_count): u32;
+		/// Seasons by id
+		pub Seasons get(season): map u32 => Option<SeasonOf<T>>;
+		/// Number of kitties tagged with a season
+		pub SeasonSupply get(season_supply): map u32 => u32;
+
+		/// Commitment of a created kitty whose DNA is not revealed yet
+		pub PendingReveals get(pending_reveal): map T::KittyIndex => Option<T::Hash>;
+		/// Kitties whose DNA is revealed at a block
+		pub RevealQueue get(reveal_queue): map T::BlockNumber => Vec<T::KittyIndex>;
+
+		/// Phase of the gen-0 drop
+		pub CurrentDropPhase get(drop_phase): DropPhase;
+		/// Id of the current drop, bumped each time a presale opens so mint counters start over
+		pub CurrentDrop get(current_drop): u32;
+		/// Most gen-0 kitties an account can buy in a phase of a drop, no limit when unset
+		pub DropMintLimit get(drop_mint_limit): map DropPhase => Option<u32>;
+		/// Gen-0 kitties bought by an account in a phase of a drop
+		pub DropMinted get(drop_minted): double_map (u32, DropPhase), blake2_256(T::AccountId) => u32;
+		/// Accounts allowed to buy during the presale
+		pub PresaleWhitelist get(is_presale_whitelisted): map T::AccountId => bool;
+		/// Kitties whose owner can buy during the presale
+		pub MintPasses get(is_mint_pass): map T::KittyIndex => bool;
+
+		/// Mint-pass tokens held by an account, each redeemable for a gen-0 kitty
+		pub MintPassTokens get(mint_pass_tokens): map T::AccountId => u32;
+
+		/// Highest price the market treasury buys kitties back at
+		pub BuybackFloor get(buyback_floor): BalanceOf<T, I>;
+		/// Last kitty the previous buyback looked at, `None` to start from the first one
+		pub BuybackCursor get(buyback_cursor): Option<T::KittyIndex>;
+
+		/// Account receiving the donations of sales, donations are off while unset
+		pub CharityAccount get(charity_account): Option<T::AccountId>;
+		/// Part of the seller's proceeds donated to the charity when a listed kitty sells
+		pub ListingDonation get(listing_donation): map T::KittyIndex => Option<Permill>;
+		/// Total donated by an account through its sales
+		pub Donated get(donated_by): map T::AccountId => BalanceOf<T, I>;
+		/// Accounts which donated the most, highest first
+		pub TopDonors get(top_donors): Vec<(T::AccountId, BalanceOf<T, I>)>;
+
+		/// Total tipped to the owners of a kitty
+		pub KittyTips get(tips_of): map T::KittyIndex => BalanceOf<T, I>;
+
+		/// Fee burned for each block a listing is featured
+		pub FeaturedListingFee get(featured_listing_fee): BalanceOf<T, I>;
+		/// Promoted listings with the block their feature ends at, expired and delisted ones are
+		/// dropped in `on_initialize`
+		pub FeaturedListings get(featured_listings): Vec<(T::KittyIndex, T::BlockNumber)>;
+
+		/// Magnitudes of the prices of the kitties for sale, lowest first, see `price_range`
+		pub ListedPriceRanges get(listed_price_ranges): Vec<u32>;
+		/// Distinct prices of the kitties for sale within a magnitude, lowest first. Only the range of
+		/// a price is rewritten when a listing changes
+		pub ListedPricesInRange get(listed_prices_in_range): map u32 => Vec<BalanceOf<T, I>>;
+		/// Kitties for sale at a price, a bucket of the price index
+		pub ListingsAtPrice get(listings_at_price): map BalanceOf<T, I> => Vec<T::KittyIndex>;
+
+		/// Kitties with a trait value, at most `MAX_TRAIT_BUCKET_SIZE` of them in creation order
+		pub KittiesWithTrait get(kitties_with_trait): map (TraitKind, u8) => Vec<T::KittyIndex>;
+		/// Number of live kitties with a trait value, unlike the buckets of `KittiesWithTrait` it has no cap
+		pub KittiesPerTrait get(kitties_per_trait): map (TraitKind, u8) => u64;
+
+		/// Number of live kitties of each generation
+		pub KittiesPerGeneration get(kitties_per_generation): map u32 => u64;
+
+		/// Owners of a kitty, oldest first. Only changes of owner since the record was added are in it
+		pub Provenance get(provenance_of): map T::KittyIndex => Vec<ProvenanceRecordOf<T>>;
+
+		/// Number of kitties exported, the id of the next export
+		pub ExportsCount get(exports_count): u64;
+		/// Hash of each export
+		pub ExportCommitments get(export_commitment): map u64 => Option<T::Hash>;
+		/// Exports of other chains already imported, by source chain and export id
+		pub ImportedExports get(is_export_imported): map (u32, u64) => bool;
+
+		pub NamingFee get(naming_fee): BalanceOf<T, I>;
+		/// Name of each named kitty, unique among kitties
+		pub KittyNames get(name_of): map T::KittyIndex => Option<Vec<u8>>;
+		pub NameToKitty get(kitty_by_name): map Vec<u8> => Option<T::KittyIndex>;
+		pub MaxNameLength get(max_name_length): Option<u32>;
+		pub MaxMetadataLength get(max_metadata_length): Option<u32>;
+		/// Names may not start with these prefixes, ignoring ASCII case
+		pub BannedNamePrefixes get(banned_name_prefixes): Vec<Vec<u8>>;
+		/// UTF-8 metadata of a kitty, e.g. a description or a link
+		pub KittyMetadata get(metadata_of): map T::KittyIndex => Option<Vec<u8>>;
+
+		/// Vote weight of an account at its last snapshot, dropped when one of its kitties leaves
+		pub VoteWeights get(vote_weight_of): map T::AccountId => u64;
+
+		pub CooldownBoostFee get(cooldown_boost_fee): BalanceOf<T, I>;
+
+		/// Decay of the listings which are Dutch auctions, the listed price is the start price.
+		/// Any change of price or owner drops it
+		pub PriceDecays get(price_decay): map T::KittyIndex => Option<PriceDecay<BalanceOf<T, I>, T::BlockNumber>>;
+
+		/// Royalty of a kitty, set by the account which minted or bred it
+		pub Royalties get(royalty_of): map T::KittyIndex => Option<Royalty<T::AccountId>>;
+
+		pub SaleBurnRate get(sale_burn_rate): Permill;
+		/// Total burned by `SaleBurnRate`
+		pub TotalSaleBurned get(total_sale_burned): BalanceOf<T, I>;
+
+		/// Loyalty points of an account from its trades, they can't be transferred
+		pub LoyaltyPoints get(loyalty_points): map T::AccountId => u64;
+		pub LoyaltyPointValue get(loyalty_point_value): BalanceOf<T, I>;
+		/// Market fee an account no longer pays on its next sales, from redeemed loyalty points
+		pub MarketFeeCredit get(market_fee_credit): map T::AccountId => BalanceOf<T, I>;
+
+		/// Gift vouchers by the hash of their claim code
+		pub Vouchers get(voucher): map T::Hash => Option<Voucher<T::AccountId, BalanceOf<T, I>, T::BlockNumber>>;
+		/// Voucher claim committed by an account, as `voucher_claim_hash` and the block it was committed at
+		pub VoucherClaims get(voucher_claim): map T::AccountId => Option<(T::Hash, T::BlockNumber)>;
+
+		/// Number of over-the-counter trades opened, the id of the next one
+		pub OtcTradesCount get(otc_trades_count): u64;
+		pub OtcTrades get(otc_trade): map u64 => Option<OtcTradeOf<T, I>>;
+		/// Reserved from the account opening a trade
+		pub TradeDeposit get(trade_deposit): BalanceOf<T, I>;
+		/// Deposit reserved from the proposer of each open trade, returned when it is executed or cancelled
+		pub TradeDeposits get(trade_deposit_of): map u64 => BalanceOf<T, I>;
+
+		/// Index tokens of each account, one is minted for every kitty wrapped into the pool
+		pub IndexTokens get(index_tokens): map T::AccountId => u64;
+		pub IndexTokenSupply get(index_token_supply): u64;
+
+		pub CleanupReward get(cleanup_reward): BalanceOf<T, I>;
+
+		/// Ring buffer of the last `AUDIT_LOG_SIZE` privileged calls, at their number modulo the size
+		AuditLog get(audit_log_entry): map u32 => Option<AuditEntryOf<T>>;
+		/// Number of privileged calls ever recorded
+		pub AuditLogCount get(audit_log_count): u64;
+
+		/// Registered species, `KITTY_SPECIES` is not stored
+		pub SpeciesRegistry get(registered_species): map SpeciesId => Option<Species>;
+		/// Number of registered species, ids start at 1
+		pub SpeciesCount get(species_count): SpeciesId;
+
+		/// The sponsorship of an account's kitty operations
+		pub Sponsorships get(sponsorship): map T::AccountId => Option<SponsorshipOf<T, I>>;
+		pub SponsoredCallFee get(sponsored_call_fee): BalanceOf<T, I>;
+
+		/// Sales of the current market era
+		pub MarketStats get(market_stats): MarketEraStats<BalanceOf<T, I>>;
+		pub MarketVolumeLimit get(market_volume_limit): Option<BalanceOf<T, I>>;
+		pub MarketDeviationLimit get(market_deviation_limit): Option<Permill>;
+
+		/// Most kitty calls an account makes in a block
+		pub MaxOperationsPerBlock get(max_operations_per_block): Option<u32>;
+		/// Kitty calls of an account in the current block, while `MaxOperationsPerBlock` is set
+		pub BlockOperations get(block_operations): map T::AccountId => u32;
+		/// Accounts with `BlockOperations`, cleared at the next block
+		BlockOperators get(block_operators): Vec<T::AccountId>;
+	}
+}
+
+decl_event!(
+	pub enum Event<T, I: Instance = DefaultInstance> where
+		<T as system::Trait>::AccountId,
+		<T as system::Trait>::BlockNumber,
+		<T as Trait<I>>::KittyIndex,
+		Balance = BalanceOf<T, I>,
+	{
+		/// A kitty is created or bred. (owner, kitty_id)
+		Created(AccountId, KittyIndex),
+		/// A kitty changed owner. (from, to, kitty_id)
+		Transferred(AccountId, AccountId, KittyIndex),
+		/// A kitty is sold. (seller, buyer, kitty_id, price)
+		Sold(AccountId, AccountId, KittyIndex, Balance),
+		/// A watched kitty's price changed, 0 means delisted. (watcher, kitty_id, price)
+		WatchedKittyListed(AccountId, KittyIndex, Balance),
+		/// A watched kitty is sold. (watcher, kitty_id, price)
+		WatchedKittySold(AccountId, KittyIndex, Balance),
+		/// Moderation blacklisted or cleared a kitty. (kitty_id, blacklisted)
+		KittyBlacklisted(KittyIndex, bool),
+		/// Moderation barred an account from the marketplace or cleared it. (account, barred)
+		AccountBarred(AccountId, bool),
+		/// A subsystem is paused or resumed. (subsystem, paused)
+		SubsystemPaused(Subsystem, bool),
+		/// The registrar granted or revoked a verified breeder badge. (account, verified)
+		BreederVerified(AccountId, bool),
+		/// A kitty's price changed, 0 means delisted. (seller, kitty_id, price, seller is a verified breeder)
+		Listed(AccountId, KittyIndex, Balance, bool),
+		/// A sale is held in escrow until the block it settles at. (seller, buyer, kitty_id, price, release_at)
+		TradeEscrowed(AccountId, AccountId, KittyIndex, Balance, BlockNumber),
+		/// A party disputed an escrowed trade. (kitty_id, account)
+		TradeDisputed(KittyIndex, AccountId),
+		/// An escrowed trade is closed, at the end of its window or by the arbiter. (kitty_id, sold to the buyer)
+		TradeReleased(KittyIndex, bool),
+		/// A transfer is scheduled. (from, to, kitty_id, at)
+		TransferScheduled(AccountId, AccountId, KittyIndex, BlockNumber),
+		/// A scheduled transfer is cancelled by the owner. (kitty_id)
+		ScheduledTransferCancelled(KittyIndex),
+		/// A scheduled transfer ran, or was called off when no longer allowed. (kitty_id, transferred)
+		ScheduledTransferExecuted(KittyIndex, bool),
+		/// An account nominated its heirs, none to drop them. (account)
+		HeirsSet(AccountId),
+		/// An heir claimed the collection of an inactive account. (owner, heir)
+		InheritanceClaimed(AccountId, AccountId),
+		/// The owner vetoed the claim on its collection. (owner)
+		InheritanceVetoed(AccountId),
+		/// Kitties of an inactive account moved to its heir. (owner, heir, count)
+		KittiesInherited(AccountId, AccountId, u32),
+		/// An account unlocked an achievement. (account, achievement bit)
+		AchievementUnlocked(AccountId, u32),
+		/// Governance added a quest. (quest_id)
+		QuestAdded(u32),
+		/// An account claimed the reward of a completed quest. (quest_id, account)
+		QuestRewardPaid(u32, AccountId),
+		/// Funds were added to the quest pool. (funder, amount)
+		QuestPoolFunded(AccountId, Balance),
+		/// Governance added a season. (season_id)
+		SeasonAdded(u32),
+		/// Two kitties were burned into a new one. (owner, kitty_id_1, kitty_id_2, fused_kitty_id)
+		Fused(AccountId, KittyIndex, KittyIndex, KittyIndex),
+		/// The DNA of a created kitty was revealed. (kitty_id)
+		KittyRevealed(KittyIndex),
+		/// The gen-0 drop entered a phase. (drop, phase)
+		DropPhaseChanged(u32, DropPhase),
+		/// An account was added to or removed from the presale whitelist. (account, whitelisted)
+		PresaleWhitelisted(AccountId, bool),
+		/// A kitty became or stopped being a mint pass. (kitty_id, mint_pass)
+		MintPassSet(KittyIndex, bool),
+		/// Governance issued mint-pass tokens. (account, count)
+		MintPassesIssued(AccountId, u32),
+		/// Mint-pass tokens changed hands. (from, to, count)
+		MintPassesTransferred(AccountId, AccountId, u32),
+		/// A mint-pass token was burned for a gen-0 kitty. (account, kitty_id)
+		MintPassRedeemed(AccountId, KittyIndex),
+		/// A burned kitty's share of the creation reserve was paid out. (owner, kitty_id, refund)
+		CreationRefunded(AccountId, KittyIndex, Balance),
+		/// A burned kitty's creation deposit was paid to its owner. (owner, kitty_id, deposit)
+		CreationDepositReturned(AccountId, KittyIndex, Balance),
+		/// The market treasury bought a kitty and burned it. (seller, kitty_id, price)
+		BoughtBack(AccountId, KittyIndex, Balance),
+		/// Governance set or cleared the charity account. (account)
+		CharityAccountSet(Option<AccountId>),
+		/// A seller opted a listing into a donation or out of it. (kitty_id, share)
+		ListingDonationSet(KittyIndex, Option<Permill>),
+		/// A sale donated part of its proceeds to the charity. (seller, charity, kitty_id, amount)
+		Donated(AccountId, AccountId, KittyIndex, Balance),
+		/// An account tipped the owner of a kitty. (tipper, owner, kitty_id, amount)
+		Tipped(AccountId, AccountId, KittyIndex, Balance),
+		/// A listing is featured until a block. (seller, kitty_id, until, fee)
+		ListingFeatured(AccountId, KittyIndex, BlockNumber, Balance),
+		/// A kitty was transferred with a memo for the recipient, the memo is not stored. (from, to, kitty_id, memo)
+		TransferredWithMemo(AccountId, AccountId, KittyIndex, Vec<u8>),
+		/// A kitty was burned to leave the chain. (owner, kitty_id, export_id, commitment)
+		KittyExported(AccountId, KittyIndex, u64, Hash),
+		/// A kitty exported by another chain was minted. (recipient, kitty_id, source_chain, export_id)
+		KittyImported(AccountId, KittyIndex, u32, u64),
+		/// A kitty was named, an empty name clears it. (owner, kitty_id, name)
+		KittyNamed(AccountId, KittyIndex, Vec<u8>),
+		/// The metadata of a kitty changed, empty metadata clears it. (owner, kitty_id)
+		KittyMetadataSet(AccountId, KittyIndex),
+		/// The vote weight of an account was snapshotted. (account, weight)
+		VoteWeightSnapshotted(AccountId, u64),
+		/// A pair of a batch was bred. (owner, kitty_id_1, kitty_id_2, child)
+		BredInBatch(AccountId, KittyIndex, KittyIndex, KittyIndex),
+		/// A pair of a batch could not breed, the batch went on. (owner, kitty_id_1, kitty_id_2, reason)
+		BreedInBatchFailed(AccountId, KittyIndex, KittyIndex, Vec<u8>),
+		/// The breeding cooldown of a kitty was skipped. (owner, kitty_id, fee)
+		CooldownBoosted(AccountId, KittyIndex, Balance),
+		/// A kitty was listed with a falling price. (owner, kitty_id, start_price, end_price, end)
+		ListedWithDecay(AccountId, KittyIndex, Balance, Balance, BlockNumber),
+		/// A royalty was paid on a sale. (beneficiary, kitty_id, amount)
+		RoyaltyPaid(AccountId, KittyIndex, Balance),
+		/// Loyalty points were redeemed for market fee credit. (account, points, credit)
+		LoyaltyPointsRedeemed(AccountId, u64, Balance),
+		/// A gift voucher was bought. (buyer, code_hash, amount, expires_at)
+		VoucherBought(AccountId, Hash, Balance, BlockNumber),
+		/// A gift voucher was redeemed, with the kitty bought if one could be. (recipient, code_hash, kitty_id)
+		VoucherRedeemed(AccountId, Hash, Option<KittyIndex>),
+		/// The funds of a redeemed voucher could not buy a kitty and stay with the recipient. (recipient, code_hash, error)
+		VoucherPurchaseFailed(AccountId, Hash, Vec<u8>),
+		/// An expired gift voucher was refunded to its buyer. (buyer, code_hash)
+		VoucherRefunded(AccountId, Hash),
+		/// An over-the-counter trade was opened. (trade_id, proposer, counterparty)
+		TradeOpened(u64, AccountId, AccountId),
+		/// A side of a trade changed its offer, the confirmations are reset. (trade_id, account)
+		TradeOfferChanged(u64, AccountId),
+		/// A side of a trade confirmed it. (trade_id, account)
+		TradeConfirmed(u64, AccountId),
+		/// Both sides confirmed a trade and it was executed. (trade_id)
+		TradeExecuted(u64),
+		/// A trade was withdrawn before it was executed. (trade_id, account)
+		TradeCancelled(u64, AccountId),
+		/// A kitty was wrapped into the index pool for a token. (account, kitty_id)
+		KittyWrapped(AccountId, KittyIndex),
+		/// A token was redeemed for a kitty of the index pool. (account, kitty_id)
+		KittyUnwrapped(AccountId, KittyIndex),
+		/// Index tokens were transferred. (from, to, amount)
+		IndexTokensTransferred(AccountId, AccountId, u64),
+		/// The maintenance pot is funded. (account, amount)
+		MaintenancePotFunded(AccountId, Balance),
+		/// The stale records of a kitty which no longer exists are removed. (caller, kitty_id, reward)
+		KittyCleanedUp(AccountId, KittyIndex, Balance),
+		/// Stale entries are removed from the list of an account. (caller, account, entries, reward)
+		AccountCleanedUp(AccountId, AccountId, u32, Balance),
+		/// A Dutch auction which ended is settled, the kitty stays listed at the end price. (kitty_id, price)
+		AuctionSettled(KittyIndex, Balance),
+		/// A species is registered. (species_id)
+		SpeciesRegistered(SpeciesId),
+		/// A sponsor pays for an account's next kitty operations. (sponsor, account, operations)
+		Sponsored(AccountId, AccountId, u32),
+		/// A sponsorship ended, the fees left are unreserved. (sponsor, account)
+		SponsorshipEnded(AccountId, AccountId),
+		/// An account withdrew all its signed offers, vouchers and sponsored calls. (account)
+		OrdersCancelled(AccountId),
+		/// A kitty is listed at a price in reference units. (owner, kitty_id, reference_price)
+		ListedAtReferencePrice(AccountId, KittyIndex, u64),
+		/// The circuit breaker paused settlement. (era volume, abnormal sales of the era)
+		MarketCircuitBroken(Balance, u32),
+	}
+);
+
+decl_module! {
+	pub struct Module<T: Trait<I>, I: Instance = DefaultInstance> for enum Call where origin: T::Origin {
+		// Weights are parts of the 1_000_000 `MaximumBlockWeight` of the runtime,
+		// sized after the benchmarks in `kitties/benchmarking.rs`.
+		fn deposit_event<T, I>() = default;
+
+		/// Highest kitty index, so clients learn the width of `KittyIndex` from the metadata
+		const MaxKittyIndex: T::KittyIndex = T::KittyIndex::max_value();
+
+		// The limits of the runtime, for wallets and dapps to read from the metadata.
+		// `module_constants` returns the same values to runtime code.
+		const DisputeWindow: T::BlockNumber = T::DisputeWindow::get();
+		const RevealDelay: T::BlockNumber = T::RevealDelay::get();
+		const TimestampCooldowns: bool = T::TimestampCooldowns::get();
+		const MaxBreedBatch: u32 = T::MaxBreedBatch::get();
+		const MaxPriceBatch: u32 = T::MaxPriceBatch::get();
+		const MaxWatchersPerKitty: u32 = T::MaxWatchersPerKitty::get();
+		const MaxHeirs: u32 = T::MaxHeirs::get();
+		const MaxRoyaltyBeneficiaries: u32 = T::MaxRoyaltyBeneficiaries::get();
+		const MaxRoyaltyPercent: u32 = T::MaxRoyaltyPercent::get();
+		const LoyaltyPointsPerTrade: u64 = T::LoyaltyPointsPerTrade::get();
+		const MaxTradeKitties: u32 = T::MaxTradeKitties::get();
+		const MaxPageSize: u32 = MAX_PAGE_SIZE;
+
+		fn on_initialize(n: T::BlockNumber) {
+			Self::migrate_storage();
+			<ChangedKitties<T, I>>::kill();
+			Self::release_escrowed_trades(n);
+			Self::execute_scheduled_transfers(n);
+			Self::reveal_kitties(n);
+			Self::roll_mint_counters(n);
+			Self::clear_block_operations();
+			Self::prune_featured_listings(n);
+		}
+
+		fn on_finalize(_n: T::BlockNumber) {
+			if cfg!(feature = "try-state") {
+				Self::do_try_state().expect("The kitties storage is inconsistent");
+			}
+		}
+
+		fn offchain_worker(now: T::BlockNumber) {
+			Self::mirror_to_indexer(now);
+			Self::report_gen0_price(now);
+			Self::report_garbage();
+		}
+
+		/// Create a new kitty
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn create(origin) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_buy_gen0(&sender, None, KITTY_SPECIES).map(|_| ())
+		}
+
+		/// Buy a gen-0 kitty from the drop, `mint_pass` is a mint-pass kitty of the sender for the presale
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn buy_gen0(origin, mint_pass: Option<T::KittyIndex>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_buy_gen0(&sender, mint_pass, KITTY_SPECIES).map(|_| ())
+		}
+
+		/// Move the gen-0 drop to a phase with its per-account limit, opening a presale starts a new drop
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_drop_phase(origin, phase: DropPhase, limit: Option<u32>) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_drop_phase(phase, limit))?;
+			let mut drop = Self::current_drop();
+			if phase == DropPhase::Presale && Self::drop_phase() == DropPhase::Public {
+				drop = drop.checked_add(1).ok_or("Drops count overflow")?;
+				<CurrentDrop<I>>::put(drop);
+			}
+
+			match limit {
+				Some(limit) => <DropMintLimit<I>>::insert(phase, limit),
+				None => <DropMintLimit<I>>::remove(phase),
+			}
+			<CurrentDropPhase<I>>::put(phase);
+			Self::deposit_event(RawEvent::DropPhaseChanged(drop, phase));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Add an account to the presale whitelist or remove it
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_presale_whitelisted(origin, who: T::AccountId, whitelisted: bool) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_presale_whitelisted(who.clone(), whitelisted))?;
+			if whitelisted {
+				<PresaleWhitelist<T, I>>::insert(&who, true);
+			} else {
+				<PresaleWhitelist<T, I>>::remove(&who);
+			}
+			Self::deposit_event(RawEvent::PresaleWhitelisted(who, whitelisted));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Issue mint-pass tokens to an account
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn issue_mint_passes(origin, to: T::AccountId, count: u32) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::issue_mint_passes(to.clone(), count))?;
+			let tokens = Self::mint_pass_tokens(&to).checked_add(count).ok_or("Mint-pass tokens overflow")?;
+			<MintPassTokens<T, I>>::insert(&to, tokens);
+			Self::deposit_event(RawEvent::MintPassesIssued(to, count));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Give mint-pass tokens to another account
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn transfer_mint_passes(origin, to: T::AccountId, count: u32) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(to != sender, "You can not transfer to yourself");
+			let remaining = Self::mint_pass_tokens(&sender).checked_sub(count).ok_or("Not enough mint-pass tokens")?;
+			let received = Self::mint_pass_tokens(&to).checked_add(count).ok_or("Mint-pass tokens overflow")?;
+
+			<MintPassTokens<T, I>>::insert(&sender, remaining);
+			<MintPassTokens<T, I>>::insert(&to, received);
+			Self::deposit_event(RawEvent::MintPassesTransferred(sender, to, count));
+			Ok(())
+		}
+
+		/// Burn a mint-pass token for a gen-0 kitty, outside the drop phases and free of the gen-0 price
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn redeem_pass(origin) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let tokens = Self::mint_pass_tokens(&sender);
+			ensure!(tokens > 0, "Not enough mint-pass tokens");
+
+			let kitty_id = Self::create_gen0(&sender, false, KITTY_SPECIES)?;
+			<MintPassTokens<T, I>>::insert(&sender, tokens - 1);
+			Self::deposit_event(RawEvent::MintPassRedeemed(sender, kitty_id));
+			Ok(())
+		}
+
+		/// Make a kitty a mint pass for the presale or clear it
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_mint_pass(origin, kitty_id: T::KittyIndex, mint_pass: bool) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_mint_pass(kitty_id, mint_pass))?;
+			ensure!(<Kitties<T, I>>::exists(kitty_id), "This cat does not exist");
+			if mint_pass {
+				<MintPasses<T, I>>::insert(kitty_id, true);
+			} else {
+				<MintPasses<T, I>>::remove(kitty_id);
+			}
+			Self::deposit_event(RawEvent::MintPassSet(kitty_id, mint_pass));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Breed kitties
+		#[weight = SimpleDispatchInfo::FixedNormal(80_000)]
+		pub fn breed(origin, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id_1)?;
+			Self::ensure_not_co_owned(kitty_id_2)?;
+
+			Self::do_breed(&sender, kitty_id_1, kitty_id_2)?;
+			Ok(())
+		}
+
+		/// End the breeding cooldown of a kitty now, paying for each block left
+		#[weight = SimpleDispatchInfo::FixedNormal(30_000)]
+		pub fn boost_cooldown(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+			Self::do_boost_cooldown(&sender, kitty_id)
+		}
+
+		/// Breed each pair of kitties, a pair which can't breed is reported and skipped
+		#[weight = BatchWeight::<T::MaxBreedBatch>::new(80_000)]
+		pub fn breed_batch(origin, pairs: Vec<(T::KittyIndex, T::KittyIndex)>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(!pairs.is_empty(), "The batch is empty");
+			ensure!(pairs.len() <= T::MaxBreedBatch::get() as usize, "Too many pairs in the batch");
+
+			for (kitty_id_1, kitty_id_2) in pairs {
+				let bred = Self::ensure_not_co_owned(kitty_id_1)
+					.and_then(|_| Self::ensure_not_co_owned(kitty_id_2))
+					.and_then(|_| Self::do_breed(&sender, kitty_id_1, kitty_id_2));
+				match bred {
+					Ok(child) => Self::deposit_event(RawEvent::BredInBatch(sender.clone(), kitty_id_1, kitty_id_2, child)),
+					Err(reason) => Self::deposit_event(
+						RawEvent::BreedInBatchFailed(sender.clone(), kitty_id_1, kitty_id_2, reason.as_bytes().to_vec())
+					),
+				}
+			}
+			Ok(())
+		}
+
+		/// Burn two kitties of the sender into a new one of the next generation
+		#[weight = SimpleDispatchInfo::FixedNormal(80_000)]
+		pub fn fuse(origin, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id_1)?;
+			Self::ensure_not_co_owned(kitty_id_2)?;
+
+			Self::do_fuse(&sender, kitty_id_1, kitty_id_2).map(|_| ())
+		}
+
+		/// Donate `share` of the proceeds of a listed kitty to the charity when it sells, `None` to opt out.
+		/// The choice lasts until the kitty is sold, delisted or changes hands
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn set_listing_donation(origin, kitty_id: T::KittyIndex, share: Option<Permill>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let kitty = Self::kitty(kitty_id).ok_or("This cat does not exist")?;
+			ensure!(Self::owner_of(kitty_id).as_ref() == Some(&sender), "You do not own this cat");
+			ensure!(!kitty.price.is_zero(), "This cat is not for sale");
+
+			match share {
+				Some(share) => {
+					let charity = Self::charity_account().ok_or("No charity account is configured")?;
+					ensure!(charity != sender, "The charity can not donate to itself");
+					<ListingDonation<T, I>>::insert(kitty_id, share);
+				}
+				None => <ListingDonation<T, I>>::remove(kitty_id),
+			}
+			Self::deposit_event(RawEvent::ListingDonationSet(kitty_id, share));
+			Ok(())
+		}
+
+		/// Give `amount` to the current owner of a kitty
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn tip(origin, kitty_id: T::KittyIndex, amount: BalanceOf<T, I>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_tip(&sender, kitty_id, amount)
+		}
+
+		/// Feature a listed kitty for `duration` blocks, burning the featured listing fee for each of them.
+		/// Featuring an already featured kitty extends it
+		#[weight = SimpleDispatchInfo::FixedNormal(30_000)]
+		pub fn feature_listing(origin, kitty_id: T::KittyIndex, duration: T::BlockNumber) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_feature_listing(&sender, kitty_id, duration)
+		}
+
+		// 作业：实现 transfer(origin, to: T::AccountId, kitty_id: T::KittyIndex)
+		// 使用 ensure! 来保证只有主人才有权限调用 transfer
+		// 使用 OwnedKitties::append 和 OwnedKitties::remove 来修改小猫的主人
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn transfer(origin, to: T::AccountId, kitty_id: T::KittyIndex) -> Result {
+            let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+
+			Self::do_transfer(&sender, to, kitty_id)
+		}
+
+		/// Burn a kitty to move it to another chain, committing to its DNA and generation
+		#[weight = SimpleDispatchInfo::FixedNormal(60_000)]
+		pub fn export_kitty(origin, kitty_id: T::KittyIndex, target_chain_account: Vec<u8>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+			Self::do_export_kitty(&sender, kitty_id, target_chain_account)
+		}
+
+		/// Mint a kitty exported by `source_chain` with its original DNA and generation
+		#[weight = SimpleDispatchInfo::FixedOperational(60_000)]
+		pub fn import_kitty(origin, source_chain: u32, export: KittyExport) -> Result {
+			let audit = Self::ensure_privileged::<T::BridgeOrigin>(origin, Call::import_kitty(source_chain, export.clone()))?;
+			Self::do_import_kitty(source_chain, export)?;
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Give a kitty a name no other kitty has, an empty name clears it
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn set_name(origin, kitty_id: T::KittyIndex, name: Vec<u8>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_set_name(&sender, kitty_id, name)
+		}
+
+		/// Set the metadata of a kitty, empty metadata clears it
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn set_metadata(origin, kitty_id: T::KittyIndex, metadata: Vec<u8>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_set_metadata(&sender, kitty_id, metadata)
+		}
+
+		/// Replace the prefixes banned from kitty names. Existing names are kept
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_banned_name_prefixes(origin, prefixes: Vec<Vec<u8>>) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_banned_name_prefixes(prefixes.clone()))?;
+			ensure!(prefixes.len() <= MAX_BANNED_NAME_PREFIXES, "Too many banned prefixes");
+			ensure!(prefixes.iter().all(|prefix| !prefix.is_empty() && prefix.len() <= DEFAULT_MAX_NAME_LENGTH as usize), "Invalid banned prefix");
+			<BannedNamePrefixes<I>>::put(prefixes);
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Snapshot your vote weight from your kitties. The weight of a rare trait changes as kitties are
+		/// born and burned, so only the account itself picks when it is taken; it can take it again at any
+		/// time, and it is dropped when one of its kitties leaves
+		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
+		pub fn snapshot_vote_weight(origin) -> Result {
+			let who = ensure_signed(origin)?;
+			let weight = Self::compute_vote_weight(&who);
+			<VoteWeights<T, I>>::insert(&who, weight);
+			Self::deposit_event(RawEvent::VoteWeightSnapshotted(who, weight));
+			Ok(())
+		}
+
+		/// Transfer a kitty with a memo, which only goes into the event, e.g. for exchanges to match deposits
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn transfer_with_memo(origin, to: T::AccountId, kitty_id: T::KittyIndex, memo: Vec<u8>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(memo.len() <= MAX_MEMO_LENGTH, "The memo is too long");
+			Self::ensure_not_co_owned(kitty_id)?;
+
+			Self::do_transfer(&sender, to.clone(), kitty_id)?;
+			Self::deposit_kitty_event(kitty_id, &[&sender, &to], RawEvent::TransferredWithMemo(sender.clone(), to.clone(), kitty_id, memo));
+			Ok(())
+		}
+
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		pub fn buy_kitty(origin, kitty_id: T::KittyIndex, max_price: BalanceOf<T, I>) -> Result{
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_buy_kitty(&sender, kitty_id, max_price)
+		}
+
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn set_price(origin, kitty_id: T::KittyIndex, price : BalanceOf<T, I>) -> Result{
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+			Self::do_set_price(&sender, kitty_id, price)
+		}
+
+		/// List a kitty at `start_price` falling linearly to `end_price` at block `end`, then staying there
+		#[weight = SimpleDispatchInfo::FixedNormal(60_000)]
+		pub fn set_decaying_price(
+			origin,
+			kitty_id: T::KittyIndex,
+			start_price: BalanceOf<T, I>,
+			end_price: BalanceOf<T, I>,
+			end: T::BlockNumber
+		) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+			ensure!(!end_price.is_zero() && end_price < start_price, "The price must fall to a non-zero price");
+			let now = <system::Module<T>>::block_number();
+			ensure!(end > now, "The decay must end in the future");
+
+			Self::do_set_price(&sender, kitty_id, start_price)?;
+			<PriceDecays<T, I>>::insert(kitty_id, PriceDecay { end_price, start: now, end });
+			Self::deposit_kitty_event(kitty_id, &[&sender], RawEvent::ListedWithDecay(sender.clone(), kitty_id, start_price, end_price, end));
+			Ok(())
+		}
+
+		/// Set the royalty of a kitty you minted or bred, no splits removes it
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn set_royalty(origin, kitty_id: T::KittyIndex, rate: Permill, splits: Vec<(T::AccountId, u32)>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let creator = Self::provenance_of(kitty_id).first().map(|record| record.owner.clone());
+			ensure!(creator.as_ref() == Some(&sender), "Only the creator of this cat can set its royalty");
+
+			if splits.is_empty() {
+				<Royalties<T, I>>::remove(kitty_id);
+				return Ok(());
+			}
+			ensure!(splits.len() <= T::MaxRoyaltyBeneficiaries::get() as usize, "Too many royalty beneficiaries");
+			ensure!(rate <= Permill::from_percent(T::MaxRoyaltyPercent::get()), "The royalty rate is too high");
+			ensure!(splits.iter().all(|(_, share)| *share > 0), "A royalty share is zero");
+			ensure!(splits.iter().all(|(_, share)| *share <= 1000), "A royalty share is over 1000");
+			ensure!(splits.iter().map(|(_, share)| u64::from(*share)).sum::<u64>() == 1000, "The royalty shares must sum to 1000");
+			<Royalties<T, I>>::insert(kitty_id, Royalty { rate, splits });
+			Ok(())
+		}
+
+		/// Turn loyalty points into credit waiving the market fee of your next sales
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn redeem_loyalty_points(origin, points: u64) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(points > 0, "No points to redeem");
+			let balance = Self::loyalty_points(&sender);
+			ensure!(points <= balance, "Not enough loyalty points");
+			let value = Self::loyalty_point_value();
+			ensure!(!value.is_zero(), "Loyalty points can not be redeemed");
+
+			let points_as_balance: u32 = points.unique_saturated_into();
+			let credit = value.saturating_mul(points_as_balance.into());
+			<LoyaltyPoints<T, I>>::insert(&sender, balance - points);
+			<MarketFeeCredit<T, I>>::mutate(&sender, |total| *total = total.saturating_add(credit));
+			Self::deposit_event(RawEvent::LoyaltyPointsRedeemed(sender, points, credit));
+			Ok(())
+		}
+
+		/// Wrap a kitty into the index pool for one index token. Kitties in the pool are interchangeable,
+		/// so only wrap kitties worth the floor price
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn wrap_kitty(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_wrap_kitty(&sender, kitty_id)
+		}
+
+		/// Burn one index token for any kitty of the index pool
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn unwrap_kitty(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_unwrap_kitty(&sender, kitty_id)
+		}
+
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn transfer_index_tokens(origin, to: T::AccountId, amount: u64) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let sender_tokens = Self::index_tokens(&sender).checked_sub(amount).ok_or("Not enough index tokens")?;
+			if sender == to {
+				return Ok(());
+			}
+			let to_tokens = Self::index_tokens(&to).checked_add(amount).ok_or("Index tokens overflow")?;
+
+			<IndexTokens<T, I>>::insert(&sender, sender_tokens);
+			<IndexTokens<T, I>>::insert(&to, to_tokens);
+			Self::deposit_event(RawEvent::IndexTokensTransferred(sender, to, amount));
+			Ok(())
+		}
+
+		/// Open an over-the-counter trade with `counterparty`, both sides start empty.
+		/// The `TradeDeposit` is reserved until the trade is executed or cancelled
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn open_trade(origin, counterparty: T::AccountId) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(sender != counterparty, "You can't trade with yourself");
+			Self::ensure_not_barred(&sender)?;
+			Self::ensure_not_barred(&counterparty)?;
+			let trade_id = Self::otc_trades_count();
+			let next_trade_id = trade_id.checked_add(1).ok_or("Trades count overflow")?;
+			let deposit = Self::trade_deposit();
+			T::Currency::reserve(&sender, deposit)?;
+
+			let side = |account| TradeSide { account, kitties: Vec::new(), balance: Zero::zero(), confirmed: false };
+			<OtcTrades<T, I>>::insert(trade_id, OtcTrade { proposer: side(sender.clone()), counterparty: side(counterparty.clone()) });
+			<TradeDeposits<T, I>>::insert(trade_id, deposit);
+			<OtcTradesCount<I>>::put(next_trade_id);
+			Self::deposit_event(RawEvent::TradeOpened(trade_id, sender, counterparty));
+			Ok(())
+		}
+
+		/// Set the kitties and balance you give in a trade, both sides have to confirm again
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn set_trade_offer(origin, trade_id: u64, kitties: Vec<T::KittyIndex>, balance: BalanceOf<T, I>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let mut trade = Self::otc_trade(trade_id).ok_or("No such trade")?;
+			Self::trade_side_of(&mut trade, &sender)?;
+			ensure!(kitties.len() <= T::MaxTradeKitties::get() as usize, "Too many kitties in the offer");
+			Self::ensure_not_barred(&sender)?;
+			for (index, kitty_id) in kitties.iter().enumerate() {
+				ensure!(!kitties[..index].contains(kitty_id), "A kitty is offered twice");
+				ensure!(Self::owner_of(*kitty_id).as_ref() == Some(&sender), "You do not own this cat");
+				Self::ensure_can_trade(*kitty_id)?;
+			}
+
+			Self::unconfirm_trade(&mut trade);
+			let side = Self::trade_side_of(&mut trade, &sender)?;
+			side.kitties = kitties;
+			side.balance = balance;
+			<OtcTrades<T, I>>::insert(trade_id, trade);
+			Self::deposit_event(RawEvent::TradeOfferChanged(trade_id, sender));
+			Ok(())
+		}
+
+		/// Confirm a trade, reserving your balance. The second confirmation executes it
+		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
+		pub fn confirm_trade(origin, trade_id: u64) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_confirm_trade(&sender, trade_id)
+		}
+
+		/// Withdraw from a trade before it is executed, giving back the reserved balances
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn cancel_trade(origin, trade_id: u64) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let mut trade = Self::otc_trade(trade_id).ok_or("No such trade")?;
+			Self::trade_side_of(&mut trade, &sender)?;
+
+			Self::unconfirm_trade(&mut trade);
+			<OtcTrades<T, I>>::remove(trade_id);
+			Self::return_trade_deposit(trade_id, &trade.proposer.account);
+			Self::deposit_event(RawEvent::TradeCancelled(trade_id, sender));
+			Ok(())
+		}
+
+		/// Reserve `amount` for a gift voucher whose claim code hashes to `code_hash`, valid for `duration` blocks
+		#[weight = SimpleDispatchInfo::FixedNormal(30_000)]
+		pub fn buy_voucher(origin, code_hash: T::Hash, amount: BalanceOf<T, I>, duration: T::BlockNumber) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(!amount.is_zero(), "The voucher amount must be greater than zero");
+			ensure!(!duration.is_zero(), "The duration must be greater than zero");
+			ensure!(!<Vouchers<T, I>>::exists(code_hash), "This voucher code is taken");
+
+			T::Currency::reserve(&sender, amount)?;
+			let expires_at = <system::Module<T>>::block_number().saturating_add(duration);
+			<Vouchers<T, I>>::insert(code_hash, Voucher { buyer: sender.clone(), amount, expires_at });
+			Self::deposit_event(RawEvent::VoucherBought(sender, code_hash, amount, expires_at));
+			Ok(())
+		}
+
+		/// Commit to redeeming a gift voucher, `claim_hash` being `voucher_claim_hash` of its code and the sender.
+		/// The code stays secret until `redeem_voucher` in a later block, so it can not be redeemed by whoever
+		/// sees it in the pool first. A new commitment replaces the sender's previous one
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn commit_voucher_claim(origin, claim_hash: T::Hash) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			<VoucherClaims<T, I>>::insert(&sender, (claim_hash, <system::Module<T>>::block_number()));
+			Ok(())
+		}
+
+		/// Redeem a gift voucher with its claim code, committed to with `commit_voucher_claim` in an earlier block.
+		/// Its funds buy the cheapest kitty for sale within them or else a gen-0 kitty, what is left or can't be
+		/// spent is paid to the recipient
+		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
+		pub fn redeem_voucher(origin, code: Vec<u8>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_redeem_voucher(&sender, code)
+		}
+
+		/// Give the funds of an expired voucher back to its buyer, anyone can call it
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn refund_voucher(origin, code_hash: T::Hash) -> Result {
+			ensure_signed(origin)?;
+			let voucher = Self::voucher(code_hash).ok_or("No such voucher")?;
+			ensure!(<system::Module<T>>::block_number() >= voucher.expires_at, "The voucher has not expired");
+
+			<Vouchers<T, I>>::remove(code_hash);
+			T::Currency::unreserve(&voucher.buyer, voucher.amount);
+			Self::deposit_event(RawEvent::VoucherRefunded(voucher.buyer, code_hash));
+			Ok(())
+		}
+
+		/// Set the price of many kitties, none is priced unless all can be
+		#[weight = BatchWeight::<T::MaxPriceBatch>::new(50_000)]
+		pub fn set_price_many(origin, items: Vec<(T::KittyIndex, BalanceOf<T, I>)>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(!items.is_empty(), "No kitties to price");
+			ensure!(items.len() <= T::MaxPriceBatch::get() as usize, "Too many kitties to price");
+			for (kitty_id, _) in items.iter() {
+				Self::ensure_not_co_owned(*kitty_id)?;
+				Self::ensure_can_set_price(&sender, *kitty_id)?;
+			}
+
+			for (kitty_id, price) in items {
+				Self::do_set_price(&sender, kitty_id, price)?;
+			}
+			Ok(())
+		}
+
+		/// Split a kitty into shares, the kitty is locked until all shares are redeemed by one account
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn fractionalize(origin, kitty_id: T::KittyIndex, shares: u32) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+			Self::do_fractionalize(&sender, kitty_id, shares)
+		}
+
+		/// Transfer shares of a fractionalized kitty
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn transfer_shares(origin, to: T::AccountId, kitty_id: T::KittyIndex, amount: u32) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_transfer_shares(&sender, to, kitty_id, amount)
+		}
+
+		/// Redeem a fractionalized kitty, requires holding all of its shares
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn redeem(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_redeem(&sender, kitty_id)
+		}
+
+		/// Share ownership of a kitty with a group of accounts,
+		/// `threshold` of them must approve any transfer, listing or breeding afterwards
+		#[weight = SimpleDispatchInfo::FixedNormal(30_000)]
+		pub fn set_co_owners(origin, kitty_id: T::KittyIndex, owners: Vec<T::AccountId>, threshold: u32) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_set_co_owners(&sender, kitty_id, owners, threshold)
+		}
+
+		/// Propose an action on a co-owned kitty, replacing any pending one. Counts as the first approval
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		pub fn propose_action(origin, kitty_id: T::KittyIndex, action: KittyActionOf<T, I>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_propose_action(&sender, kitty_id, action)
+		}
+
+		/// Approve the pending action on a co-owned kitty, executes it once the threshold is reached
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		pub fn approve_action(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_approve_action(&sender, kitty_id)
+		}
+
+		/// Allow another account to breed and battle with a kitty. It can not transfer or sell the kitty
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn delegate(origin, kitty_id: T::KittyIndex, delegate_account: T::AccountId) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_delegate(&sender, kitty_id, Some(delegate_account))
+		}
+
+		/// Revoke the delegation of a kitty
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn undelegate(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_delegate(&sender, kitty_id, None)
+		}
+
+		/// Get notified by events when a kitty is listed or sold
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn watch(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(<Kitties<T, I>>::exists(kitty_id), "This cat does not exist");
+
+			let mut watchers = Self::kitty_watchers(kitty_id);
+			ensure!(!watchers.contains(&sender), "You already watch this cat");
+			ensure!(watchers.len() < T::MaxWatchersPerKitty::get() as usize, "Too many watchers for this cat");
+			watchers.push(sender);
+			<KittyWatchers<T, I>>::insert(kitty_id, watchers);
+
+			Ok(())
+		}
+
+		/// Stop watching a kitty
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn unwatch(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+
+			let mut watchers = Self::kitty_watchers(kitty_id);
+			let position = watchers.iter().position(|watcher| *watcher == sender).ok_or("You do not watch this cat")?;
+			watchers.swap_remove(position);
+			if watchers.is_empty() {
+				<KittyWatchers<T, I>>::remove(kitty_id);
+			} else {
+				<KittyWatchers<T, I>>::insert(kitty_id, watchers);
+			}
+
+			Ok(())
+		}
+
+		/// Burn a kitty, removing it from storage
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn burn(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+			Self::do_burn(&sender, kitty_id)
+		}
+
+		/// Set the keys allowed to sign gen-0 price reports
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_gen0_price_oracles(origin, oracles: Vec<sr25519::Public>) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_gen0_price_oracles(oracles.clone()))?;
+			<Gen0PriceOracles<I>>::put(oracles);
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Set the keys allowed to sign reference price quotes
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_reference_price_oracles(origin, oracles: Vec<sr25519::Public>) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_reference_price_oracles(oracles.clone()))?;
+			<ReferencePriceOracles<I>>::put(oracles);
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Blacklist a kitty, barring it from the marketplace and from breeding, or clear it.
+		/// Blacklisting delists the kitty
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_kitty_blacklisted(origin, kitty_id: T::KittyIndex, blacklisted: bool) -> Result {
+			let audit = Self::ensure_privileged::<T::ModeratorOrigin>(origin, Call::set_kitty_blacklisted(kitty_id, blacklisted))?;
+			let kitty = Self::kitty(kitty_id).ok_or("This cat does not exist")?;
+
+			if blacklisted {
+				<BlacklistedKitties<T, I>>::insert(kitty_id, true);
+				if !kitty.price.is_zero() {
+					Self::update_listing_price(kitty_id, Zero::zero());
+					Self::note_kitty_changed(kitty_id);
+				}
+			} else {
+				<BlacklistedKitties<T, I>>::remove(kitty_id);
+			}
+			Self::deposit_event(RawEvent::KittyBlacklisted(kitty_id, blacklisted));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Bar an account from the marketplace, or clear it
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_account_barred(origin, account: T::AccountId, barred: bool) -> Result {
+			let audit = Self::ensure_privileged::<T::ModeratorOrigin>(origin, Call::set_account_barred(account.clone(), barred))?;
+			if barred {
+				<BarredAccounts<T, I>>::insert(&account, true);
+			} else {
+				<BarredAccounts<T, I>>::remove(&account);
+			}
+			Self::deposit_event(RawEvent::AccountBarred(account, barred));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Pause or resume a subsystem, for the emergency origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_paused(origin, subsystem: Subsystem, paused: bool) -> Result {
+			let audit = Self::ensure_privileged::<T::EmergencyOrigin>(origin, Call::set_paused(subsystem, paused))?;
+			if paused {
+				<PausedSubsystems<I>>::insert(subsystem, true);
+			} else {
+				<PausedSubsystems<I>>::remove(subsystem);
+				// The sales which broke the circuit do not count again
+				if subsystem == Subsystem::Settlement {
+					<MarketStats<T, I>>::kill();
+				}
+			}
+			Self::deposit_event(RawEvent::SubsystemPaused(subsystem, paused));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Grant or revoke the verified breeder badge of an account, for the registrar origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_verified_breeder(origin, account: T::AccountId, verified: bool) -> Result {
+			let audit = Self::ensure_privileged::<T::RegistrarOrigin>(origin, Call::set_verified_breeder(account.clone(), verified))?;
+			if verified {
+				<VerifiedBreeders<T, I>>::insert(&account, true);
+			} else {
+				<VerifiedBreeders<T, I>>::remove(&account);
+			}
+			Self::deposit_event(RawEvent::BreederVerified(account, verified));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Buy a kitty through escrow: the payment is reserved and the kitty locked until the dispute window
+		/// ends, then the sale settles unless the buyer or the seller disputed it
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		pub fn buy_kitty_escrowed(origin, kitty_id: T::KittyIndex, max_price: BalanceOf<T, I>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_buy_kitty_escrowed(&sender, kitty_id, max_price)
+		}
+
+		/// Dispute an escrowed trade before it settles, leaving it to the arbiter
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn dispute_trade(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let mut trade = Self::escrowed_trade(kitty_id).ok_or("This cat is not in escrow")?;
+			ensure!(sender == trade.buyer || sender == trade.seller, "Only the buyer or the seller can dispute a trade");
+			ensure!(!trade.disputed, "This trade is already disputed");
+			ensure!(<system::Module<T>>::block_number() < trade.release_at, "The dispute window is over");
+
+			trade.disputed = true;
+			<EscrowedTrades<T, I>>::insert(kitty_id, trade);
+			Self::deposit_event(RawEvent::TradeDisputed(kitty_id, sender));
+			Ok(())
+		}
+
+		/// Close a disputed trade, selling the kitty to the buyer or giving the payment back
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		pub fn resolve_dispute(origin, kitty_id: T::KittyIndex, to_buyer: bool) -> Result {
+			let audit = Self::ensure_privileged::<T::ArbiterOrigin>(origin, Call::resolve_dispute(kitty_id, to_buyer))?;
+			let trade = Self::escrowed_trade(kitty_id).ok_or("This cat is not in escrow")?;
+			ensure!(trade.disputed, "This trade is not disputed");
+			if to_buyer {
+				Self::ensure_not_paused(Subsystem::Settlement)?;
+				Self::settle_sale(&trade.seller, &trade.buyer, kitty_id, trade.price);
+			} else {
+				T::Currency::unreserve(&trade.buyer, trade.price);
+			}
+			<EscrowedTrades<T, I>>::remove(kitty_id);
+			Self::deposit_event(RawEvent::TradeReleased(kitty_id, to_buyer));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Transfer a kitty automatically at block `at`. The kitty is locked until then
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn schedule_transfer(origin, to: T::AccountId, kitty_id: T::KittyIndex, at: T::BlockNumber) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+
+			Self::do_schedule_transfer(&sender, to, kitty_id, at)
+		}
+
+		/// Cancel a scheduled transfer, unlocking the kitty
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn cancel_scheduled_transfer(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(<ScheduledTransfers<T, I>>::exists(kitty_id), "No transfer is scheduled for this cat");
+			ensure!(Self::owner_of(kitty_id) == Some(sender), "You do not own this cat");
+
+			<ScheduledTransfers<T, I>>::remove(kitty_id);
+			Self::deposit_event(RawEvent::ScheduledTransferCancelled(kitty_id));
+			Ok(())
+		}
+
+		/// Nominate the heirs of the caller's collection, and how many blocks the caller must be inactive
+		/// before one of them can claim it. No heirs drops the nomination
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn set_heirs(origin, heirs: Vec<T::AccountId>, delay: T::BlockNumber) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+
+			if heirs.is_empty() {
+				<Heirs<T, I>>::remove(&sender);
+				<LastActive<T, I>>::remove(&sender);
+			} else {
+				ensure!(heirs.len() <= T::MaxHeirs::get() as usize, "Too many heirs");
+				ensure!(!heirs.contains(&sender), "You can not be your own heir");
+				ensure!(!delay.is_zero(), "The inactivity delay must be greater than zero");
+
+				<Heirs<T, I>>::insert(&sender, (heirs, delay));
+				<LastActive<T, I>>::insert(&sender, <system::Module<T>>::block_number());
+			}
+			<InheritanceClaims<T, I>>::remove(&sender);
+			<InheritanceCursor<T, I>>::remove(&sender);
+			Self::deposit_event(RawEvent::HeirsSet(sender));
+			Ok(())
+		}
+
+		/// Claim the collection of an account inactive for its delay. The owner can veto the claim
+		/// during another delay, after which `complete_inheritance` moves the kitties
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn claim_inheritance(origin, owner: T::AccountId) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let (heirs, delay) = Self::heirs_of(&owner).ok_or("This account has no heirs")?;
+			ensure!(heirs.contains(&sender), "You are not an heir of this account");
+			ensure!(!<InheritanceClaims<T, I>>::exists(&owner), "The collection is already claimed");
+
+			let now = <system::Module<T>>::block_number();
+			ensure!(now >= Self::last_active(&owner).saturating_add(delay), "The owner is still active");
+
+			<InheritanceClaims<T, I>>::insert(&owner, (sender.clone(), now));
+			Self::deposit_event(RawEvent::InheritanceClaimed(owner, sender));
+			Ok(())
+		}
+
+		/// Veto the claim on the caller's collection
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn veto_inheritance(origin) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			ensure!(<InheritanceClaims<T, I>>::exists(&sender), "Your collection is not claimed");
+
+			<InheritanceClaims<T, I>>::remove(&sender);
+			<InheritanceCursor<T, I>>::remove(&sender);
+			Self::deposit_event(RawEvent::InheritanceVetoed(sender));
+			Ok(())
+		}
+
+		/// Look at the next `INHERITANCE_BATCH` kitties of a claimed collection and move them to the claiming heir,
+		/// once the veto delay is over. Call again while kitties are left, locked and co-owned kitties stay with the owner
+		#[weight = SimpleDispatchInfo::FixedNormal(500_000)]
+		pub fn complete_inheritance(origin, owner: T::AccountId) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_complete_inheritance(&sender, &owner)
+		}
+
+		/// Mint a soulbound gen-0 kitty to an account, for the soulbound issuer origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(50_000)]
+		pub fn mint_soulbound(origin, to: T::AccountId) -> Result {
+			let audit = Self::ensure_privileged::<T::SoulboundIssuerOrigin>(origin, Call::mint_soulbound(to.clone()))?;
+			Self::do_mint_soulbound(&to)?;
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Burn a soulbound kitty, for the soulbound issuer origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(50_000)]
+		pub fn burn_soulbound(origin, kitty_id: T::KittyIndex) -> Result {
+			let audit = Self::ensure_privileged::<T::SoulboundIssuerOrigin>(origin, Call::burn_soulbound(kitty_id))?;
+			let kitty = Self::kitty(kitty_id).ok_or("This cat does not exist")?;
+			ensure!(kitty.soulbound, "This cat is not soulbound");
+			let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+
+			Self::remove_kitty(&owner, kitty_id);
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Add a season starting after the last one ends, for the governance origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn add_season(
+			origin,
+			start: T::BlockNumber,
+			end: T::BlockNumber,
+			genes: Vec<GeneRange>,
+			max_supply: Option<u32>
+		) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::add_season(start, end, genes.clone(), max_supply))?;
+			ensure!(start <= end, "The season must start before it ends");
+			ensure!(start > <system::Module<T>>::block_number(), "The season must start in the future");
+			ensure!(genes.len() <= 16, "The DNA has 16 bytes");
+			for gene in &genes {
+				ensure!(gene.byte < 16, "The DNA has 16 bytes");
+				ensure!(gene.min <= gene.max, "Invalid gene range");
+			}
+
+			let season_id = Self::seasons_count();
+			if let Some(last) = season_id.checked_sub(1).and_then(Self::season) {
+				ensure!(start > last.end, "Seasons can not overlap");
+			}
+			let next_season_id = season_id.checked_add(1).ok_or("Seasons count overflow")?;
+
+			<Seasons<T, I>>::insert(season_id, Season { start, end, genes, max_supply });
+			<SeasonsCount<I>>::put(next_season_id);
+			Self::deposit_event(RawEvent::SeasonAdded(season_id));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Spend up to `budget` of the market treasury on listings at or below the buyback floor and burn them,
+		/// looking at `max_scanned` kitties from where the previous buyback stopped
+		#[weight = SimpleDispatchInfo::FixedOperational(500_000)]
+		pub fn buyback(origin, budget: BalanceOf<T, I>, max_scanned: u32) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::buyback(budget, max_scanned))?;
+			Self::ensure_not_paused(Subsystem::Market)?;
+			let max_scanned = max_scanned.min(BUYBACK_SCAN_LIMIT);
+			Self::do_buyback(budget, max_scanned);
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Set the account receiving the donations of sales, `None` turns donations off
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_charity_account(origin, charity: Option<T::AccountId>) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_charity_account(charity.clone()))?;
+			match &charity {
+				Some(charity) => <CharityAccount<T, I>>::put(charity),
+				None => <CharityAccount<T, I>>::kill(),
+			}
+			Self::deposit_event(RawEvent::CharityAccountSet(charity));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Add a quest, for the governance origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn add_quest(origin, objective: QuestObjective, reward: QuestReward<BalanceOf<T, I>>, ends_at: T::BlockNumber) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::add_quest(objective.clone(), reward.clone(), ends_at))?;
+			match objective {
+				QuestObjective::BreedWithTrait { byte, .. } => ensure!(byte < 16, "The DNA has 16 bytes"),
+				_ => ensure!(objective.target() > 0, "The objective count must be greater than zero"),
+			}
+			let now = <system::Module<T>>::block_number();
+			ensure!(ends_at > now, "The quest must end in the future");
+
+			let mut active: Vec<u32> = Self::active_quests().into_iter()
+				.filter(|quest_id| Self::quest(quest_id).map_or(false, |quest| quest.ends_at >= now))
+				.collect();
+			ensure!(active.len() < MAX_ACTIVE_QUESTS, "Too many active quests");
+
+			let quest_id = Self::quests_count();
+			let next_quest_id = quest_id.checked_add(1).ok_or("Quests count overflow")?;
+			active.push(quest_id);
+			<Quests<T, I>>::insert(quest_id, Quest { objective, reward, ends_at });
+			<ActiveQuests<I>>::put(active);
+			<QuestsCount<I>>::put(next_quest_id);
+			Self::deposit_event(RawEvent::QuestAdded(quest_id));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Claim the reward of a completed quest, once per account. Balance rewards fail while the quest pool
+		/// is short of them
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn claim_quest_reward(origin, quest_id: u32) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let quest = Self::quest(quest_id).ok_or("This quest does not exist")?;
+			ensure!(Self::quest_progress(quest_id, &sender) >= quest.objective.target(), "The quest is not completed");
+			ensure!(!Self::quest_reward_claimed(quest_id, &sender), "The reward is already claimed");
+
+			match quest.reward {
+				QuestReward::Balance(amount) => {
+					// The pool account is kept alive, so it can be funded again
+					ensure!(amount <= Self::quest_pool(), "The quest pool is short of the reward");
+					T::Currency::transfer(&Self::quest_pool_account(), &sender, amount)?;
+				}
+				QuestReward::Kitty => {
+					let kitty = Kitty {
+						dna: Self::random_value(&sender),
+						price: 0.into(),
+						generation: 0,
+						soulbound: false,
+						season: None,
+						species: KITTY_SPECIES,
+					};
+					Self::insert_kitty(&sender, kitty)?;
+				}
+			}
+			<QuestRewardClaimed<T, I>>::insert(quest_id, &sender, true);
+			Self::deposit_event(RawEvent::QuestRewardPaid(quest_id, sender));
+			Ok(())
+		}
+
+		/// Add to the quest pool, the funds are transferred from the caller to the pool account
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn fund_quest_pool(origin, amount: BalanceOf<T, I>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+
+			T::Currency::transfer(&sender, &Self::quest_pool_account(), amount)?;
+			Self::deposit_event(RawEvent::QuestPoolFunded(sender, amount));
+			Ok(())
+		}
+
+		/// Tune an economic parameter, for the governance origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn set_parameter(origin, parameter: KittiesParameterOf<T, I>) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::set_parameter(parameter.clone()))?;
+			match parameter {
+				KittiesParameter::BreedingFee(fee) => <BreedingFee<T, I>>::put(fee),
+				KittiesParameter::MarketFee(fee) => <MarketFee<I>>::put(fee),
+				KittiesParameter::BreedingCooldown(cooldown) => <BreedingCooldown<T, I>>::put(cooldown),
+				KittiesParameter::MaxKittiesPerAccount(Some(max)) => <MaxKittiesPerAccount<I>>::put(max),
+				KittiesParameter::MaxKittiesPerAccount(None) => <MaxKittiesPerAccount<I>>::kill(),
+				KittiesParameter::FusionFee(fee) => <FusionFee<T, I>>::put(fee),
+				KittiesParameter::FusionCooldown(cooldown) => <FusionCooldown<T, I>>::put(cooldown),
+				KittiesParameter::MaxMintsPerBlock(Some(max)) => <MaxMintsPerBlock<I>>::put(max),
+				KittiesParameter::MaxMintsPerBlock(None) => <MaxMintsPerBlock<I>>::kill(),
+				KittiesParameter::MaxMintsPerEra(Some(max)) => <MaxMintsPerEra<I>>::put(max),
+				KittiesParameter::MaxMintsPerEra(None) => <MaxMintsPerEra<I>>::kill(),
+				KittiesParameter::MintEraLength(length) => <MintEraLength<T, I>>::put(length),
+				KittiesParameter::CreationBasePrice(price) => <CreationBasePrice<T, I>>::put(price),
+				KittiesParameter::CreationPriceSlope(slope) => <CreationPriceSlope<T, I>>::put(slope),
+				KittiesParameter::CreationReserveShare(share) => <CreationReserveShare<I>>::put(share),
+				KittiesParameter::BuybackFloor(floor) => <BuybackFloor<T, I>>::put(floor),
+				KittiesParameter::FeaturedListingFee(fee) => <FeaturedListingFee<T, I>>::put(fee),
+				KittiesParameter::NamingFee(fee) => <NamingFee<T, I>>::put(fee),
+				KittiesParameter::MaxNameLength(length) => <MaxNameLength<I>>::put(length),
+				KittiesParameter::MaxMetadataLength(length) => <MaxMetadataLength<I>>::put(length),
+				KittiesParameter::CooldownBoostFee(fee) => <CooldownBoostFee<T, I>>::put(fee),
+				KittiesParameter::SaleBurnRate(rate) => <SaleBurnRate<I>>::put(rate),
+				KittiesParameter::LoyaltyPointValue(value) => <LoyaltyPointValue<T, I>>::put(value),
+				KittiesParameter::CleanupReward(reward) => <CleanupReward<T, I>>::put(reward),
+				KittiesParameter::SponsoredCallFee(fee) => <SponsoredCallFee<T, I>>::put(fee),
+				KittiesParameter::MarketVolumeLimit(Some(limit)) => <MarketVolumeLimit<T, I>>::put(limit),
+				KittiesParameter::MarketVolumeLimit(None) => <MarketVolumeLimit<T, I>>::kill(),
+				KittiesParameter::MarketDeviationLimit(Some(limit)) => <MarketDeviationLimit<I>>::put(limit),
+				KittiesParameter::MarketDeviationLimit(None) => <MarketDeviationLimit<I>>::kill(),
+				KittiesParameter::MaxOperationsPerBlock(Some(max)) => <MaxOperationsPerBlock<I>>::put(max),
+				KittiesParameter::MaxOperationsPerBlock(None) => <MaxOperationsPerBlock<I>>::kill(),
+				KittiesParameter::CreationDeposit(deposit) => <CreationDeposit<T, I>>::put(deposit),
+				KittiesParameter::TradeDeposit(deposit) => <TradeDeposit<T, I>>::put(deposit),
+			}
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Record a gen-0 price reported by an off-chain worker. The report is an unsigned transaction,
+		/// `signature` by one of the `Gen0PriceOracles` keys over `gen0_price_payload` is checked in `validate_unsigned`
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn submit_gen0_price(
+			origin,
+			price: BalanceOf<T, I>,
+			reported_at: T::BlockNumber,
+			_oracle: sr25519::Public,
+			_signature: sr25519::Signature
+		) -> Result {
+			ensure_none(origin)?;
+			Self::ensure_valid_gen0_price(price, reported_at)?;
+			<Gen0PriceOracle<T, I>>::put((price, reported_at));
+			Ok(())
+		}
+
+		/// Transfer a kitty with a voucher signed by its owner, the sender only pays for the transaction
+		#[weight = SimpleDispatchInfo::FixedNormal(60_000)]
+		pub fn transfer_with_signature(origin, voucher: TransferVoucherOf<T, I>, signature: T::Signature) -> Result {
+			ensure_signed(origin)?;
+			Self::ensure_not_co_owned(voucher.kitty_id)?;
+			Self::do_transfer_with_signature(voucher, signature)
+		}
+
+		/// Add to the maintenance pot, the funds are transferred from the caller to the pot account
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn fund_maintenance_pot(origin, amount: BalanceOf<T, I>) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+
+			T::Currency::transfer(&sender, &Self::maintenance_pot_account(), amount)?;
+			Self::deposit_event(RawEvent::MaintenancePotFunded(sender, amount));
+			Ok(())
+		}
+
+		/// Remove the owner entries left of a kitty which no longer exists. Anyone can call it,
+		/// for a reward out of the maintenance pot
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn cleanup(origin, kitty_id: T::KittyIndex) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_cleanup(&sender, kitty_id)
+		}
+
+		/// Remove the entries of the owned kitties list of `account` which it does not own,
+		/// looking at `MAX_CLEANUP_STEPS` entries at most. Anyone can call it, for a reward
+		/// out of the maintenance pot for each entry removed
+		#[weight = SimpleDispatchInfo::FixedNormal(200_000)]
+		pub fn cleanup_account(origin, account: T::AccountId) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_cleanup_account(&sender, &account)
+		}
+
+		/// Settle the Dutch auctions which ended: the decay is dropped and the kitty stays listed at its end price.
+		/// An unsigned transaction of the off-chain worker, kitties whose auction did not end are skipped
+		#[weight = SimpleDispatchInfo::FixedOperational(100_000)]
+		pub fn collect_garbage(origin, kitty_ids: Vec<T::KittyIndex>) -> Result {
+			ensure_none(origin)?;
+			ensure!(kitty_ids.len() <= MAX_GARBAGE_ITEMS, "Too many kitties to collect");
+
+			for kitty_id in kitty_ids {
+				if !Self::is_finished_auction(kitty_id) {
+					continue;
+				}
+				if let Some(decay) = Self::price_decay(kitty_id) {
+					Self::update_listing_price(kitty_id, decay.end_price);
+					Self::deposit_event(RawEvent::AuctionSettled(kitty_id, decay.end_price));
+				}
+			}
+			Ok(())
+		}
+
+		/// Register a species of creatures, for the governance origin only
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn register_species(origin, name: Vec<u8>, dna_length: u8, trait_bytes: [u8; 4]) -> Result {
+			let audit = Self::ensure_privileged::<T::GovernanceOrigin>(origin, Call::register_species(name.clone(), dna_length, trait_bytes))?;
+			ensure!(!name.is_empty() && name.len() <= DEFAULT_MAX_NAME_LENGTH as usize, "Invalid species name");
+			ensure!(dna_length > 0 && dna_length <= 16, "The DNA has 1 to 16 bytes");
+			ensure!(trait_bytes.iter().all(|byte| *byte < dna_length), "A trait is read past the DNA");
+
+			let species_id = Self::species_count().checked_add(1).ok_or("Species count overflow")?;
+			<SpeciesRegistry<I>>::insert(species_id, Species { name, dna_length, trait_bytes });
+			<SpeciesCount<I>>::put(species_id);
+			Self::deposit_event(RawEvent::SpeciesRegistered(species_id));
+			Self::record_audit(audit);
+			Ok(())
+		}
+
+		/// Create a gen-0 creature of a registered species, like `create` does for kitties
+		#[weight = SimpleDispatchInfo::FixedNormal(50_000)]
+		pub fn create_creature(origin, species: SpeciesId) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::do_buy_gen0(&sender, None, species).map(|_| ())
+		}
+
+		/// Pay for the next `operations` kitty operations of `who`, reserving `SponsoredCallFee` for each.
+		/// An account has one sponsor at a time
+		#[weight = SimpleDispatchInfo::FixedNormal(20_000)]
+		pub fn sponsor(origin, who: T::AccountId, operations: u32) -> Result {
+			let sponsor = Self::ensure_signed_active(origin)?;
+			ensure!(operations > 0 && operations <= MAX_SPONSORED_OPERATIONS, "Invalid number of sponsored operations");
+			ensure!(!<Sponsorships<T, I>>::exists(&who), "This account already has a sponsor");
+
+			let fee = Self::sponsored_call_fee();
+			let count: BalanceOf<T, I> = operations.into();
+			T::Currency::reserve(&sponsor, fee.saturating_mul(count))?;
+			<Sponsorships<T, I>>::insert(&who, Sponsorship { sponsor: sponsor.clone(), remaining: operations, fee });
+			Self::deposit_event(RawEvent::Sponsored(sponsor, who, operations));
+			Ok(())
+		}
+
+		/// End the sponsorship of `who`, for its sponsor only. The fees of the operations left are unreserved
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn end_sponsorship(origin, who: T::AccountId) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			let sponsorship = Self::sponsorship(&who).ok_or("This account has no sponsor")?;
+			ensure!(sponsorship.sponsor == sender, "Only the sponsor can end a sponsorship");
+
+			Self::end_sponsorship_of(&who, sponsorship);
+			Ok(())
+		}
+
+		/// Dispatch a kitty operation signed by `who`, with the fee paid by its sponsor. An unsigned
+		/// transaction, so accounts without balance can move the kitties they are given
+		#[weight = SimpleDispatchInfo::FixedNormal(60_000)]
+		pub fn sponsored_call(origin, who: T::AccountId, operation: SponsoredOperationOf<T, I>, signature: T::Signature) -> Result {
+			ensure_none(origin)?;
+			Self::do_sponsored_call(who, operation, signature)
+		}
+
+		/// Buy a kitty at the price of an offer signed by its owner
+		#[weight = SimpleDispatchInfo::FixedNormal(100_000)]
+		pub fn fill_sale_offer(origin, offer: SaleOfferOf<T, I>, signature: T::Signature) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(offer.kitty_id)?;
+			Self::do_fill_sale_offer(&sender, offer, signature)
+		}
+
+		/// Withdraw every offer, transfer voucher and sponsored call the sender signed so far, by moving
+		/// all its nonces on
+		#[weight = SimpleDispatchInfo::FixedNormal(10_000)]
+		pub fn cancel_all_orders(origin) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			for purpose in NoncePurpose::all().iter() {
+				Self::bump_nonce(&sender, *purpose);
+			}
+			Self::deposit_event(RawEvent::OrdersCancelled(sender));
+			Ok(())
+		}
+
+		/// Record a reference price quote pushed by an oracle. The quote is an unsigned transaction,
+		/// `signature` by one of the `ReferencePriceOracles` keys is checked in `validate_unsigned`
+		#[weight = SimpleDispatchInfo::FixedOperational(10_000)]
+		pub fn submit_reference_price(
+			origin,
+			price: BalanceOf<T, I>,
+			quoted_at: T::BlockNumber,
+			_oracle: sr25519::Public,
+			_signature: sr25519::Signature
+		) -> Result {
+			ensure_none(origin)?;
+			Self::ensure_valid_reference_price(price, quoted_at)?;
+			<ReferencePrice<T, I>>::put((price, quoted_at));
+			Ok(())
+		}
+
+		/// List a kitty at `price` reference units. The buyer pays them at the reference price of the block
+		/// the kitty is bought in, the listed balance is only the conversion at listing time
+		#[weight = SimpleDispatchInfo::FixedNormal(60_000)]
+		pub fn set_reference_price_listing(origin, kitty_id: T::KittyIndex, price: u64) -> Result {
+			let sender = Self::ensure_signed_active(origin)?;
+			Self::ensure_not_co_owned(kitty_id)?;
+			ensure!(price > 0, "The reference price must be greater than zero");
+			let converted = Self::reference_to_balance(price)?;
+			ensure!(!converted.is_zero(), "The reference price converts to nothing");
+
+			Self::do_set_price(&sender, kitty_id, converted)?;
+			<ReferenceListings<T, I>>::insert(kitty_id, price);
+			Self::deposit_kitty_event(kitty_id, &[&sender], RawEvent::ListedAtReferencePrice(sender.clone(), kitty_id, price));
+			Ok(())
+		}
+	}
+}
+
+/// Raw storage key of a `map` entry, for migrating entries the typed storage can not decode
+fn raw_map_key<K: Encode>(storage_prefix: &[u8], key: &K) -> [u8; 32] {
+    let mut raw_key = storage_prefix.to_vec();
+    key.encode_to(&mut raw_key);
+    blake2_256(&raw_key)
+}
+
+/// Take a page of at most `limit` items, and the cursor of the next page if there are more
+fn page<K: Clone, I: Iterator<Item = K>>(iter: I, limit: u32) -> (Vec<K>, Option<K>) {
+    let limit = limit.min(MAX_PAGE_SIZE) as usize;
+    let mut items: Vec<K> = iter.take(limit + 1).collect();
+
+    if items.len() > limit {
+        items.truncate(limit);
+        let cursor = items.last().cloned();
+        (items, cursor)
+    } else {
+        (items, None)
+    }
+}
+
+/// Put `key` with its new `score` into a leaderboard sorted highest first, keeping at most `LEADERBOARD_SIZE` entries.
+/// A zero score takes the key off the board
+fn update_leaderboard<K: PartialEq, V: PartialOrd + Zero>(board: &mut Vec<(K, V)>, key: K, score: V) {
+    board.retain(|(k, _)| *k != key);
+    if score.is_zero() {
+        return;
+    }
+
+    let position = board.iter().position(|(_, v)| *v < score).unwrap_or(board.len());
+    if position < LEADERBOARD_SIZE {
+        board.insert(position, (key, score));
+        board.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+fn combine_dna(dna1: u8, dna2: u8, selector: u8) -> u8 {
+    ((selector & dna1) | (!selector & dna2))
+}
+
+/// Whether byte `byte` of `dna` has the bits of `mask` set to `value`
+fn dna_has_trait(dna: &[u8; 16], byte: u8, mask: u8, value: u8) -> bool {
+    dna.get(byte as usize).map_or(false, |b| b & mask == value & mask)
+}
+
+/// Move the bytes of `dna` into the gene ranges of `season`
+fn shape_genes<BlockNumber>(season: &Season<BlockNumber>, dna: &mut [u8; 16]) {
+    for gene in &season.genes {
+        let byte = &mut dna[gene.byte as usize];
+        let width = u16::from(gene.max - gene.min) + 1;
+        *byte = gene.min + (u16::from(*byte) % width) as u8;
+    }
+}
+
+/// The DNA of a fused kitty: each byte is the average of the parents' bytes, weighted by their generation plus one
+fn fuse_dna(dna1: &[u8; 16], generation1: u32, dna2: &[u8; 16], generation2: u32) -> [u8; 16] {
+    let weight1 = u64::from(generation1) + 1;
+    let weight2 = u64::from(generation2) + 1;
+    let mut dna = [0u8; 16];
+    for (i, byte) in dna.iter_mut().enumerate() {
+        let weighted = u64::from(dna1[i]) * weight1 + u64::from(dna2[i]) * weight2;
+        *byte = (weighted / (weight1 + weight2)) as u8;
+    }
+    dna
+}
+
+/// The DNA of a child: each bit comes from `dna1` where `selector` is set and from `dna2` elsewhere
+fn breed_dna(dna1: &[u8; 16], dna2: &[u8; 16], selector: &[u8; 16]) -> [u8; 16] {
+    let mut new_dna = [0u8; 16];
+    for i in 0..new_dna.len() {
+        new_dna[i] = combine_dna(dna1[i], dna2[i], selector[i]);
+    }
+    new_dna
+}
+
+// Storage writes are not reverted when a call fails, so every function below that
+// writes runs all of its checks first and only then starts writing.
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    fn random_value(sender: &T::AccountId) -> [u8; 16] {
+        let payload = (<system::Module<T>>::random_seed(), sender, <system::Module<T>>::extrinsic_index(), <system::Module<T>>::block_number());
+        payload.using_encoded(blake2_128)
+    }
+
+    /// Take the next kitty id and bump the counter
+    fn allocate_kitty_id() -> result::Result<T::KittyIndex, &'static str> {
+        let kitty_id = Self::kitties_count();
+        let next_kitty_id = kitty_id.checked_add(&1.into()).ok_or("Kitties count overflow")?;
+        <KittiesCount<T, I>>::put(next_kitty_id);
+        Ok(kitty_id)
+    }
+
+    fn insert_owned_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+        // 作业：调用 OwnedKitties::append 完成实现
+        <KittyOwner<T, I>>::insert(kitty_id, owner.clone());
+        Self::append_owned_kitty(owner, kitty_id);
+        Self::note_kitty_changed(kitty_id);
+    }
+
+    /// Record a kitty for the off-chain worker to mirror at the end of the block
+    fn note_kitty_changed(kitty_id: T::KittyIndex) {
+        <ChangedKitties<T, I>>::mutate(|changed| if !changed.contains(&kitty_id) {
+            changed.push(kitty_id);
+        });
+    }
+
+    fn insert_kitty(owner: &T::AccountId, kitty: Kitty<BalanceOf<T, I>>) -> result::Result<T::KittyIndex, &'static str> {
+        ensure!(!<KittyOwner<T, I>>::exists(Self::kitties_count()), "This cat already has an owner");
+        Self::ensure_below_kitty_cap(owner)?;
+
+        // The id is allocated right before the kitty is stored, nothing can fail after it
+        let kitty_id = Self::allocate_kitty_id()?;
+
+        if let Some(season_id) = kitty.season {
+            <SeasonSupply<I>>::mutate(season_id, |supply| *supply = supply.saturating_add(1));
+        }
+
+        // Create and store kitty
+        Self::index_traits(kitty_id, &kitty);
+        <KittiesPerGeneration<I>>::mutate(kitty.generation, |count| *count = count.saturating_add(1));
+        let acquisition = if kitty.generation == 0 { Acquisition::Mint } else { Acquisition::Breed };
+        Self::record_provenance(kitty_id, owner.clone(), None, acquisition);
+        <Kitties<T, I>>::insert(kitty_id, kitty);
+        <AllKittiesList<T, I>>::append(&(), kitty_id);
+        Self::insert_owned_kitty(owner, kitty_id);
+        T::OnKittyCreated::on_kitty_created(owner, kitty_id);
+        Self::deposit_kitty_event(kitty_id, &[owner], RawEvent::Created(owner.clone(), kitty_id));
+        Self::check_collection_achievement(owner);
+
+        Ok(kitty_id)
+    }
+
+    /// Price of the next gen-0 kitty: the reported price, raised along the minting curve.
+    /// Free while no price was ever reported, an error once the report is stale
+    fn gen0_price() -> result::Result<BalanceOf<T, I>, &'static str> {
+        let (price, reported_at) = match Self::gen0_price_oracle() {
+            Some(report) => report,
+            None => return Ok(Zero::zero()),
+        };
+
+        let now = <system::Module<T>>::block_number();
+        ensure!(now.saturating_sub(reported_at) <= GEN0_PRICE_MAX_AGE.into(), "The gen-0 price is stale");
+
+        let steps = Self::gen0_minted() / GEN0_CURVE_STEP;
+        Ok(price.saturating_add(price.saturating_mul(steps.into())))
+    }
+
+    /// Insert a kitty once `fee` is burned from `owner`, the fee is given back if the kitty can not be inserted.
+    /// The `reserved` part of the fee goes to the creation reserve account instead of being burned
+    fn insert_paid_kitty(
+        owner: &T::AccountId,
+        kitty: Kitty<BalanceOf<T, I>>,
+        fee: BalanceOf<T, I>,
+        reserved: BalanceOf<T, I>,
+    ) -> result::Result<T::KittyIndex, &'static str> {
+        if fee.is_zero() {
+            return Self::insert_kitty(owner, kitty);
+        }
+
+        let payment = T::Currency::withdraw(owner, fee, WithdrawReason::Fee, ExistenceRequirement::KeepAlive)?;
+        match Self::insert_kitty(owner, kitty) {
+            Ok(kitty_id) => {
+                let (reserved, _) = payment.split(reserved);
+                T::Currency::resolve_creating(&Self::creation_reserve_account(), reserved);
+                Ok(kitty_id)
+            }
+            Err(e) => {
+                // Give the payment back, nothing else was written
+                T::Currency::resolve_creating(owner, payment);
+                Err(e)
+            }
+        }
+    }
+
+    /// Account of the module fund `id` for this instance. The default instance uses the plain module account,
+    /// the others a sub account named after the instance, so the instances never share funds
+    fn module_account(id: ModuleId) -> T::AccountId {
+        let name = instance_name::<I>();
+        if name.is_empty() {
+            id.into_account()
+        } else {
+            id.into_sub_account(name)
+        }
+    }
+
+    /// Account holding the share of the creation prices paid back on burns
+    pub fn creation_reserve_account() -> T::AccountId {
+        Self::module_account(CREATION_RESERVE_ID)
+    }
+
+    /// Funds in the creation reserve account owed back to the kitties backed by it
+    pub fn creation_reserve() -> BalanceOf<T, I> {
+        Self::spendable_balance(&Self::creation_reserve_account())
+    }
+
+    /// Creation price along the supply curve, part of it goes to the reserve
+    pub fn creation_price() -> BalanceOf<T, I> {
+        let supply: u128 = Self::kitties_count().unique_saturated_into();
+        Self::creation_base_price().saturating_add(Self::creation_price_slope().saturating_mul(BalanceOf::<T, I>::unique_saturated_from(supply)))
+    }
+
+    /// Mint a gen-0 kitty, the gen-0 and creation prices are burned from `owner` but for the share of the
+    /// creation reserve, and the creation deposit reserved when `priced`
+    fn insert_gen0_kitty(owner: &T::AccountId, kitty: Kitty<BalanceOf<T, I>>, priced: bool) -> result::Result<T::KittyIndex, &'static str> {
+        let (gen0_price, creation_price, deposit) = if priced {
+            (Self::gen0_price()?, Self::creation_price(), Self::creation_deposit())
+        } else {
+            (Zero::zero(), Zero::zero(), Zero::zero())
+        };
+        let reserved = Self::creation_reserve_share() * creation_price;
+        T::Currency::reserve(owner, deposit)?;
+        let kitty_id = match Self::insert_paid_kitty(owner, kitty, gen0_price.saturating_add(creation_price), reserved) {
+            Ok(kitty_id) => kitty_id,
+            Err(e) => {
+                let _ = T::Currency::unreserve(owner, deposit);
+                return Err(e);
+            }
+        };
+        if !deposit.is_zero() {
+            <KittyDeposits<T, I>>::insert(kitty_id, (owner.clone(), deposit));
+        }
+        if !gen0_price.is_zero() {
+            <Gen0Minted<I>>::mutate(|minted| *minted = minted.saturating_add(1));
+        }
+        if !reserved.is_zero() {
+            <ReserveBackedKitties<I>>::mutate(|backed| *backed = backed.saturating_add(1));
+            <ReserveBacked<T, I>>::insert(kitty_id, true);
+        }
+        if Self::is_verified_breeder(owner) {
+            <MintedByVerifiedBreeder<T, I>>::insert(kitty_id, true);
+        }
+
+        Ok(kitty_id)
+    }
+
+    fn ensure_not_paused(subsystem: Subsystem) -> Result {
+        ensure!(!Self::is_paused(subsystem), "This subsystem is paused");
+        Ok(())
+    }
+
+    fn ensure_not_blacklisted(kitty_id: T::KittyIndex) -> Result {
+        ensure!(!Self::is_kitty_blacklisted(kitty_id), "This cat is blacklisted");
+        Ok(())
+    }
+
+    fn ensure_not_barred(account: &T::AccountId) -> Result {
+        ensure!(!Self::is_account_barred(account), "This account is barred from the marketplace");
+        Ok(())
+    }
+
+    /// Refuse a kitty to an account owning `MaxKittiesPerAccount` kitties already
+    fn ensure_below_kitty_cap(account: &T::AccountId) -> Result {
+        if let Some(max) = Self::max_kitties_per_account() {
+            ensure!(Self::owned_kitties_count(account) < u64::from(max), "The account owns too many kitties");
+        }
+        Ok(())
+    }
+
+    fn do_create(sender: &T::AccountId) -> result::Result<T::KittyIndex, &'static str> {
+        Self::create_gen0(sender, true, KITTY_SPECIES)
+    }
+
+    /// Create a gen-0 kitty, paying the gen-0 price when `priced`
+    fn create_gen0(sender: &T::AccountId, priced: bool, species: SpeciesId) -> result::Result<T::KittyIndex, &'static str> {
+        Self::ensure_not_paused(Subsystem::Minting)?;
+        let species_info = Self::species_info(species).ok_or("No such species")?;
+
+        let block_mints = Self::block_mints();
+        if let Some(max) = Self::max_mints_per_block() {
+            ensure!(block_mints < max, "Too many kitties created in this block");
+        }
+        let era = Self::mint_era();
+        let era_mints = match Self::era_mints(sender) {
+            (mint_era, mints) if mint_era == era => mints,
+            _ => 0,
+        };
+        if let Some(max) = Self::max_mints_per_era() {
+            ensure!(era_mints < max, "Too many kitties created by this account in this era");
+        }
+
+        // Generate a random 128bit value
+        let mut dna = Self::random_value(sender);
+        let season = Self::apply_season(&mut dna);
+        species_info.mask(&mut dna);
+
+        // With a reveal delay only a commitment to the value is kept, the DNA is drawn at the reveal block.
+        // Grinding `create` in one block then only picks the commitment, not the DNA
+        let delay = T::RevealDelay::get();
+        let commitment = if delay.is_zero() {
+            None
+        } else {
+            let commitment = T::Hashing::hash_of(&dna);
+            dna = [0; 16];
+            Some(commitment)
+        };
+
+        // Create and store kitty
+        let kitty = Kitty {
+            dna,
+            price: 0.into(),
+            generation: 0,
+            soulbound: false,
+            season,
+            species,
+        };
+
+        let kitty_id = Self::insert_gen0_kitty(sender, kitty, priced)?;
+        <BlockMints<I>>::put(block_mints.saturating_add(1));
+        <EraMints<T, I>>::insert(sender, (era, era_mints.saturating_add(1)));
+        if let Some(commitment) = commitment {
+            let reveal_at = <system::Module<T>>::block_number().saturating_add(delay.max(MIN_REVEAL_DELAY.into()));
+            <PendingReveals<T, I>>::insert(kitty_id, commitment);
+            <RevealQueue<T, I>>::mutate(reveal_at, |queue| queue.push(kitty_id));
+        }
+
+        Ok(kitty_id)
+    }
+
+    /// Create a gen-0 creature of `species` within the rules of the drop phase
+    fn do_buy_gen0(
+        sender: &T::AccountId,
+        mint_pass: Option<T::KittyIndex>,
+        species: SpeciesId
+    ) -> result::Result<T::KittyIndex, &'static str> {
+        let phase = Self::drop_phase();
+        if phase == DropPhase::Presale {
+            let holds_pass = mint_pass.map_or(false, |kitty_id| {
+                Self::is_mint_pass(kitty_id) && Self::owner_of(kitty_id).as_ref() == Some(sender)
+            });
+            ensure!(Self::is_presale_whitelisted(sender) || holds_pass, "Only whitelisted accounts and mint-pass holders can buy in the presale");
+        }
+
+        let key = (Self::current_drop(), phase);
+        let minted = Self::drop_minted(key, sender);
+        if let Some(limit) = Self::drop_mint_limit(phase) {
+            ensure!(minted < limit, "The mint limit of this phase is reached");
+        }
+
+        let kitty_id = Self::create_gen0(sender, true, species)?;
+        <DropMinted<T, I>>::insert(key, sender, minted.saturating_add(1));
+        Ok(kitty_id)
+    }
+
+    /// Start the mint counters over for a new block, and for a new era at its first block
+    fn roll_mint_counters(now: T::BlockNumber) {
+        <BlockMints<I>>::kill();
+        let length = Self::mint_era_length();
+        if !length.is_zero() && (now % length).is_zero() {
+            <MintEra<I>>::mutate(|era| *era = era.wrapping_add(1));
+        }
+    }
+
+    /// Draw the DNA of the kitties due at `now` from their commitment and the hash of the parent block.
+    /// With `MIN_REVEAL_DELAY` the parent is strictly after the creation block, its hash was unknown
+    /// to the creator and to the author of the creation block
+    fn reveal_kitties(now: T::BlockNumber) {
+        let parent_hash = <system::Module<T>>::block_hash(now.saturating_sub(1.into()));
+        for kitty_id in <RevealQueue<T, I>>::take(now) {
+            let commitment = match <PendingReveals<T, I>>::take(kitty_id) {
+                Some(commitment) => commitment,
+                None => continue,
+            };
+            let mut kitty = match Self::kitty(kitty_id) {
+                Some(kitty) => kitty,
+                None => continue,
+            };
+
+            Self::unindex_traits(kitty_id, &kitty);
+            kitty.dna = (commitment, parent_hash, kitty_id).using_encoded(blake2_128);
+            if let Some(season) = kitty.season.and_then(Self::season) {
+                shape_genes(&season, &mut kitty.dna);
+            }
+            if let Some(species) = Self::species_info(kitty.species) {
+                species.mask(&mut kitty.dna);
+            }
+            Self::index_traits(kitty_id, &kitty);
+            <Kitties<T, I>>::insert(kitty_id, kitty);
+            Self::note_kitty_changed(kitty_id);
+            Self::deposit_event(RawEvent::KittyRevealed(kitty_id));
+        }
+    }
+
+    fn ensure_revealed(kitty_id: T::KittyIndex) -> Result {
+        ensure!(!<PendingReveals<T, I>>::exists(kitty_id), "This cat is not revealed yet");
+        Ok(())
+    }
+
+    /// Mint a soulbound gen-0 kitty, free of the gen-0 price
+    fn do_mint_soulbound(owner: &T::AccountId) -> result::Result<T::KittyIndex, &'static str> {
+        Self::ensure_not_paused(Subsystem::Minting)?;
+
+        let kitty = Kitty {
+            dna: Self::random_value(owner),
+            price: 0.into(),
+            generation: 0,
+            soulbound: true,
+            season: None,
+            species: KITTY_SPECIES,
+        };
+
+        Self::insert_kitty(owner, kitty)
+    }
+
+    /// Checks of a gen-0 price report which do not need its signature
+    fn ensure_valid_gen0_price(price: BalanceOf<T, I>, reported_at: T::BlockNumber) -> Result {
+        ensure!(!price.is_zero(), "The gen-0 price must be greater than zero");
+
+        let now = <system::Module<T>>::block_number();
+        ensure!(reported_at <= now, "The gen-0 price is reported from the future");
+        ensure!(now.saturating_sub(reported_at) <= GEN0_PRICE_MAX_AGE.into(), "The gen-0 price is stale");
+
+        if let Some((previous, previous_at)) = Self::gen0_price_oracle() {
+            ensure!(reported_at > previous_at, "A newer gen-0 price is already recorded");
+            let max_change: BalanceOf<T, I> = GEN0_PRICE_MAX_CHANGE.into();
+            ensure!(
+                price <= previous.saturating_mul(max_change) && price.saturating_mul(max_change) >= previous,
+                "The gen-0 price moves too far"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// `tag` followed by the instance name and the encoded `data`, for signed messages and transaction pool tags.
+    /// The default instance encodes as `(tag, data)`, other instances don't accept its signatures
+    fn tagged<D: Encode>(tag: &[u8], data: D) -> Vec<u8> {
+        let mut message = [tag, instance_name::<I>()].concat();
+        data.encode_to(&mut message);
+        message
+    }
+
+    /// The message an oracle signs for a gen-0 price report, bound to the chain by its genesis hash
+    pub fn gen0_price_payload(price: BalanceOf<T, I>, reported_at: T::BlockNumber) -> Vec<u8> {
+        let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+        Self::tagged(b"kitties-gen0-price", (genesis_hash, price, reported_at))
+    }
+
+    /// The message an oracle signs for a reference price quote
+    pub fn reference_price_payload(price: BalanceOf<T, I>, quoted_at: T::BlockNumber) -> Vec<u8> {
+        Self::tagged(b"kitties-reference-price", (price, quoted_at))
+    }
+
+    /// Checks of a reference price quote which do not need its signature
+    fn ensure_valid_reference_price(price: BalanceOf<T, I>, quoted_at: T::BlockNumber) -> Result {
+        ensure!(!price.is_zero(), "The reference price must be greater than zero");
+
+        let now = <system::Module<T>>::block_number();
+        ensure!(quoted_at <= now, "The reference price is quoted from the future");
+        ensure!(now.saturating_sub(quoted_at) <= REFERENCE_PRICE_MAX_AGE.into(), "The reference price is stale");
+        if let Some((_, previous_at)) = Self::reference_price() {
+            ensure!(quoted_at > previous_at, "A newer reference price is already recorded");
+        }
+        Ok(())
+    }
+
+    /// Balance worth `amount` reference units at the last quote, an error without a fresh quote
+    pub fn reference_to_balance(amount: u64) -> result::Result<BalanceOf<T, I>, &'static str> {
+        let (price, quoted_at) = Self::reference_price().ok_or("No reference price is quoted")?;
+        let now = <system::Module<T>>::block_number();
+        ensure!(now.saturating_sub(quoted_at) <= REFERENCE_PRICE_MAX_AGE.into(), "The reference price is stale");
+        Ok(price.saturating_mul(BalanceOf::<T, I>::unique_saturated_from(amount)))
+    }
+
+    /// Reference units worth `amount` at the last quote, rounded down, an error without a fresh quote
+    pub fn balance_to_reference(amount: BalanceOf<T, I>) -> result::Result<u64, &'static str> {
+        let one_unit = Self::reference_to_balance(1)?;
+        Ok((amount / one_unit).unique_saturated_into())
+    }
+
+    /// Now on the clock of the cooldowns: the block number, or the time in milliseconds with `TimestampCooldowns`
+    fn cooldown_clock() -> T::BlockNumber {
+        if T::TimestampCooldowns::get() {
+            let now: u64 = T::Time::now().unique_saturated_into();
+            now.unique_saturated_into()
+        } else {
+            <system::Module<T>>::block_number()
+        }
+    }
+
+    fn do_boost_cooldown(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        ensure!(Self::owner_of(kitty_id).as_ref() == Some(sender), "You do not own this cat");
+        Self::ensure_not_paused(Subsystem::Breeding)?;
+        Self::ensure_not_blacklisted(kitty_id)?;
+        let now = Self::cooldown_clock();
+        let ready_at = Self::breeding_ready_at(kitty_id);
+        ensure!(ready_at > now, "This cat is not cooling down");
+
+        let blocks: u32 = (ready_at - now).unique_saturated_into();
+        let fee = Self::cooldown_boost_fee().saturating_mul(blocks.into());
+        if !fee.is_zero() {
+            let _ = T::Currency::withdraw(sender, fee, WithdrawReason::Fee, ExistenceRequirement::KeepAlive)?;
+        }
+
+        <BreedingReadyAt<T, I>>::remove(kitty_id);
+        Self::deposit_kitty_event(kitty_id, &[sender], RawEvent::CooldownBoosted(sender.clone(), kitty_id, fee));
+        Ok(())
+    }
+
+    fn do_breed(sender: &T::AccountId, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> result::Result<T::KittyIndex, &'static str> {
+        let kitty1 = Self::kitty(kitty_id_1);
+        let kitty2 = Self::kitty(kitty_id_2);
+
+        ensure!(kitty1.is_some(), "Invalid kitty_id_1");
+        ensure!(kitty2.is_some(), "Invalid kitty_id_2");
+        ensure!(kitty_id_1 != kitty_id_2, "Needs different parent");
+        Self::ensure_not_paused(Subsystem::Breeding)?;
+        ensure!(Self::can_play(sender, kitty_id_1), "You can not breed with kitty_id_1");
+        ensure!(Self::can_play(sender, kitty_id_2), "You can not breed with kitty_id_2");
+        Self::ensure_not_blacklisted(kitty_id_1)?;
+        Self::ensure_not_blacklisted(kitty_id_2)?;
+        Self::ensure_revealed(kitty_id_1)?;
+        Self::ensure_revealed(kitty_id_2)?;
+
+        let now = Self::cooldown_clock();
+        ensure!(Self::breeding_ready_at(kitty_id_1) <= now, "kitty_id_1 is cooling down");
+        ensure!(Self::breeding_ready_at(kitty_id_2) <= now, "kitty_id_2 is cooling down");
+
+        let kitty1 = kitty1.unwrap();
+        let kitty2 = kitty2.unwrap();
+        ensure!(kitty1.species == kitty2.species, "Creatures of different species can not breed");
+
+        // Generate a random 128bit value
+        let selector = Self::random_value(&sender);
+
+        // Combine parents and selector to create new kitty
+        let generation = kitty1.generation.max(kitty2.generation).saturating_add(1);
+        let mut dna = breed_dna(&kitty1.dna, &kitty2.dna, &selector);
+        let season = Self::apply_season(&mut dna);
+        if let Some(species) = Self::species_info(kitty1.species) {
+            species.mask(&mut dna);
+        }
+        let kitty = Kitty {
+            dna,
+            price: 0.into(),
+            generation,
+            soulbound: false,
+            season,
+            species: kitty1.species,
+        };
+
+        let kitty_id = Self::insert_paid_kitty(sender, kitty, Self::breeding_fee(), Zero::zero())?;
+
+        let cooldown = Self::breeding_cooldown();
+        if !cooldown.is_zero() {
+            let ready_at = now.saturating_add(cooldown);
+            <BreedingReadyAt<T, I>>::insert(kitty_id_1, ready_at);
+            <BreedingReadyAt<T, I>>::insert(kitty_id_2, ready_at);
+        }
+
+        Self::note_quest_progress(sender, |objective| match objective {
+            QuestObjective::BreedWithTrait { byte, mask, value } =>
+                dna_has_trait(&dna, *byte, *mask, *value),
+            QuestObjective::Breeds(_) => true,
+            QuestObjective::Sales(_) => false,
+        });
+        Self::unlock_achievement(sender, ACHIEVEMENT_FIRST_BREED);
+        if generation >= 5 {
+            Self::unlock_achievement(sender, ACHIEVEMENT_FIRST_GEN5);
+        }
+
+        Ok(kitty_id)
+    }
+
+    /// The fused kitty is inserted before the parents are burned, so an account at `MaxKittiesPerAccount` can not fuse
+    fn do_fuse(sender: &T::AccountId, kitty_id_1: T::KittyIndex, kitty_id_2: T::KittyIndex) -> result::Result<T::KittyIndex, &'static str> {
+        ensure!(kitty_id_1 != kitty_id_2, "Needs different kitties");
+        let kitty1 = Self::kitty(kitty_id_1).ok_or("Invalid kitty_id_1")?;
+        let kitty2 = Self::kitty(kitty_id_2).ok_or("Invalid kitty_id_2")?;
+        Self::ensure_not_paused(Subsystem::Breeding)?;
+        ensure!(Self::owner_of(kitty_id_1).as_ref() == Some(sender), "You do not own kitty_id_1");
+        ensure!(Self::owner_of(kitty_id_2).as_ref() == Some(sender), "You do not own kitty_id_2");
+        Self::ensure_not_blacklisted(kitty_id_1)?;
+        Self::ensure_not_blacklisted(kitty_id_2)?;
+        Self::ensure_revealed(kitty_id_1)?;
+        Self::ensure_revealed(kitty_id_2)?;
+        Self::ensure_not_locked(kitty_id_1)?;
+        Self::ensure_not_locked(kitty_id_2)?;
+        ensure!(kitty1.species == kitty2.species, "Creatures of different species can not fuse");
+
+        let now = Self::cooldown_clock();
+        ensure!(Self::breeding_ready_at(kitty_id_1) <= now, "kitty_id_1 is cooling down");
+        ensure!(Self::breeding_ready_at(kitty_id_2) <= now, "kitty_id_2 is cooling down");
+
+        let mut dna = fuse_dna(&kitty1.dna, kitty1.generation, &kitty2.dna, kitty2.generation);
+        let season = Self::apply_season(&mut dna);
+        if let Some(species) = Self::species_info(kitty1.species) {
+            species.mask(&mut dna);
+        }
+        let kitty = Kitty {
+            dna,
+            price: 0.into(),
+            generation: kitty1.generation.max(kitty2.generation).saturating_add(1),
+            soulbound: false,
+            season,
+            species: kitty1.species,
+        };
+
+        let kitty_id = Self::insert_paid_kitty(sender, kitty, Self::fusion_fee(), Zero::zero())?;
+        Self::remove_kitty(sender, kitty_id_1);
+        Self::remove_kitty(sender, kitty_id_2);
+
+        let cooldown = Self::fusion_cooldown();
+        if !cooldown.is_zero() {
+            <BreedingReadyAt<T, I>>::insert(kitty_id, now.saturating_add(cooldown));
+        }
+
+        Self::deposit_event(RawEvent::Fused(sender.clone(), kitty_id_1, kitty_id_2, kitty_id));
+        Ok(kitty_id)
+    }
+
+    /// The message an owner signs for a transfer voucher. The genesis hash keeps it from being
+    /// replayed on another chain running the module
+    pub fn transfer_voucher_payload(voucher: &TransferVoucherOf<T, I>) -> Vec<u8> {
+        let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+        Self::tagged(b"kitties-transfer-voucher", (genesis_hash, voucher))
+    }
+
+    fn do_transfer_with_signature(voucher: TransferVoucherOf<T, I>, signature: T::Signature) -> Result {
+        let owner = Self::owner_of(voucher.kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(voucher.deadline >= <system::Module<T>>::block_number(), "The voucher has expired");
+        ensure!(voucher.nonce == Self::nonce_of(&owner, NoncePurpose::TransferVoucher), "The voucher nonce is not the owner's next nonce");
+        ensure!(
+            signature.verify(&Self::transfer_voucher_payload(&voucher)[..], &owner),
+            "The voucher is not signed by the owner"
+        );
+        Self::ensure_can_transfer(&owner, &voucher.to, voucher.kitty_id)?;
+
+        Self::bump_nonce(&owner, NoncePurpose::TransferVoucher);
+        Self::transfer_ownership(&owner, voucher.to, voucher.kitty_id, Acquisition::Transfer);
+
+        Ok(())
+    }
+
+    /// Nonce of the next off-chain signature of `who` for `purpose`
+    pub fn nonce_of(who: &T::AccountId, purpose: NoncePurpose) -> u64 {
+        let nonce = Self::nonce(who, purpose);
+        match purpose {
+            NoncePurpose::TransferVoucher => nonce.max(Self::transfer_nonce(who)),
+            _ => nonce,
+        }
+    }
+
+    /// Use up the nonce of `who` for `purpose`, every signature for it becomes invalid
+    fn bump_nonce(who: &T::AccountId, purpose: NoncePurpose) {
+        let next = Self::nonce_of(who, purpose).saturating_add(1);
+        <Nonces<T, I>>::insert(who, purpose, next);
+        if purpose == NoncePurpose::TransferVoucher {
+            <TransferNonces<T, I>>::remove(who);
+        }
+    }
+
+    /// The message an owner signs for a sale offer, bound to the chain by its genesis hash like a transfer voucher
+    pub fn sale_offer_payload(offer: &SaleOfferOf<T, I>) -> Vec<u8> {
+        let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+        Self::tagged(b"kitties-sale-offer", (genesis_hash, offer))
+    }
+
+    fn do_fill_sale_offer(buyer: &T::AccountId, offer: SaleOfferOf<T, I>, signature: T::Signature) -> Result {
+        Self::ensure_market_enabled()?;
+        Self::ensure_not_paused(Subsystem::Market)?;
+        Self::ensure_not_paused(Subsystem::Settlement)?;
+        let seller = Self::owner_of(offer.kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(seller != *buyer, "You can't buy your own cat");
+        ensure!(!offer.price.is_zero(), "The offer price must be greater than zero");
+        ensure!(offer.deadline >= <system::Module<T>>::block_number(), "The offer has expired");
+        ensure!(offer.nonce == Self::nonce_of(&seller, NoncePurpose::SaleOffer), "The offer nonce is not the owner's next nonce");
+        ensure!(
+            signature.verify(&Self::sale_offer_payload(&offer)[..], &seller),
+            "The offer is not signed by the owner"
+        );
+        Self::ensure_not_blacklisted(offer.kitty_id)?;
+        Self::ensure_not_barred(buyer)?;
+        Self::ensure_not_barred(&seller)?;
+        Self::ensure_can_transfer(&seller, buyer, offer.kitty_id)?;
+
+        // Reserving the payment is the last check: once it holds, the settlement can not fail
+        T::Currency::reserve(buyer, offer.price)?;
+        Self::bump_nonce(&seller, NoncePurpose::SaleOffer);
+        Self::settle_sale(&seller, buyer, offer.kitty_id, offer.price);
+        Ok(())
+    }
+
+    fn do_transfer(sender: &T::AccountId, to: T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        Self::ensure_can_transfer(sender, &to, kitty_id)?;
+        Self::transfer_ownership(sender, to, kitty_id, Acquisition::Transfer);
+        Ok(())
+    }
+
+    fn ensure_can_transfer(sender: &T::AccountId, to: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        Self::ensure_not_paused(Subsystem::Transfers)?;
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(owner == *sender, "Sender does not own this kitty");
+        Self::ensure_not_locked(kitty_id)?;
+        if to != sender {
+            Self::ensure_below_kitty_cap(to)?;
+        }
+        Ok(())
+    }
+
+    /// Move a kitty to a new owner. Callers check with `ensure_can_transfer` first
+    fn transfer_ownership(from: &T::AccountId, to: T::AccountId, kitty_id: T::KittyIndex, acquisition: Acquisition) {
+        // The new owner holds the kitty alone
+        <KittyCoOwners<T, I>>::remove(kitty_id);
+        <PendingKittyActions<T, I>>::remove(kitty_id);
+        <KittyDelegate<T, I>>::remove(kitty_id);
+        <ListingDonation<T, I>>::remove(kitty_id);
+        <VoteWeights<T, I>>::remove(from);
+
+        if *from != to {
+            Self::record_provenance(kitty_id, to.clone(), Some(from.clone()), acquisition);
+        }
+        <KittyOwner<T, I>>::insert(kitty_id, to.clone());
+        Self::remove_owned_kitty(from, kitty_id);
+        Self::append_owned_kitty(&to, kitty_id);
+        Self::note_kitty_changed(kitty_id);
+        T::OnKittyTransfer::on_kitty_transfer(from, &to, kitty_id);
+        Self::deposit_kitty_event(kitty_id, &[from, &to], RawEvent::Transferred(from.clone(), to.clone(), kitty_id));
+        Self::check_collection_achievement(&to);
+    }
+
+    fn record_provenance(kitty_id: T::KittyIndex, owner: T::AccountId, previous_owner: Option<T::AccountId>, acquisition: Acquisition) {
+        let record = ProvenanceRecord {
+            owner,
+            previous_owner,
+            at: <system::Module<T>>::block_number(),
+            acquisition,
+        };
+        <Provenance<T, I>>::mutate(kitty_id, |records| {
+            if records.len() >= MAX_PROVENANCE_RECORDS {
+                records.remove(1);
+            }
+            records.push(record);
+        });
+    }
+
+    /// Unlock an achievement of `account` once, minting its badge when `AchievementBadges` is on.
+    /// Runs after the operation which earned it, a badge which can not be minted (e.g. minting paused) is skipped
+    fn unlock_achievement(account: &T::AccountId, achievement: u32) {
+        let unlocked = Self::achievements_of(account);
+        if unlocked & achievement != 0 {
+            return;
+        }
+
+        <Achievements<T, I>>::insert(account, unlocked | achievement);
+        Self::deposit_event(RawEvent::AchievementUnlocked(account.clone(), achievement));
+        if T::AchievementBadges::get() {
+            let _ = Self::do_mint_soulbound(account);
+        }
+    }
+
+    /// The season running at this block, seasons are ordered so the search stops at the first started one
+    fn current_season() -> Option<(u32, SeasonOf<T>)> {
+        let now = <system::Module<T>>::block_number();
+        let mut season_id = Self::seasons_count();
+        while season_id > 0 {
+            season_id -= 1;
+            if let Some(season) = Self::season(season_id) {
+                if season.start <= now {
+                    return if season.end >= now { Some((season_id, season)) } else { None };
+                }
+            }
+        }
+        None
+    }
+
+    /// Move `dna` into the genes of the running season and return its id, `None` outside seasons
+    /// or once the season supply is reached
+    fn apply_season(dna: &mut [u8; 16]) -> Option<u32> {
+        let (season_id, season) = Self::current_season()?;
+        if season.max_supply.map_or(false, |max_supply| Self::season_supply(season_id) >= max_supply) {
+            return None;
+        }
+
+        shape_genes(&season, dna);
+        Some(season_id)
+    }
+
+    /// Count one step for `account` on the active quests whose objective `counts`
+    fn note_quest_progress<F: Fn(&QuestObjective) -> bool>(account: &T::AccountId, counts: F) {
+        let now = <system::Module<T>>::block_number();
+        for quest_id in Self::active_quests() {
+            let counted = Self::quest(quest_id).map_or(false, |quest| quest.ends_at >= now && counts(&quest.objective));
+            if counted {
+                <QuestProgress<T, I>>::mutate(quest_id, account, |progress| *progress = progress.saturating_add(1));
+            }
+        }
+    }
+
+    fn check_collection_achievement(account: &T::AccountId) {
+        if Self::owned_kitties_count(account) >= HUNDRED_KITTIES {
+            Self::unlock_achievement(account, ACHIEVEMENT_HUNDRED_KITTIES);
+        }
+    }
+
+    /// Checks of a purchase, returns the seller and the price
+    fn ensure_can_buy(
+        sender: &T::AccountId,
+        kitty_id: T::KittyIndex,
+        max_price: BalanceOf<T, I>,
+    ) -> result::Result<(T::AccountId, BalanceOf<T, I>), &'static str> {
+        Self::ensure_market_enabled()?;
+        Self::ensure_not_paused(Subsystem::Market)?;
+        Self::ensure_not_paused(Subsystem::Settlement)?;
+        let kitty = Self::kitty(kitty_id).ok_or("This cat does not exist")?;
+
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(owner != *sender, "You can't buy your own cat");
+        Self::ensure_not_blacklisted(kitty_id)?;
+        Self::ensure_not_barred(sender)?;
+        Self::ensure_not_barred(&owner)?;
+
+        let kitty_price = match Self::reference_listing(kitty_id) {
+            Some(reference_price) => Self::reference_to_balance(reference_price)?,
+            None => Self::current_price(kitty_id, kitty.price),
+        };
+        ensure!(!kitty_price.is_zero(), "The cat you want to buy is not for sale");
+        ensure!(kitty_price <= max_price, "The cat you want to buy costs more than your max price");
+        Self::ensure_can_transfer(&owner, sender, kitty_id)?;
+
+        Ok((owner, kitty_price))
+    }
+
+    fn do_wrap_kitty(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        Self::ensure_not_paused(Subsystem::Transfers)?;
+        ensure!(Self::owner_of(kitty_id).as_ref() == Some(sender), "You do not own this cat");
+        Self::ensure_not_co_owned(kitty_id)?;
+        Self::ensure_not_locked(kitty_id)?;
+        Self::ensure_not_blacklisted(kitty_id)?;
+        Self::ensure_revealed(kitty_id)?;
+        let supply = Self::index_token_supply().checked_add(1).ok_or("Index tokens overflow")?;
+
+        Self::update_listing_price(kitty_id, Zero::zero());
+        Self::transfer_ownership(sender, T::IndexPoolAccount::get(), kitty_id, Acquisition::Transfer);
+        <IndexTokens<T, I>>::mutate(sender, |tokens| *tokens += 1);
+        <IndexTokenSupply<I>>::put(supply);
+        Self::deposit_kitty_event(kitty_id, &[sender], RawEvent::KittyWrapped(sender.clone(), kitty_id));
+        Ok(())
+    }
+
+    fn do_unwrap_kitty(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        Self::ensure_not_paused(Subsystem::Transfers)?;
+        let pool = T::IndexPoolAccount::get();
+        ensure!(Self::owner_of(kitty_id).as_ref() == Some(&pool), "This cat is not in the index pool");
+        let tokens = Self::index_tokens(sender).checked_sub(1).ok_or("Not enough index tokens")?;
+        Self::ensure_below_kitty_cap(sender)?;
+
+        <IndexTokens<T, I>>::insert(sender, tokens);
+        <IndexTokenSupply<I>>::mutate(|supply| *supply -= 1);
+        Self::transfer_ownership(&pool, sender.clone(), kitty_id, Acquisition::Transfer);
+        Self::deposit_kitty_event(kitty_id, &[sender], RawEvent::KittyUnwrapped(sender.clone(), kitty_id));
+        Ok(())
+    }
+
+    fn trade_side_of<'a>(
+        trade: &'a mut OtcTradeOf<T, I>,
+        account: &T::AccountId,
+    ) -> result::Result<&'a mut TradeSide<T::AccountId, T::KittyIndex, BalanceOf<T, I>>, &'static str> {
+        if trade.proposer.account == *account {
+            Ok(&mut trade.proposer)
+        } else if trade.counterparty.account == *account {
+            Ok(&mut trade.counterparty)
+        } else {
+            Err("You are not part of this trade")
+        }
+    }
+
+    /// Drop both confirmations of a trade, giving back the reserved balances
+    fn unconfirm_trade(trade: &mut OtcTradeOf<T, I>) {
+        for side in &mut [&mut trade.proposer, &mut trade.counterparty] {
+            if side.confirmed {
+                T::Currency::unreserve(&side.account, side.balance);
+                side.confirmed = false;
+            }
+        }
+    }
+
+    /// Checks of a kitty given in a trade: those `buy_kitty` applies to a kitty, and it can't be
+    /// listed, escrowed or co-owned
+    fn ensure_can_trade(kitty_id: T::KittyIndex) -> Result {
+        Self::ensure_not_blacklisted(kitty_id)?;
+        Self::ensure_not_co_owned(kitty_id)?;
+        Self::ensure_not_locked(kitty_id)?;
+        let listed = Self::kitty(kitty_id).map_or(false, |kitty| !kitty.price.is_zero())
+            || <ReferenceListings<T, I>>::exists(kitty_id);
+        ensure!(!listed, "This cat is listed for sale");
+        Ok(())
+    }
+
+    /// Check that `side` can give its kitties to `to`, which gives back `returned` kitties
+    fn ensure_can_give(side: &TradeSide<T::AccountId, T::KittyIndex, BalanceOf<T, I>>, to: &T::AccountId, returned: usize) -> Result {
+        Self::ensure_not_barred(&side.account)?;
+        for kitty_id in side.kitties.iter() {
+            Self::ensure_can_trade(*kitty_id)?;
+            Self::ensure_can_transfer(&side.account, to, *kitty_id)?;
+        }
+        if let Some(max) = Self::max_kitties_per_account() {
+            let owned = Self::owned_kitties_count(to).saturating_sub(returned as u64);
+            ensure!(owned + side.kitties.len() as u64 <= u64::from(max), "The account owns too many kitties");
+        }
+        Ok(())
+    }
+
+    /// Give back the deposit of a trade to its proposer
+    fn return_trade_deposit(trade_id: u64, proposer: &T::AccountId) {
+        T::Currency::unreserve(proposer, <TradeDeposits<T, I>>::take(trade_id));
+    }
+
+    /// Pay the balance `from` gives in a trade, out of its reserve, as the price of the `kitties` it
+    /// gets back. Like a sale it pays the market fee and the royalties of the kitties, and it counts for
+    /// the market era limits. A balance given for no kitty is paid in full
+    fn pay_trade_balance(from: &T::AccountId, to: &T::AccountId, amount: BalanceOf<T, I>, kitties: &[T::KittyIndex]) {
+        if amount.is_zero() || kitties.is_empty() {
+            Self::pay_reserved(from, to, amount);
+            return;
+        }
+
+        let fee = Self::market_fee() * amount;
+        Self::pay_reserved(from, &Self::treasury_account(), fee);
+        let count = BalanceOf::<T, I>::unique_saturated_from(kitties.len() as u64);
+        let proceeds_per_kitty = (amount - fee) / count;
+        let mut paid = fee;
+        for kitty_id in kitties {
+            for (beneficiary, royalty) in Self::royalty_payouts(*kitty_id, proceeds_per_kitty) {
+                Self::pay_reserved(from, &beneficiary, royalty);
+                paid += royalty;
+                Self::deposit_event(RawEvent::RoyaltyPaid(beneficiary, *kitty_id, royalty));
+            }
+            Self::watch_market(amount / count);
+        }
+        Self::pay_reserved(from, to, amount - paid);
+    }
+
+    fn do_confirm_trade(sender: &T::AccountId, trade_id: u64) -> Result {
+        Self::ensure_not_paused(Subsystem::Settlement)?;
+        let mut trade = Self::otc_trade(trade_id).ok_or("No such trade")?;
+        let (own, other) = if trade.proposer.account == *sender {
+            (&trade.proposer, &trade.counterparty)
+        } else if trade.counterparty.account == *sender {
+            (&trade.counterparty, &trade.proposer)
+        } else {
+            return Err("You are not part of this trade");
+        };
+        ensure!(!own.confirmed, "You already confirmed this trade");
+        Self::ensure_can_give(own, &other.account, other.kitties.len())?;
+
+        if !other.confirmed {
+            T::Currency::reserve(sender, own.balance)?;
+            Self::trade_side_of(&mut trade, sender)?.confirmed = true;
+            <OtcTrades<T, I>>::insert(trade_id, trade);
+            Self::deposit_event(RawEvent::TradeConfirmed(trade_id, sender.clone()));
+            return Ok(());
+        }
+
+        Self::ensure_can_give(other, &own.account, own.kitties.len())?;
+        T::Currency::reserve(sender, own.balance)?;
+        <OtcTrades<T, I>>::remove(trade_id);
+        Self::return_trade_deposit(trade_id, &trade.proposer.account);
+        for (from, to) in &[(&trade.proposer, &trade.counterparty), (&trade.counterparty, &trade.proposer)] {
+            Self::pay_trade_balance(&from.account, &to.account, from.balance, &to.kitties);
+            for kitty_id in from.kitties.iter() {
+                Self::transfer_ownership(&from.account, to.account.clone(), *kitty_id, Acquisition::Transfer);
+            }
+        }
+        Self::deposit_event(RawEvent::TradeConfirmed(trade_id, sender.clone()));
+        Self::deposit_event(RawEvent::TradeExecuted(trade_id));
+        Ok(())
+    }
+
+    fn do_redeem_voucher(recipient: &T::AccountId, code: Vec<u8>) -> Result {
+        let code_hash = T::Hashing::hash(&code);
+        let voucher = Self::voucher(code_hash).ok_or("No such voucher")?;
+        let now = <system::Module<T>>::block_number();
+        ensure!(now < voucher.expires_at, "The voucher has expired");
+        let (claim_hash, committed_at) = Self::voucher_claim(recipient).ok_or("No voucher claim is committed")?;
+        ensure!(claim_hash == Self::voucher_claim_hash(&code, recipient), "The voucher claim is for another code");
+        ensure!(committed_at < now, "The voucher claim must be committed in an earlier block");
+
+        // The listings are sorted by price, the first one the recipient can buy is the cheapest
+        let listing = Self::listings_under(voucher.amount, MAX_PAGE_SIZE).into_iter()
+            .find(|(kitty_id, _)| Self::ensure_can_buy(recipient, *kitty_id, voucher.amount).is_ok());
+
+        Self::pay_reserved(&voucher.buyer, recipient, voucher.amount);
+        <Vouchers<T, I>>::remove(code_hash);
+        <VoucherClaims<T, I>>::remove(recipient);
+
+        // The funds are the recipient's now, if no kitty can be bought they keep them
+        let purchase = match listing {
+            Some((kitty_id, _)) => Some(Self::do_buy_kitty(recipient, kitty_id, voucher.amount).map(|_| kitty_id)),
+            None => {
+                let cost = Self::gen0_price().map(|price| price.saturating_add(Self::creation_price()));
+                match cost {
+                    Ok(cost) if cost <= voucher.amount => Some(Self::create_gen0(recipient, true, KITTY_SPECIES)),
+                    _ => None,
+                }
+            }
+        };
+        let kitty_id = match purchase {
+            Some(Ok(kitty_id)) => Some(kitty_id),
+            Some(Err(e)) => {
+                Self::deposit_event(RawEvent::VoucherPurchaseFailed(recipient.clone(), code_hash, e.as_bytes().to_vec()));
+                None
+            }
+            None => None,
+        };
+        Self::deposit_event(RawEvent::VoucherRedeemed(recipient.clone(), code_hash, kitty_id));
+        Ok(())
+    }
+
+    /// The commitment `recipient` makes with `commit_voucher_claim` before redeeming the voucher of `code`
+    pub fn voucher_claim_hash(code: &[u8], recipient: &T::AccountId) -> T::Hash {
+        T::Hashing::hash_of(&(b"kitties-voucher-claim", code, recipient))
+    }
+
+    /// Royalty of each beneficiary on `proceeds`, the rounding stays with the seller
+    fn royalty_payouts(kitty_id: T::KittyIndex, proceeds: BalanceOf<T, I>) -> Vec<(T::AccountId, BalanceOf<T, I>)> {
+        let royalty = match Self::royalty_of(kitty_id) {
+            Some(royalty) => royalty,
+            None => return Vec::new(),
+        };
+        let total = royalty.rate * proceeds;
+        // The shares are checked when set, the payouts still never add up to more than `total`
+        let mut left = total;
+        royalty.splits.into_iter()
+            .map(|(beneficiary, share)| {
+                let amount = (Permill::from_parts(share.min(1000).saturating_mul(1000)) * total).min(left);
+                left -= amount;
+                (beneficiary, amount)
+            })
+            .filter(|(_, amount)| !amount.is_zero())
+            .collect()
+    }
+
+    /// Whether the kitty has a decay which reached its end price, left for `collect_garbage`
+    pub(crate) fn is_finished_auction(kitty_id: T::KittyIndex) -> bool {
+        let now = <system::Module<T>>::block_number();
+        Self::price_decay(kitty_id).map_or(false, |decay| now >= decay.end)
+    }
+
+    /// Price a listed kitty sells at now, following its decay if it has one
+    pub fn current_price(kitty_id: T::KittyIndex, listed_price: BalanceOf<T, I>) -> BalanceOf<T, I> {
+        let decay = match Self::price_decay(kitty_id) {
+            Some(decay) => decay,
+            None => return listed_price,
+        };
+        let now = <system::Module<T>>::block_number();
+        if now >= decay.end {
+            return decay.end_price;
+        }
+        let elapsed: u64 = now.saturating_sub(decay.start).unique_saturated_into();
+        let duration: u64 = (decay.end - decay.start).unique_saturated_into();
+        listed_price - Permill::from_rational_approximation(elapsed, duration) * (listed_price - decay.end_price)
+    }
+
+    fn do_buy_kitty(sender: &T::AccountId, kitty_id: T::KittyIndex, max_price: BalanceOf<T, I>) -> Result {
+        let (owner, kitty_price) = Self::ensure_can_buy(sender, kitty_id, max_price)?;
+
+        // Reserving the payment is the last check: once it holds, the settlement can not fail
+        T::Currency::reserve(&sender, kitty_price)?;
+        Self::settle_sale(&owner, sender, kitty_id, kitty_price);
+
+        Ok(())
+    }
+
+    /// Pay `amount` out of the reserve of `from` to `to`, creating the account of `to` if needed.
+    /// Unlike `repatriate_reserved` it can not fail, the caller made sure `amount` is reserved
+    fn pay_reserved(from: &T::AccountId, to: &T::AccountId, amount: BalanceOf<T, I>) {
+        let (payment, _) = T::Currency::slash_reserved(from, amount);
+        T::Currency::resolve_creating(to, payment);
+    }
+
+    /// Free balance of a module account over the existential deposit, what it can pay out without
+    /// being reaped
+    fn spendable_balance(account: &T::AccountId) -> BalanceOf<T, I> {
+        T::Currency::free_balance(account).saturating_sub(T::Currency::minimum_balance())
+    }
+
+    /// Pay a sale out of the buyer's reserve and hand the kitty over, the checks of the sale are done
+    /// and `price` is reserved. The funds are settled first, the ownership last. The market fee goes to the treasury
+    fn settle_sale(seller: &T::AccountId, buyer: &T::AccountId, kitty_id: T::KittyIndex, price: BalanceOf<T, I>) {
+        let full_fee = Self::market_fee() * price;
+        let credit = Self::market_fee_credit(seller);
+        let fee = full_fee - credit.min(full_fee);
+        let burned = (Self::sale_burn_rate() * price).min(price - fee);
+        let donation = match (Self::listing_donation(kitty_id), Self::charity_account()) {
+            (Some(share), Some(charity)) => Some((share * (price - fee - burned), charity)),
+            _ => None,
+        };
+        let donated = donation.as_ref().map_or_else(Zero::zero, |(amount, _)| *amount);
+        let royalties = Self::royalty_payouts(kitty_id, price - fee - burned - donated);
+        let royalty_total = royalties.iter().fold(Zero::zero(), |total: BalanceOf<T, I>, (_, amount)| total + *amount);
+        Self::pay_reserved(buyer, seller, price - fee - burned - donated - royalty_total);
+        if let Some((amount, charity)) = &donation {
+            Self::pay_reserved(buyer, charity, *amount);
+        }
+        for (beneficiary, amount) in royalties {
+            Self::pay_reserved(buyer, &beneficiary, amount);
+            Self::deposit_event(RawEvent::RoyaltyPaid(beneficiary, kitty_id, amount));
+        }
+        Self::pay_reserved(buyer, &Self::treasury_account(), fee);
+        if full_fee > fee {
+            <MarketFeeCredit<T, I>>::insert(seller, credit - (full_fee - fee));
+        }
+        for account in &[seller, buyer] {
+            <LoyaltyPoints<T, I>>::mutate(*account, |points| *points = points.saturating_add(T::LoyaltyPointsPerTrade::get()));
+        }
+        if !burned.is_zero() {
+            let _ = T::Currency::slash_reserved(buyer, burned);
+            <TotalSaleBurned<T, I>>::mutate(|total| *total = total.saturating_add(burned));
+        }
+        Self::transfer_ownership(seller, buyer.clone(), kitty_id, Acquisition::Sale);
+
+        Self::update_listing_price(kitty_id, Zero::zero());
+
+        Self::deposit_kitty_event(kitty_id, &[seller, buyer], RawEvent::Sold(seller.clone(), buyer.clone(), kitty_id, price));
+        Self::note_quest_progress(seller, |objective| match objective {
+            QuestObjective::Sales(_) => true,
+            _ => false,
+        });
+        Self::notify_watchers(kitty_id, |watcher| RawEvent::WatchedKittySold(watcher, kitty_id, price));
+        <ListingDonation<T, I>>::remove(kitty_id);
+        if let Some((amount, charity)) = donation {
+            Self::record_donation(seller, charity, kitty_id, amount);
+        }
+
+        Self::watch_market(price);
+        <RecentSales<T, I>>::mutate(|sales| {
+            sales.push(price);
+            if sales.len() > RECENT_SALES_SIZE {
+                sales.remove(0);
+            }
+        });
+
+        let mut highest_sales = Self::highest_sales();
+        let is_new_high = highest_sales.iter()
+            .find(|(id, _)| *id == kitty_id)
+            .map(|(_, high)| *high < price)
+            .unwrap_or(true);
+        if is_new_high {
+            update_leaderboard(&mut highest_sales, kitty_id, price);
+            <HighestSales<T, I>>::put(highest_sales);
+        }
+    }
+
+    /// Add a sale to the market era stats, pausing settlement once the era's volume
+    /// or abnormal sales are over their limit. Called before the sale is in `RecentSales`
+    fn watch_market(price: BalanceOf<T, I>) {
+        let now = <system::Module<T>>::block_number();
+        let era: u32 = (now / MARKET_ERA_LENGTH.into()).unique_saturated_into();
+        let mut stats = Self::market_stats();
+        if stats.era != era {
+            stats = MarketEraStats { era, ..Default::default() };
+        }
+
+        stats.volume = stats.volume.saturating_add(price);
+        let recent_sales = Self::recent_sales();
+        if let Some(limit) = Self::market_deviation_limit() {
+            if !recent_sales.is_empty() {
+                let total = recent_sales.iter().fold(Zero::zero(), |total: BalanceOf<T, I>, sale| total.saturating_add(*sale));
+                let average = total / BalanceOf::<T, I>::unique_saturated_from(recent_sales.len() as u64);
+                let deviation = if price > average { price - average } else { average - price };
+                if deviation > limit * average {
+                    stats.abnormal_sales = stats.abnormal_sales.saturating_add(1);
+                }
+            }
+        }
+
+        let over_volume = Self::market_volume_limit().map_or(false, |limit| stats.volume > limit);
+        if (over_volume || stats.abnormal_sales >= MAX_ABNORMAL_SALES) && !Self::is_paused(Subsystem::Settlement) {
+            <PausedSubsystems<I>>::insert(Subsystem::Settlement, true);
+            Self::deposit_event(RawEvent::SubsystemPaused(Subsystem::Settlement, true));
+            Self::deposit_event(RawEvent::MarketCircuitBroken(stats.volume, stats.abnormal_sales));
+        }
+        <MarketStats<T, I>>::put(stats);
+    }
+
+    /// Account holding the market fees of the sales until buybacks spend them
+    pub fn treasury_account() -> T::AccountId {
+        Self::module_account(TREASURY_ID)
+    }
+
+    /// Market fees in the treasury account not spent on buybacks yet
+    pub fn market_treasury() -> BalanceOf<T, I> {
+        Self::spendable_balance(&Self::treasury_account())
+    }
+
+    /// Buy back and burn the cheap listings among the next `max_scanned` kitties. Kitties which can not
+    /// change hands are skipped
+    fn do_buyback(budget: BalanceOf<T, I>, max_scanned: u32) {
+        let treasury = Self::treasury_account();
+        let floor = Self::buyback_floor();
+        let mut remaining = budget.min(Self::market_treasury());
+        let scanned: Vec<T::KittyIndex> = <AllKittiesList<T, I>>::iter_after(&(), Self::buyback_cursor())
+            .take(max_scanned as usize)
+            .collect();
+
+        for kitty_id in scanned.iter().cloned() {
+            let price = match Self::kitty(kitty_id) {
+                Some(kitty) => kitty.price,
+                None => continue,
+            };
+            if price.is_zero() || price > floor || price > remaining {
+                continue;
+            }
+            let seller = match Self::owner_of(kitty_id) {
+                Some(seller) => seller,
+                None => continue,
+            };
+            let can_sell = Self::ensure_not_blacklisted(kitty_id)
+                .and_then(|_| Self::ensure_not_barred(&seller))
+                .and_then(|_| Self::ensure_not_co_owned(kitty_id))
+                .and_then(|_| Self::ensure_not_locked(kitty_id))
+                .is_ok();
+            if !can_sell {
+                continue;
+            }
+
+            // A seller without an account can not be paid less than the existential deposit
+            if T::Currency::transfer(&treasury, &seller, price).is_err() {
+                continue;
+            }
+            remaining -= price;
+            Self::remove_kitty(&seller, kitty_id);
+            Self::deposit_event(RawEvent::BoughtBack(seller, kitty_id, price));
+        }
+
+        if (scanned.len() as u32) < max_scanned {
+            <BuybackCursor<T, I>>::kill();
+        } else {
+            // The last scanned kitty may be burned, the cursor must stay in the list
+            match scanned.iter().rev().find(|kitty_id| <Kitties<T, I>>::exists(*kitty_id)) {
+                Some(kitty_id) => <BuybackCursor<T, I>>::put(kitty_id),
+                None => <BuybackCursor<T, I>>::kill(),
+            }
+        }
+    }
+
+    fn do_buy_kitty_escrowed(sender: &T::AccountId, kitty_id: T::KittyIndex, max_price: BalanceOf<T, I>) -> Result {
+        let (owner, kitty_price) = Self::ensure_can_buy(sender, kitty_id, max_price)?;
+
+        T::Currency::reserve(&sender, kitty_price)?;
+
+        // The kitty is delisted and locked until the trade is closed
+        Self::update_listing_price(kitty_id, Zero::zero());
+        Self::note_kitty_changed(kitty_id);
+
+        // Releases run at the start of a block, so a trade settles one block later at the earliest
+        let window = T::DisputeWindow::get().max(1.into());
+        let release_at = <system::Module<T>>::block_number().saturating_add(window);
+        <EscrowedTrades<T, I>>::insert(kitty_id, EscrowedTrade {
+            seller: owner.clone(),
+            buyer: sender.clone(),
+            price: kitty_price,
+            release_at,
+            disputed: false,
+        });
+        <EscrowReleases<T, I>>::mutate(release_at, |kitties| kitties.push(kitty_id));
+
+        Self::deposit_kitty_event(
+            kitty_id,
+            &[&owner, sender],
+            RawEvent::TradeEscrowed(owner.clone(), sender.clone(), kitty_id, kitty_price, release_at),
+        );
+
+        Ok(())
+    }
+
+    /// Settle the undisputed escrowed trades whose window ends at block `now`. While settlement is
+    /// paused the trades are pushed back one block at a time until it resumes
+    fn release_escrowed_trades(now: T::BlockNumber) {
+        let paused = Self::is_paused(Subsystem::Settlement);
+        for kitty_id in <EscrowReleases<T, I>>::take(now) {
+            let mut trade = match Self::escrowed_trade(kitty_id) {
+                Some(trade) if !trade.disputed && trade.release_at == now => trade,
+                _ => continue,
+            };
+            if paused {
+                let release_at = now.saturating_add(1.into());
+                trade.release_at = release_at;
+                <EscrowedTrades<T, I>>::insert(kitty_id, trade);
+                <EscrowReleases<T, I>>::mutate(release_at, |kitties| kitties.push(kitty_id));
+                continue;
+            }
+            <EscrowedTrades<T, I>>::remove(kitty_id);
+
+            Self::settle_sale(&trade.seller, &trade.buyer, kitty_id, trade.price);
+            Self::deposit_event(RawEvent::TradeReleased(kitty_id, true));
+        }
+    }
+
+    /// `ensure_signed`, also recording the activity of accounts with heirs and counting the call against
+    /// `MaxOperationsPerBlock`. Any call to the module counts, even a failing one: it still shows the owner
+    /// is around, and failing calls are how randomness is ground
+    fn ensure_signed_active(origin: T::Origin) -> result::Result<T::AccountId, &'static str> {
+        let who = ensure_signed(origin)?;
+        Self::note_operation(&who)?;
+        Ok(who)
+    }
+
+    /// Record the activity of `who` if it has heirs and count its call against `MaxOperationsPerBlock`,
+    /// for the signed calls and the sponsored calls made in its name
+    fn note_operation(who: &T::AccountId) -> Result {
+        if <Heirs<T, I>>::exists(who) {
+            <LastActive<T, I>>::insert(who, <system::Module<T>>::block_number());
+        }
+        Self::count_block_operation(who)
+    }
+
+    fn count_block_operation(who: &T::AccountId) -> Result {
+        let max = match Self::max_operations_per_block() {
+            Some(max) => max,
+            None => return Ok(()),
+        };
+        let operations = Self::block_operations(who);
+        ensure!(operations < max, "Too many kitty calls by this account in this block");
+
+        if operations == 0 {
+            <BlockOperators<T, I>>::mutate(|operators| operators.push(who.clone()));
+        }
+        <BlockOperations<T, I>>::insert(who, operations + 1);
+        Ok(())
+    }
+
+    /// Start the per-account call counters over for a new block
+    fn clear_block_operations() {
+        for who in <BlockOperators<T, I>>::take() {
+            <BlockOperations<T, I>>::remove(&who);
+        }
+    }
+
+    fn do_complete_inheritance(heir: &T::AccountId, owner: &T::AccountId) -> Result {
+        let (claimant, claimed_at) = Self::inheritance_claim(owner).ok_or("The collection is not claimed")?;
+        ensure!(claimant == *heir, "Only the claiming heir can complete the inheritance");
+        let (_, delay) = Self::heirs_of(owner).ok_or("This account has no heirs")?;
+        ensure!(<system::Module<T>>::block_number() >= claimed_at.saturating_add(delay), "The veto delay is not over");
+        ensure!(Self::last_active(owner) < claimed_at, "The owner was active since the claim");
+        Self::ensure_not_paused(Subsystem::Transfers)?;
+
+        // Kitties left with the owner stay in its list, the batch starts after the last one. A cursor
+        // which left the list meanwhile starts over from the head
+        let cursor = Self::inheritance_cursor(owner).filter(|kitty_id| Self::owner_of(kitty_id).as_ref() == Some(owner));
+        // Collected first, the owner's list changes with every move
+        let kitties: Vec<_> = <OwnedKittiesList<T, I>>::iter_after(owner, cursor).take(INHERITANCE_BATCH).collect();
+        let scanned = kitties.len();
+        let mut moved = 0;
+        let mut last_skipped = cursor;
+        for kitty_id in kitties {
+            if Self::ensure_not_co_owned(kitty_id).is_ok() && Self::ensure_can_transfer(owner, heir, kitty_id).is_ok() {
+                Self::transfer_ownership(owner, heir.clone(), kitty_id, Acquisition::Inheritance);
+                moved += 1;
+            } else {
+                last_skipped = Some(kitty_id);
+            }
+        }
+
+        if scanned < INHERITANCE_BATCH {
+            <InheritanceClaims<T, I>>::remove(owner);
+            <InheritanceCursor<T, I>>::remove(owner);
+        } else if let Some(kitty_id) = last_skipped {
+            <InheritanceCursor<T, I>>::insert(owner, kitty_id);
+        }
+        Self::deposit_event(RawEvent::KittiesInherited(owner.clone(), heir.clone(), moved as u32));
+        Ok(())
+    }
+
+    /// A kitty can not move nor be listed while soulbound, fractionalized, in escrow or scheduled for a transfer
+    fn ensure_not_locked(kitty_id: T::KittyIndex) -> Result {
+        ensure!(!Self::kitty(kitty_id).map_or(false, |kitty| kitty.soulbound), "This cat is soulbound");
+        Self::ensure_not_fractionalized(kitty_id)?;
+        ensure!(!<EscrowedTrades<T, I>>::exists(kitty_id), "This cat is in escrow");
+        ensure!(!<ScheduledTransfers<T, I>>::exists(kitty_id), "This cat is scheduled for a transfer");
+        Ok(())
+    }
+
+    fn do_schedule_transfer(sender: &T::AccountId, to: T::AccountId, kitty_id: T::KittyIndex, at: T::BlockNumber) -> Result {
+        Self::ensure_can_transfer(sender, &to, kitty_id)?;
+        ensure!(at > <system::Module<T>>::block_number(), "The transfer must be scheduled for a future block");
+
+        // The kitty is delisted and locked until the transfer
+        Self::update_listing_price(kitty_id, Zero::zero());
+        Self::note_kitty_changed(kitty_id);
+
+        <ScheduledTransfers<T, I>>::insert(kitty_id, (to.clone(), at));
+        <ScheduledTransferIndex<T, I>>::mutate(at, |kitties| kitties.push(kitty_id));
+        Self::deposit_kitty_event(kitty_id, &[sender, &to], RawEvent::TransferScheduled(sender.clone(), to, kitty_id, at));
+
+        Ok(())
+    }
+
+    /// Run the transfers scheduled for block `now`. A transfer which is no longer allowed
+    /// (transfers paused, recipient at its kitty cap) is called off and the kitty unlocked
+    fn execute_scheduled_transfers(now: T::BlockNumber) {
+        for kitty_id in <ScheduledTransferIndex<T, I>>::take(now) {
+            let to = match Self::scheduled_transfer(kitty_id) {
+                Some((to, at)) if at == now => to,
+                _ => continue,
+            };
+            <ScheduledTransfers<T, I>>::remove(kitty_id);
+
+            let owner = match Self::owner_of(kitty_id) {
+                Some(owner) => owner,
+                None => continue,
+            };
+            let executed = Self::ensure_can_transfer(&owner, &to, kitty_id).is_ok();
+            if executed {
+                Self::transfer_ownership(&owner, to, kitty_id, Acquisition::Transfer);
+            }
+            Self::deposit_event(RawEvent::ScheduledTransferExecuted(kitty_id, executed));
+        }
+    }
+
+    fn ensure_market_enabled() -> Result {
+        ensure!(cfg!(feature = "market") && T::MarketEnabled::get(), "The market is not enabled");
+        Ok(())
+    }
+
+    fn ensure_can_set_price(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        Self::ensure_market_enabled()?;
+        Self::ensure_not_paused(Subsystem::Market)?;
+        ensure!(<Kitties<T, I>>::exists(kitty_id), "This cat does not exist");
+
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(owner == *sender, "You do not own this cat");
+        Self::ensure_not_locked(kitty_id)?;
+        Self::ensure_not_blacklisted(kitty_id)?;
+        Self::ensure_not_barred(sender)
+    }
+
+    fn do_set_price(sender: &T::AccountId, kitty_id: T::KittyIndex, new_price: BalanceOf<T, I>) -> Result {
+        Self::ensure_can_set_price(sender, kitty_id)?;
+
+        Self::update_listing_price(kitty_id, new_price);
+        if new_price.is_zero() {
+            <ListingDonation<T, I>>::remove(kitty_id);
+        }
+        Self::note_kitty_changed(kitty_id);
+
+        let verified = Self::is_verified_breeder(sender);
+        Self::deposit_kitty_event(kitty_id, &[sender], RawEvent::Listed(sender.clone(), kitty_id, new_price, verified));
+        Self::notify_watchers(kitty_id, |watcher| RawEvent::WatchedKittyListed(watcher, kitty_id, new_price));
+
+        Ok(())
+    }
+
+    /// Link a kitty into its owner's list, keeping `OwnedKittiesCount` in step
+    fn append_owned_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+        if <OwnedKittiesList<T, I>>::append(owner, kitty_id) {
+            Self::update_collection_size(owner, true);
+        }
+    }
+
+    /// Unlink a kitty from its owner's list, keeping `OwnedKittiesCount` in step
+    fn remove_owned_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+        if <OwnedKittiesList<T, I>>::remove(owner, kitty_id) {
+            Self::update_collection_size(owner, false);
+        }
+    }
+
+    fn update_collection_size(account: &T::AccountId, increase: bool) {
+        let count = <OwnedKittiesCount<T, I>>::mutate(account, |count| {
+            *count = if increase { count.saturating_add(1) } else { count.saturating_sub(1) };
+            *count
+        });
+
+        let mut board = Self::largest_collections();
+        update_leaderboard(&mut board, account.clone(), count);
+        <LargestCollections<T, I>>::put(board);
+    }
+
+    /// Set the price of a stored kitty, 0 to delist it, keeping the price index in step
+    fn update_listing_price(kitty_id: T::KittyIndex, new_price: BalanceOf<T, I>) {
+        let old_price = <Kitties<T, I>>::mutate(kitty_id, |kitty| kitty.as_mut().map(|kitty| {
+            let old_price = kitty.price;
+            kitty.price = new_price;
+            old_price
+        }));
+        if let Some(old_price) = old_price {
+            Self::reindex_listing(kitty_id, old_price, new_price);
+        }
+        <PriceDecays<T, I>>::remove(kitty_id);
+        <ReferenceListings<T, I>>::remove(kitty_id);
+    }
+
+    /// Move a kitty between the buckets of the price index, a zero price is not indexed
+    fn reindex_listing(kitty_id: T::KittyIndex, old_price: BalanceOf<T, I>, new_price: BalanceOf<T, I>) {
+        if old_price == new_price {
+            return;
+        }
+
+        if !old_price.is_zero() {
+            let mut bucket = Self::listings_at_price(old_price);
+            bucket.retain(|id| *id != kitty_id);
+            if bucket.is_empty() {
+                <ListingsAtPrice<T, I>>::remove(old_price);
+                Self::remove_listed_price(old_price);
+            } else {
+                <ListingsAtPrice<T, I>>::insert(old_price, bucket);
+            }
+        }
+
+        if !new_price.is_zero() {
+            let mut bucket = Self::listings_at_price(new_price);
+            if bucket.is_empty() {
+                Self::insert_listed_price(new_price);
+            }
+            bucket.push(kitty_id);
+            <ListingsAtPrice<T, I>>::insert(new_price, bucket);
+        }
+    }
+
+    /// Range of the price index a price falls in: its bit length, so each range holds the prices
+    /// of one power of two
+    fn price_range(price: BalanceOf<T, I>) -> u32 {
+        let price: u128 = price.unique_saturated_into();
+        128 - price.leading_zeros()
+    }
+
+    fn insert_listed_price(price: BalanceOf<T, I>) {
+        let range = Self::price_range(price);
+        let mut prices = Self::listed_prices_in_range(range);
+        if let Err(index) = prices.binary_search(&price) {
+            if prices.is_empty() {
+                <ListedPriceRanges<I>>::mutate(|ranges| if let Err(index) = ranges.binary_search(&range) {
+                    ranges.insert(index, range);
+                });
+            }
+            prices.insert(index, price);
+            <ListedPricesInRange<T, I>>::insert(range, prices);
+        }
+    }
+
+    fn remove_listed_price(price: BalanceOf<T, I>) {
+        let range = Self::price_range(price);
+        let mut prices = Self::listed_prices_in_range(range);
+        if let Ok(index) = prices.binary_search(&price) {
+            prices.remove(index);
+            if prices.is_empty() {
+                <ListedPricesInRange<T, I>>::remove(range);
+                <ListedPriceRanges<I>>::mutate(|ranges| if let Ok(index) = ranges.binary_search(&range) {
+                    ranges.remove(index);
+                });
+            } else {
+                <ListedPricesInRange<T, I>>::insert(range, prices);
+            }
+        }
+    }
+
+    /// Distinct prices of the kitties for sale, lowest first. It reads every range of the index
+    pub fn listed_prices() -> Vec<BalanceOf<T, I>> {
+        Self::listed_price_ranges().into_iter().flat_map(Self::listed_prices_in_range).collect()
+    }
+
+    /// Add a kitty to the bucket of each of its trait values which has room left, once, and count it
+    /// Traits of a creature, decoded the way of its species
+    fn traits_of(kitty: &Kitty<BalanceOf<T, I>>) -> Vec<(TraitKind, u8)> {
+        let species = Self::species_info(kitty.species).unwrap_or_else(Species::kitties);
+        TraitKind::ALL.iter().map(|kind| (*kind, species.trait_value(*kind, &kitty.dna))).collect()
+    }
+
+    fn index_traits(kitty_id: T::KittyIndex, kitty: &Kitty<BalanceOf<T, I>>) {
+        for key in Self::traits_of(kitty) {
+            <KittiesWithTrait<T, I>>::mutate(key, |bucket| {
+                if bucket.len() < MAX_TRAIT_BUCKET_SIZE && !bucket.contains(&kitty_id) {
+                    bucket.push(kitty_id);
+                }
+            });
+            <KittiesPerTrait<I>>::mutate(key, |count| *count = count.saturating_add(1));
+        }
+    }
+
+    fn unindex_traits(kitty_id: T::KittyIndex, kitty: &Kitty<BalanceOf<T, I>>) {
+        for key in Self::traits_of(kitty) {
+            let mut bucket = Self::kitties_with_trait(key);
+            bucket.retain(|id| *id != kitty_id);
+            if bucket.is_empty() {
+                <KittiesWithTrait<T, I>>::remove(key);
+            } else {
+                <KittiesWithTrait<T, I>>::insert(key, bucket);
+            }
+            <KittiesPerTrait<I>>::mutate(key, |count| *count = count.saturating_sub(1));
+        }
+    }
+
+    /// Art of a kitty, `None` if it does not exist or is not revealed yet
+    pub fn art_of(kitty_id: T::KittyIndex) -> Option<ArtDescriptor> {
+        if <PendingReveals<T, I>>::exists(kitty_id) {
+            return None;
+        }
+        Self::kitty(kitty_id).map(|kitty| ArtDescriptor::from_dna(&kitty.dna))
+    }
+
+    /// Key of the owner of a kitty in the state, an off-chain system checks a read proof of it
+    /// against the state root of a block to know who owned the kitty at that block
+    pub fn owner_storage_key(kitty_id: T::KittyIndex) -> Vec<u8> {
+        <KittyOwner<T, I>>::hashed_key_for(&kitty_id)
+    }
+
+    /// Lowest listed price of the kitties for sale. The index holds the listed prices: a decaying listing
+    /// is indexed at its start price and sells for less (see `current_price`), and a listing in reference
+    /// units at its conversion when it was listed
+    pub fn floor_price() -> Option<BalanceOf<T, I>> {
+        Self::listed_price_ranges().first().and_then(|range| Self::listed_prices_in_range(range).first().cloned())
+    }
+
+    /// At most `limit` kitties for sale at a listed price of `max_price` or less with that price, cheapest
+    /// first. Like `floor_price` it goes by the listed prices, not the decayed or reference ones
+    pub fn listings_under(max_price: BalanceOf<T, I>, limit: u32) -> Vec<(T::KittyIndex, BalanceOf<T, I>)> {
+        let limit = limit.min(MAX_PAGE_SIZE) as usize;
+        let max_range = Self::price_range(max_price);
+        Self::listed_price_ranges().into_iter()
+            .take_while(|range| *range <= max_range)
+            .flat_map(Self::listed_prices_in_range)
+            .take_while(|price| *price <= max_price)
+            .flat_map(|price| Self::listings_at_price(price).into_iter().map(move |kitty_id| (kitty_id, price)))
+            .take(limit)
+            .collect()
+    }
+
+    fn do_feature_listing(sender: &T::AccountId, kitty_id: T::KittyIndex, duration: T::BlockNumber) -> Result {
+        Self::ensure_market_enabled()?;
+        Self::ensure_not_paused(Subsystem::Market)?;
+        ensure!(!duration.is_zero(), "The duration must be greater than zero");
+        let kitty = Self::kitty(kitty_id).ok_or("This cat does not exist")?;
+        ensure!(Self::owner_of(kitty_id).as_ref() == Some(sender), "You do not own this cat");
+        ensure!(!kitty.price.is_zero(), "This cat is not for sale");
+
+        let mut featured = Self::featured_listings();
+        let now = <system::Module<T>>::block_number();
+        let position = featured.iter().position(|(id, _)| *id == kitty_id);
+        let until = match position {
+            Some(index) => featured[index].1.max(now),
+            None => {
+                ensure!(featured.len() < MAX_FEATURED_LISTINGS, "Too many featured listings");
+                now
+            }
+        }.saturating_add(duration);
+
+        let blocks: u32 = duration.unique_saturated_into();
+        let fee = Self::featured_listing_fee().saturating_mul(blocks.into());
+        if !fee.is_zero() {
+            let _ = T::Currency::withdraw(sender, fee, WithdrawReason::Fee, ExistenceRequirement::KeepAlive)?;
+        }
+
+        match position {
+            Some(index) => featured[index].1 = until,
+            None => featured.push((kitty_id, until)),
+        }
+        <FeaturedListings<T, I>>::put(featured);
+        Self::deposit_event(RawEvent::ListingFeatured(sender.clone(), kitty_id, until, fee));
+        Ok(())
+    }
+
+    /// Drop the featured listings which ended or whose kitty is no longer for sale
+    fn prune_featured_listings(now: T::BlockNumber) {
+        let featured = Self::featured_listings();
+        if featured.is_empty() {
+            return;
+        }
+        let kept: Vec<_> = featured.iter().cloned()
+            .filter(|(kitty_id, until)| *until > now && Self::kitty(kitty_id).map_or(false, |kitty| !kitty.price.is_zero()))
+            .collect();
+        if kept.len() != featured.len() {
+            <FeaturedListings<T, I>>::put(kept);
+        }
+    }
+
+    fn do_export_kitty(sender: &T::AccountId, kitty_id: T::KittyIndex, recipient: Vec<u8>) -> Result {
+        ensure!(!recipient.is_empty() && recipient.len() <= MAX_BRIDGE_ACCOUNT_LENGTH, "Invalid target chain account");
+        Self::ensure_not_paused(Subsystem::Transfers)?;
+        let kitty = Self::kitty(kitty_id).ok_or("This cat does not exist")?;
+        ensure!(Self::owner_of(kitty_id).as_ref() == Some(sender), "You do not own this cat");
+        Self::ensure_not_locked(kitty_id)?;
+        Self::ensure_not_blacklisted(kitty_id)?;
+        Self::ensure_revealed(kitty_id)?;
+        ensure!(kitty.species == KITTY_SPECIES, "Only kitties can be exported");
+
+        let export_id = Self::exports_count();
+        let next_export_id = export_id.checked_add(1).ok_or("Exports count overflow")?;
+        let export = KittyExport {
+            export_id,
+            dna: kitty.dna,
+            generation: kitty.generation,
+            recipient,
+        };
+        let commitment = T::Hashing::hash_of(&export);
+
+        Self::remove_kitty(sender, kitty_id);
+        <ExportCommitments<T, I>>::insert(export_id, commitment);
+        <ExportsCount<I>>::put(next_export_id);
+        Self::deposit_kitty_event(kitty_id, &[sender], RawEvent::KittyExported(sender.clone(), kitty_id, export_id, commitment));
+        Ok(())
+    }
+
+    fn do_import_kitty(source_chain: u32, export: KittyExport) -> Result {
+        ensure!(!Self::is_export_imported((source_chain, export.export_id)), "This export is already imported");
+        let recipient = T::AccountId::decode(&mut &export.recipient[..]).map_err(|_| "Invalid recipient")?;
+        Self::ensure_not_paused(Subsystem::Minting)?;
+
+        let kitty = Kitty {
+            dna: export.dna,
+            price: 0.into(),
+            generation: export.generation,
+            soulbound: false,
+            season: None,
+            species: KITTY_SPECIES,
+        };
+        let kitty_id = Self::insert_kitty(&recipient, kitty)?;
+        <ImportedExports<I>>::insert((source_chain, export.export_id), true);
+        Self::deposit_event(RawEvent::KittyImported(recipient, kitty_id, source_chain, export.export_id));
+        Ok(())
+    }
+
+    fn do_set_name(sender: &T::AccountId, kitty_id: T::KittyIndex, name: Vec<u8>) -> Result {
+        ensure!(Self::owner_of(kitty_id).as_ref() == Some(sender), "You do not own this cat");
+        if !name.is_empty() {
+            let named = Self::kitty_by_name(&name);
+            // Giving a kitty the name it has is a no-op, and free
+            if named == Some(kitty_id) {
+                return Ok(());
+            }
+            Self::ensure_valid_name(&name)?;
+            ensure!(named.is_none(), "This name is taken");
+            let fee = Self::naming_fee();
+            if !fee.is_zero() {
+                let _ = T::Currency::withdraw(sender, fee, WithdrawReason::Fee, ExistenceRequirement::KeepAlive)?;
+            }
+        }
+
+        Self::clear_name(kitty_id);
+        if !name.is_empty() {
+            <KittyNames<T, I>>::insert(kitty_id, name.clone());
+            <NameToKitty<T, I>>::insert(&name, kitty_id);
+        }
+        Self::deposit_kitty_event(kitty_id, &[sender], RawEvent::KittyNamed(sender.clone(), kitty_id, name));
+        Ok(())
+    }
+
+    fn ensure_valid_name(name: &[u8]) -> Result {
+        let max_length = Self::max_name_length().unwrap_or(DEFAULT_MAX_NAME_LENGTH);
+        ensure!(name.len() <= max_length as usize, "The name is too long");
+        ensure!(rstd::str::from_utf8(name).is_ok(), "The name is not valid UTF-8");
+        let banned = Self::banned_name_prefixes().iter()
+            .any(|prefix| name.len() >= prefix.len() && name[..prefix.len()].eq_ignore_ascii_case(prefix));
+        ensure!(!banned, "The name starts with a banned prefix");
+        Ok(())
+    }
+
+    fn do_set_metadata(sender: &T::AccountId, kitty_id: T::KittyIndex, metadata: Vec<u8>) -> Result {
+        ensure!(Self::owner_of(kitty_id).as_ref() == Some(sender), "You do not own this cat");
+        let max_length = Self::max_metadata_length().unwrap_or(DEFAULT_MAX_METADATA_LENGTH);
+        ensure!(metadata.len() <= max_length as usize, "The metadata is too long");
+        ensure!(rstd::str::from_utf8(&metadata).is_ok(), "The metadata is not valid UTF-8");
+
+        if metadata.is_empty() {
+            <KittyMetadata<T, I>>::remove(kitty_id);
+        } else {
+            <KittyMetadata<T, I>>::insert(kitty_id, metadata);
+        }
+        Self::deposit_kitty_event(kitty_id, &[sender], RawEvent::KittyMetadataSet(sender.clone(), kitty_id));
+        Ok(())
+    }
+
+    fn clear_name(kitty_id: T::KittyIndex) {
+        if let Some(name) = <KittyNames<T, I>>::take(kitty_id) {
+            <NameToKitty<T, I>>::remove(&name);
+        }
+    }
+
+    fn do_tip(sender: &T::AccountId, kitty_id: T::KittyIndex, amount: BalanceOf<T, I>) -> Result {
+        ensure!(!amount.is_zero(), "The tip must be greater than zero");
+        let owner = Self::owner_of(kitty_id).ok_or("This cat does not exist")?;
+        ensure!(owner != *sender, "You can not tip your own cat");
+        let tips = Self::tips_of(kitty_id).checked_add(&amount).ok_or("Tips overflow")?;
+
+        let tip = T::Currency::withdraw(sender, amount, WithdrawReason::Transfer, ExistenceRequirement::KeepAlive)?;
+        T::Currency::resolve_creating(&owner, tip);
+        <KittyTips<T, I>>::insert(kitty_id, tips);
+
+        Self::deposit_kitty_event(kitty_id, &[sender, &owner], RawEvent::Tipped(sender.clone(), owner.clone(), kitty_id, amount));
+        Ok(())
+    }
+
+    fn record_donation(seller: &T::AccountId, charity: T::AccountId, kitty_id: T::KittyIndex, amount: BalanceOf<T, I>) {
+        if amount.is_zero() {
+            return;
+        }
+        let total = <Donated<T, I>>::mutate(seller, |donated| {
+            *donated = donated.saturating_add(amount);
+            *donated
+        });
+
+        let mut board = Self::top_donors();
+        update_leaderboard(&mut board, seller.clone(), total);
+        <TopDonors<T, I>>::put(board);
+        Self::deposit_event(RawEvent::Donated(seller.clone(), charity, kitty_id, amount));
+        Self::unlock_achievement(seller, ACHIEVEMENT_FIRST_DONATION);
+    }
+
+    /// Record a battle won by `winner`, called by the battle code paths
+    pub fn record_battle_win(winner: &T::AccountId) {
+        let wins = <BattleWins<T, I>>::mutate(winner, |wins| {
+            *wins = wins.saturating_add(1);
+            *wins
+        });
+
+        let mut board = Self::most_battle_wins();
+        update_leaderboard(&mut board, winner.clone(), wins);
+        <MostBattleWins<T, I>>::put(board);
+    }
+
+    /// Bring the storage up to `STORAGE_VERSION`, all of it in the first block of the new runtime.
+    ///
+    /// The steps walk every kitty id below `KittiesCount`, up to nine times from version 0, and none of it is
+    /// weighed. They are sized for the lesson chains this module runs on: with more than `MAX_MIGRATED_KITTIES`
+    /// kitty ids nothing is migrated, the storage stays on its version and each block logs it.
+    fn migrate_storage() {
+        let version = Self::storage_version();
+        if version >= STORAGE_VERSION {
+            return;
+        }
+
+        let kitties_count: u64 = Self::kitties_count().unique_saturated_into();
+        if kitties_count > MAX_MIGRATED_KITTIES {
+            runtime_io::print("Too many kitties to migrate the kitties storage in one block");
+            return;
+        }
+
+        // Each translation writes the current `Kitty` layout, so at most one of them runs
+        let mut kitties_translated = false;
+
+        if version < 1 {
+            if Self::has_lesson_4_layout() {
+                Self::migrate_from_lesson_4();
+                kitties_translated = true;
+            } else {
+                Self::migrate_owned_kitties_to_double_map();
+            }
+        }
+
+        if version < 2 {
+            let mut kitty_id = T::KittyIndex::default();
+            while kitty_id < Self::kitties_count() {
+                if <Kitties<T, I>>::exists(kitty_id) {
+                    <AllKittiesList<T, I>>::append(&(), kitty_id);
+                }
+                kitty_id += 1.into();
+            }
+        }
+
+        if version < 3 && !kitties_translated {
+            Self::translate_kitties(|kitty: KittyV1<BalanceOf<T, I>>| Kitty {
+                dna: kitty.dna,
+                price: kitty.price,
+                generation: 0,
+                soulbound: false,
+                season: None,
+                species: KITTY_SPECIES,
+            });
+            kitties_translated = true;
+        }
+
+        if version < 4 {
+            Self::migrate_owned_kitties_to_child_tries();
+        }
+
+        if version < 5 && !kitties_translated {
+            Self::translate_kitties(|kitty: KittyV2<BalanceOf<T, I>>| Kitty {
+                dna: kitty.dna,
+                price: kitty.price,
+                generation: kitty.generation,
+                soulbound: false,
+                season: None,
+                species: KITTY_SPECIES,
+            });
+            kitties_translated = true;
+        }
+
+        if version < 6 && !kitties_translated {
+            Self::translate_kitties(|kitty: KittyV3<BalanceOf<T, I>>| Kitty {
+                dna: kitty.dna,
+                price: kitty.price,
+                generation: kitty.generation,
+                soulbound: false,
+                season: None,
+                species: KITTY_SPECIES,
+            });
+            kitties_translated = true;
+        }
+
+        if version < 7 && !kitties_translated {
+            Self::translate_kitties(|kitty: KittyV4<BalanceOf<T, I>>| Kitty {
+                dna: kitty.dna,
+                price: kitty.price,
+                generation: kitty.generation,
+                soulbound: kitty.soulbound,
+                season: None,
+                species: KITTY_SPECIES,
+            });
+            kitties_translated = true;
+        }
+
+        if version < 11 && !kitties_translated {
+            Self::translate_kitties(|kitty: KittyV5<BalanceOf<T, I>>| Kitty {
+                dna: kitty.dna,
+                price: kitty.price,
+                generation: kitty.generation,
+                soulbound: kitty.soulbound,
+                season: kitty.season,
+                species: KITTY_SPECIES,
+            });
+        }
+
+        // After the translations, which keep the stored prices
+        if version < 8 {
+            Self::rebuild_price_index();
+        }
+
+        if version < 9 {
+            let mut kitty_id = T::KittyIndex::default();
+            while kitty_id < Self::kitties_count() {
+                if let Some(kitty) = Self::kitty(kitty_id) {
+                    Self::index_traits(kitty_id, &kitty);
+                }
+                kitty_id += 1.into();
+            }
+        }
+
+        if version < 10 {
+            Self::recount_generations();
+        }
+
+        if version < 12 {
+            Self::split_listed_prices();
+        }
+
+        if version < 13 {
+            Self::recount_traits();
+        }
+
+        <StorageVersion<I>>::put(STORAGE_VERSION);
+    }
+
+    /// Count the live kitties of each generation from scratch
+    fn recount_generations() {
+        let mut counts: Vec<u64> = Vec::new();
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            if let Some(kitty) = Self::kitty(kitty_id) {
+                let generation = kitty.generation as usize;
+                if counts.len() <= generation {
+                    counts.resize(generation + 1, 0);
+                }
+                counts[generation] += 1;
+            }
+            kitty_id += 1.into();
+        }
+        for (generation, count) in counts.into_iter().enumerate() {
+            <KittiesPerGeneration<I>>::insert(generation as u32, count);
+        }
+    }
+
+    /// Count the live kitties with each trait value from scratch
+    fn recount_traits() {
+        let mut counts: Vec<((TraitKind, u8), u64)> = Vec::new();
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            if let Some(kitty) = Self::kitty(kitty_id) {
+                for key in Self::traits_of(&kitty) {
+                    match counts.iter_mut().find(|(counted, _)| *counted == key) {
+                        Some((_, count)) => *count += 1,
+                        None => counts.push((key, 1)),
+                    }
+                }
+            }
+            kitty_id += 1.into();
+        }
+        for (key, count) in counts {
+            <KittiesPerTrait<I>>::insert(key, count);
+        }
+    }
+
+    /// Storage prefix of the item `name` of this instance, to migrate items the typed storage can't decode
+    fn storage_prefix(name: &[u8]) -> Vec<u8> {
+        [instance_name::<I>(), &b"Kitties "[..], name].concat()
+    }
+
+    /// Move the single sorted `ListedPrices` list of the price index into its ranges
+    fn split_listed_prices() {
+        let key = twox_128(&Self::storage_prefix(b"ListedPrices"));
+        let prices = runtime_io::storage(&key)
+            .and_then(|raw| Vec::<BalanceOf<T, I>>::decode(&mut &raw[..]).ok())
+            .unwrap_or_default();
+        runtime_io::clear_storage(&key);
+
+        for price in prices {
+            Self::insert_listed_price(price);
+        }
+    }
+
+    /// Index every kitty for sale, for kitties listed before the index existed
+    fn rebuild_price_index() {
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            if let Some(kitty) = Self::kitty(kitty_id) {
+                Self::reindex_listing(kitty_id, Zero::zero(), kitty.price);
+            }
+            kitty_id += 1.into();
+        }
+    }
+
+    /// Re-encode every stored kitty from the older layout `Old`, records which do not decode are left as they are
+    fn translate_kitties<Old: Decode, F: Fn(Old) -> Kitty<BalanceOf<T, I>>>(translate: F) {
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            let old = runtime_io::storage(&raw_map_key(&Self::storage_prefix(b"Kitties"), &kitty_id))
+                .and_then(|raw| Old::decode(&mut &raw[..]).ok());
+            if let Some(old) = old {
+                <Kitties<T, I>>::insert(kitty_id, translate(old));
+            }
+            kitty_id += 1.into();
+        }
+    }
+
+    /// Rebuild every owner's linked list and remove the old tuple keyed entries.
+    /// The old map can not be enumerated so it walks all kitties, which is fine for the sizes this module runs with.
+    fn migrate_owned_kitties_to_double_map() {
+        Self::reset_owned_kitties_counts();
+
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            if let Some(owner) = Self::owner_of(kitty_id) {
+                runtime_io::clear_storage(&raw_map_key(&Self::storage_prefix(b"OwnedKitties"), &(owner.clone(), None::<T::KittyIndex>)));
+                runtime_io::clear_storage(&raw_map_key(&Self::storage_prefix(b"OwnedKitties"), &(owner.clone(), Some(kitty_id))));
+                Self::append_owned_kitty(&owner, kitty_id);
+            }
+            kitty_id += 1.into();
+        }
+    }
+
+    /// Move every owner's linked list from the `OwnedKitties` double map into its child trie, keeping the order
+    fn migrate_owned_kitties_to_child_tries() {
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            if let Some(owner) = Self::owner_of(kitty_id) {
+                if <OwnedKitties<T, I>>::exists(&owner, &None) {
+                    let max_steps: u64 = Self::kitties_count().unique_saturated_into();
+                    let owned: Vec<_> = <LegacyOwnedKittiesList<T, I>>::iter(&owner).take(max_steps as usize).collect();
+                    <LegacyOwnedKittiesList<T, I>>::clear(&owner);
+                    for owned_kitty_id in owned {
+                        <OwnedKittiesList<T, I>>::append(&owner, owned_kitty_id);
+                    }
+                }
+            }
+            kitty_id += 1.into();
+        }
+    }
+
+    /// Drop the counts of every owner so migrations can count again while relinking
+    fn reset_owned_kitties_counts() {
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            if let Some(owner) = Self::owner_of(kitty_id) {
+                <OwnedKittiesCount<T, I>>::remove(&owner);
+            }
+            kitty_id += 1.into();
+        }
+    }
+
+    /// Whether the storage was written by the lesson-4 module, which indexes kitties per owner with `OwnedKittiesIndex`
+    fn has_lesson_4_layout() -> bool {
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < Self::kitties_count() {
+            if <KittyOwner<T, I>>::exists(kitty_id) {
+                return runtime_io::exists_storage(&raw_map_key(&Self::storage_prefix(b"OwnedKittiesIndex"), &kitty_id));
+            }
+            kitty_id += 1.into();
+        }
+        false
+    }
+
+    /// Convert the lesson-4 layout: `Kitty([u8; 16])` records become `Kitty { dna, price, generation }`,
+    /// and the `OwnedKitties`/`OwnedKittiesCount`/`OwnedKittiesIndex` index vectors become linked lists
+    fn migrate_from_lesson_4() {
+        let count = Self::kitties_count();
+
+        // Old counts are `KittyIndex` encoded
+        Self::reset_owned_kitties_counts();
+
+        Self::translate_kitties(|dna: [u8; 16]| Kitty {
+            dna,
+            price: 0.into(),
+            generation: 0,
+            soulbound: false,
+            season: None,
+            species: KITTY_SPECIES,
+        });
+
+        let mut kitty_id = T::KittyIndex::default();
+        while kitty_id < count {
+            let index_key = raw_map_key(&Self::storage_prefix(b"OwnedKittiesIndex"), &kitty_id);
+            let owned_index = runtime_io::storage(&index_key)
+                .and_then(|raw| T::KittyIndex::decode(&mut &raw[..]).ok());
+            runtime_io::clear_storage(&index_key);
+
+            if let Some(owner) = Self::owner_of(kitty_id) {
+                if let Some(owned_index) = owned_index {
+                    runtime_io::clear_storage(&raw_map_key(&Self::storage_prefix(b"OwnedKitties"), &(owner.clone(), owned_index)));
+                }
+                Self::append_owned_kitty(&owner, kitty_id);
+            }
+            kitty_id += 1.into();
+        }
+    }
+
+    /// Deposit an event with the kitty id and the `accounts` taking part as topics,
+    /// so clients can follow a kitty or an account without scanning every block
+    fn deposit_kitty_event(kitty_id: T::KittyIndex, accounts: &[&T::AccountId], event: Event<T, I>) {
+        let mut topics = Vec::with_capacity(accounts.len() + 1);
+        topics.push(T::Hashing::hash_of(&kitty_id));
+        topics.extend(accounts.iter().map(|account| T::Hashing::hash_of(account)));
+
+        let event = <T as Trait<I>>::Event::from(event).into();
+        <system::Module<T>>::deposit_event_indexed(&topics, event);
+    }
+
+    /// Deposit one event per watcher of the kitty, with the watcher as topic
+    fn notify_watchers<F>(kitty_id: T::KittyIndex, make_event: F) where
+        F: Fn(T::AccountId) -> Event<T, I>,
+    {
+        for watcher in Self::kitty_watchers(kitty_id) {
+            let topic = T::Hashing::hash_of(&watcher);
+            let event = <T as Trait<I>>::Event::from(make_event(watcher)).into();
+            <system::Module<T>>::deposit_event_indexed(&[topic], event);
+        }
+    }
+
+    fn ensure_not_fractionalized(kitty_id: T::KittyIndex) -> Result {
+        ensure!(!<KittyTotalShares<T, I>>::exists(kitty_id), "This cat is fractionalized");
+        Ok(())
+    }
+
+    fn do_fractionalize(sender: &T::AccountId, kitty_id: T::KittyIndex, shares: u32) -> Result {
+        ensure!(<Kitties<T, I>>::exists(kitty_id), "This cat does not exist");
+
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(owner == *sender, "You do not own this cat");
+        ensure!(shares > 0, "Shares must be greater than zero");
+        Self::ensure_not_locked(kitty_id)?;
+
+        // A locked cat can not stay on sale
+        Self::update_listing_price(kitty_id, Zero::zero());
+        Self::note_kitty_changed(kitty_id);
+
+        <KittyTotalShares<T, I>>::insert(kitty_id, shares);
+        <KittyShares<T, I>>::insert(&(kitty_id, sender.clone()), shares);
+
+        Ok(())
+    }
+
+    fn do_transfer_shares(sender: &T::AccountId, to: T::AccountId, kitty_id: T::KittyIndex, amount: u32) -> Result {
+        ensure!(<KittyTotalShares<T, I>>::exists(kitty_id), "This cat is not fractionalized");
+
+        let sender_shares = Self::kitty_shares(&(kitty_id, sender.clone()))
+            .checked_sub(amount)
+            .ok_or("Not enough shares")?;
+        if *sender == to {
+            return Ok(());
+        }
+        let to_shares = Self::kitty_shares(&(kitty_id, to.clone()))
+            .checked_add(amount)
+            .ok_or("Shares overflow")?;
+
+        if sender_shares == 0 {
+            <KittyShares<T, I>>::remove(&(kitty_id, sender.clone()));
+        } else {
+            <KittyShares<T, I>>::insert(&(kitty_id, sender.clone()), sender_shares);
+        }
+        <KittyShares<T, I>>::insert(&(kitty_id, to), to_shares);
+
+        Ok(())
+    }
+
+    fn do_redeem(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        let total_shares = Self::kitty_total_shares(kitty_id).ok_or("This cat is not fractionalized")?;
+        ensure!(Self::kitty_shares(&(kitty_id, sender.clone())) == total_shares, "You need all shares to redeem this cat");
+
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        if owner != *sender {
+            Self::ensure_not_paused(Subsystem::Transfers)?;
+            Self::ensure_below_kitty_cap(sender)?;
+        }
+
+        <KittyShares<T, I>>::remove(&(kitty_id, sender.clone()));
+        <KittyTotalShares<T, I>>::remove(kitty_id);
+
+        if owner != *sender {
+            Self::transfer_ownership(&owner, sender.clone(), kitty_id, Acquisition::Transfer);
+        }
+
+        Ok(())
+    }
+
+    fn ensure_not_co_owned(kitty_id: T::KittyIndex) -> Result {
+        ensure!(!<KittyCoOwners<T, I>>::exists(kitty_id), "This cat is co-owned, the action needs approvals");
+        Ok(())
+    }
+
+    fn ensure_co_owner(sender: &T::AccountId, kitty_id: T::KittyIndex) -> result::Result<CoOwnership<T::AccountId>, &'static str> {
+        let co_ownership = Self::kitty_co_owners(kitty_id).ok_or("This cat is not co-owned")?;
+        ensure!(co_ownership.owners.contains(sender), "You are not a co-owner of this cat");
+        Ok(co_ownership)
+    }
+
+    fn do_set_co_owners(sender: &T::AccountId, kitty_id: T::KittyIndex, owners: Vec<T::AccountId>, threshold: u32) -> Result {
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(owner == *sender, "You do not own this cat");
+        Self::ensure_not_co_owned(kitty_id)?;
+        Self::ensure_not_fractionalized(kitty_id)?;
+
+        ensure!(threshold > 0, "Threshold must be greater than zero");
+        ensure!(threshold as usize <= owners.len(), "Threshold can not exceed the number of co-owners");
+        for (i, account) in owners.iter().enumerate() {
+            ensure!(!owners[..i].contains(account), "Duplicated co-owner");
+        }
+
+        <KittyCoOwners<T, I>>::insert(kitty_id, CoOwnership {
+            owners,
+            threshold,
+        });
+
+        Ok(())
+    }
+
+    fn do_propose_action(sender: &T::AccountId, kitty_id: T::KittyIndex, action: KittyActionOf<T, I>) -> Result {
+        let co_ownership = Self::ensure_co_owner(sender, kitty_id)?;
+
+        let mut approvals = Vec::new();
+        approvals.push(sender.clone());
+        let pending = PendingKittyAction {
+            action,
+            approvals,
+        };
+
+        Self::approve_or_execute(kitty_id, &co_ownership, pending)
+    }
+
+    fn do_approve_action(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        let co_ownership = Self::ensure_co_owner(sender, kitty_id)?;
+
+        let mut pending = Self::pending_kitty_action(kitty_id).ok_or("No pending action for this cat")?;
+        ensure!(!pending.approvals.contains(sender), "You already approved this action");
+        pending.approvals.push(sender.clone());
+
+        Self::approve_or_execute(kitty_id, &co_ownership, pending)
+    }
+
+    fn approve_or_execute(kitty_id: T::KittyIndex, co_ownership: &CoOwnership<T::AccountId>, pending: PendingKittyActionOf<T, I>) -> Result {
+        if (pending.approvals.len() as u32) < co_ownership.threshold {
+            <PendingKittyActions<T, I>>::insert(kitty_id, pending);
+            return Ok(());
+        }
+
+        // Only drop the pending action once it went through, a failed action stays pending
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        match pending.action {
+            KittyAction::Transfer(to) => Self::do_transfer(&owner, to, kitty_id)?,
+            KittyAction::SetPrice(price) => Self::do_set_price(&owner, kitty_id, price)?,
+            KittyAction::Breed(other_kitty_id) => Self::do_breed(&owner, kitty_id, other_kitty_id).map(|_| ())?,
+        }
+        <PendingKittyActions<T, I>>::remove(kitty_id);
+
+        Ok(())
+    }
+
+    /// Iterate over the kitties owned by `account`, oldest first.
+    /// The walk is bounded by the total number of kitties, so a corrupted (cyclic) list can not loop forever
+    pub fn owned_kitties_iter(account: &T::AccountId) -> impl Iterator<Item = T::KittyIndex> {
+        let max_steps: u64 = Self::kitties_count().unique_saturated_into();
+        <OwnedKittiesList<T, I>>::iter(account).take(max_steps as usize)
+    }
+
+    /// Up to `limit` kitties owned by `account`, oldest first
+    pub fn owned_kitty_ids(account: &T::AccountId, limit: u32) -> Vec<T::KittyIndex> {
+        Self::owned_kitties_iter(account).take(limit as usize).collect()
+    }
+
+    /// A page of at most `limit` kitties owned by `account` following the `start_after` cursor.
+    /// Returns the cursor of the next page, `None` if this is the last one
+    pub fn owned_kitties_page(
+        account: &T::AccountId,
+        start_after: Option<T::KittyIndex>,
+        limit: u32,
+    ) -> (Vec<T::KittyIndex>, Option<T::KittyIndex>) {
+        page(<OwnedKittiesList<T, I>>::iter_after(account, start_after), limit)
+    }
+
+    /// A page of at most `limit` live kitties following the `start_after` cursor, in creation order.
+    /// Returns the cursor of the next page, `None` if this is the last one
+    pub fn all_kitties_page(start_after: Option<T::KittyIndex>, limit: u32) -> (Vec<T::KittyIndex>, Option<T::KittyIndex>) {
+        page(<AllKittiesList<T, I>>::iter_after(&(), start_after), limit)
+    }
+
+    /// A page of at most `limit` kitties for sale with their price following the `start_after` cursor, in creation order.
+    /// Returns the cursor of the next page, `None` if this is the last one
+    pub fn market_listings_page(
+        start_after: Option<T::KittyIndex>,
+        limit: u32,
+    ) -> (Vec<(T::KittyIndex, BalanceOf<T, I>)>, Option<T::KittyIndex>) {
+        let listings = <AllKittiesList<T, I>>::iter_after(&(), start_after)
+            .filter_map(|kitty_id| Self::kitty(kitty_id).map(|kitty| (kitty_id, kitty.price)))
+            .filter(|(_, price)| !price.is_zero());
+        let (items, cursor) = page(listings, limit);
+        (items, cursor.map(|(kitty_id, _)| kitty_id))
+    }
+
+    /// A page of at most `limit` kitties in the order they were added, following the `start_after` cursor.
+    /// Walking every page gives a deterministic snapshot of the kitties, e.g. to build the genesis of
+    /// another chain. Returns the cursor of the next page, `None` if this is the last one
+    pub fn kitties_page(start_after: Option<T::KittyIndex>, limit: u32) -> (Vec<T::KittyIndex>, Option<T::KittyIndex>) {
+        page(<AllKittiesList<T, I>>::iter_after(&(), start_after), limit)
+    }
+
+    /// Whether an account may use a kitty for gameplay (breeding, battles): its owner or delegate
+    pub fn can_play(who: &T::AccountId, kitty_id: T::KittyIndex) -> bool {
+        Self::owner_of(kitty_id).map(|owner| owner == *who).unwrap_or(false)
+            || Self::kitty_delegate(kitty_id).map(|delegate| delegate == *who).unwrap_or(false)
+    }
+
+    /// Vote weight of the first `MAX_VOTING_KITTIES` kitties of an account: one per kitty plus one
+    /// per rare trait
+    pub fn compute_vote_weight(who: &T::AccountId) -> u64 {
+        let kitties_count: u64 = Self::kitties_count().unique_saturated_into();
+        let rare_limit = kitties_count / RARE_TRAIT_DIVISOR;
+        Self::owned_kitties_iter(who)
+            .take(MAX_VOTING_KITTIES)
+            .filter_map(|kitty_id| Self::kitty(kitty_id))
+            .map(|kitty| {
+                let rare_traits = Self::traits_of(&kitty).into_iter()
+                    .filter(|key| Self::kitties_per_trait(key) <= rare_limit)
+                    .count();
+                1 + rare_traits as u64
+            })
+            .sum()
+    }
+
+    fn do_delegate(sender: &T::AccountId, kitty_id: T::KittyIndex, delegate_account: Option<T::AccountId>) -> Result {
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(owner == *sender, "You do not own this cat");
+        Self::ensure_not_co_owned(kitty_id)?;
+
+        if let Some(delegate_account) = delegate_account {
+            <KittyDelegate<T, I>>::insert(kitty_id, delegate_account);
+        } else {
+            <KittyDelegate<T, I>>::remove(kitty_id);
+        }
+
+        Ok(())
+    }
+
+    fn do_burn(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        ensure!(<Kitties<T, I>>::exists(kitty_id), "This cat does not exist");
+
+        let owner = Self::owner_of(kitty_id).ok_or("No owner for this kitty")?;
+        ensure!(owner == *sender, "You do not own this cat");
+        Self::ensure_not_locked(kitty_id)?;
+
+        Self::remove_kitty(&owner, kitty_id);
+
+        Ok(())
+    }
+
+    /// Remove every record of a kitty, paying back its share of the creation reserve and its creation deposit
+    fn remove_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+        if let Some(kitty) = Self::kitty(kitty_id) {
+            Self::unindex_traits(kitty_id, &kitty);
+            <KittiesPerGeneration<I>>::mutate(kitty.generation, |count| *count = count.saturating_sub(1));
+        }
+        Self::update_listing_price(kitty_id, Zero::zero());
+        <Kitties<T, I>>::remove(kitty_id);
+        <KittyOwner<T, I>>::remove(kitty_id);
+        <KittyCoOwners<T, I>>::remove(kitty_id);
+        <PendingKittyActions<T, I>>::remove(kitty_id);
+        <KittyDelegate<T, I>>::remove(kitty_id);
+        <KittyWatchers<T, I>>::remove(kitty_id);
+        <BreedingReadyAt<T, I>>::remove(kitty_id);
+        <BlacklistedKitties<T, I>>::remove(kitty_id);
+        <MintedByVerifiedBreeder<T, I>>::remove(kitty_id);
+        <PendingReveals<T, I>>::remove(kitty_id);
+        <MintPasses<T, I>>::remove(kitty_id);
+        <ListingDonation<T, I>>::remove(kitty_id);
+        <KittyTips<T, I>>::remove(kitty_id);
+        <Provenance<T, I>>::remove(kitty_id);
+        <KittyMetadata<T, I>>::remove(kitty_id);
+        <Royalties<T, I>>::remove(kitty_id);
+        <VoteWeights<T, I>>::remove(owner);
+        Self::clear_name(kitty_id);
+        if <ReserveBacked<T, I>>::take(kitty_id) {
+            Self::refund_from_reserve(owner, kitty_id);
+        }
+        // The deposit stays reserved from the creator while the kitty changes hands
+        if let Some((depositor, deposit)) = <KittyDeposits<T, I>>::take(kitty_id) {
+            Self::pay_reserved(&depositor, owner, deposit);
+            Self::deposit_event(RawEvent::CreationDepositReturned(owner.clone(), kitty_id, deposit));
+        }
+
+        Self::remove_owned_kitty(owner, kitty_id);
+        <AllKittiesList<T, I>>::remove(&(), kitty_id);
+        Self::note_kitty_changed(kitty_id);
+        T::OnKittyBurned::on_kitty_burned(owner, kitty_id);
+    }
+
+    /// Pay `owner` an even share of the creation reserve for a burned kitty backed by it. The kitty
+    /// no longer counts against the reserve even when the refund can not be paid
+    fn refund_from_reserve(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+        let backed = Self::reserve_backed_kitties();
+        let reserve = Self::creation_reserve();
+        let refund = if backed > 1 { reserve / backed.into() } else { reserve };
+
+        <ReserveBackedKitties<I>>::put(backed.saturating_sub(1));
+        if T::Currency::transfer(&Self::creation_reserve_account(), owner, refund).is_ok() {
+            Self::deposit_event(RawEvent::CreationRefunded(owner.clone(), kitty_id, refund));
+        }
+    }
+
+    fn do_cleanup(sender: &T::AccountId, kitty_id: T::KittyIndex) -> Result {
+        ensure!(!<Kitties<T, I>>::exists(kitty_id), "This cat exists");
+
+        let mut cleaned = 0;
+        if let Some(owner) = <KittyOwner<T, I>>::take(kitty_id) {
+            Self::remove_owned_kitty(&owner, kitty_id);
+            cleaned += 1;
+        }
+        if <AllKittiesList<T, I>>::remove(&(), kitty_id) {
+            cleaned += 1;
+        }
+        ensure!(cleaned > 0, "Nothing to clean up");
+
+        let reward = Self::pay_cleanup_reward(sender, cleaned);
+        Self::deposit_event(RawEvent::KittyCleanedUp(sender.clone(), kitty_id, reward));
+        Ok(())
+    }
+
+    fn do_cleanup_account(sender: &T::AccountId, account: &T::AccountId) -> Result {
+        let listed: Vec<T::KittyIndex> = <OwnedKittiesList<T, I>>::iter(account).take(MAX_CLEANUP_STEPS + 1).collect();
+        let stale: Vec<T::KittyIndex> = listed.iter()
+            .take(MAX_CLEANUP_STEPS)
+            .filter(|kitty_id| Self::owner_of(**kitty_id).as_ref() != Some(account))
+            .cloned()
+            .collect();
+        // The count can only be checked against a list walked to its end
+        let count = (listed.len() - stale.len()) as u64;
+        let recount = listed.len() <= MAX_CLEANUP_STEPS && Self::owned_kitties_count(account) != count;
+        ensure!(!stale.is_empty() || recount, "Nothing to clean up");
+
+        for kitty_id in &stale {
+            Self::remove_owned_kitty(account, *kitty_id);
+        }
+        if recount {
+            <OwnedKittiesCount<T, I>>::insert(account, count);
+        }
+
+        let cleaned = stale.len() as u32 + if recount { 1 } else { 0 };
+        let reward = Self::pay_cleanup_reward(sender, cleaned);
+        Self::deposit_event(RawEvent::AccountCleanedUp(sender.clone(), account.clone(), cleaned, reward));
+        Ok(())
+    }
+
+    /// Account funding the rewards of `cleanup` and `cleanup_account`
+    pub fn maintenance_pot_account() -> T::AccountId {
+        Self::module_account(MAINTENANCE_POT_ID)
+    }
+
+    /// Account paying the balance rewards of quests
+    pub fn quest_pool_account() -> T::AccountId {
+        Self::module_account(QUEST_POOL_ID)
+    }
+
+    /// Funds in the quest pool account left for the quest rewards
+    pub fn quest_pool() -> BalanceOf<T, I> {
+        Self::spendable_balance(&Self::quest_pool_account())
+    }
+
+    /// Funds in the maintenance pot account left for the cleanup rewards
+    pub fn maintenance_pot() -> BalanceOf<T, I> {
+        Self::spendable_balance(&Self::maintenance_pot_account())
+    }
+
+    /// Pay `CleanupReward` for each of the `cleaned` entries out of the maintenance pot, as far as it goes
+    fn pay_cleanup_reward(sender: &T::AccountId, cleaned: u32) -> BalanceOf<T, I> {
+        let reward = Self::cleanup_reward().saturating_mul(cleaned.into()).min(Self::maintenance_pot());
+        if reward.is_zero() || T::Currency::transfer(&Self::maintenance_pot_account(), sender, reward).is_err() {
+            return Zero::zero();
+        }
+        reward
+    }
+
+    /// Check a privileged origin with `O` and prepare the audit log entry of `call`
+    fn ensure_privileged<O: EnsureOrigin<T::Origin>>(origin: T::Origin, call: Call<T, I>) -> result::Result<AuditEntryOf<T>, &'static str> {
+        // The origin can't be cloned, a signed one is taken apart for the caller and put back together
+        let raw: result::Result<system::RawOrigin<T::AccountId>, T::Origin> = origin.into();
+        let (caller, origin) = match raw {
+            Ok(system::RawOrigin::Signed(who)) => (Some(who.clone()), system::RawOrigin::Signed(who).into()),
+            Ok(raw) => (None, raw.into()),
+            Err(origin) => (None, origin),
+        };
+        O::ensure_origin(origin)?;
+        Ok(Self::audit_entry(caller, call))
+    }
+
+    fn audit_entry(caller: Option<T::AccountId>, call: Call<T, I>) -> AuditEntryOf<T> {
+        AuditEntry { caller, call: call.encode(), at: <system::Module<T>>::block_number() }
+    }
+
+    /// Add a privileged call which went through to the audit log, overwriting the oldest one once it is full
+    fn record_audit(entry: AuditEntryOf<T>) {
+        let count = Self::audit_log_count();
+        <AuditLog<T, I>>::insert((count % u64::from(AUDIT_LOG_SIZE)) as u32, entry);
+        <AuditLogCount<I>>::put(count.saturating_add(1));
+    }
+
+    /// The last `limit` privileged calls, the latest first
+    pub fn audit_log(limit: u32) -> Vec<AuditEntryOf<T>> {
+        let count = Self::audit_log_count();
+        (1..=count.min(u64::from(AUDIT_LOG_SIZE.min(limit))))
+            .filter_map(|back| Self::audit_log_entry(((count - back) % u64::from(AUDIT_LOG_SIZE)) as u32))
+            .collect()
+    }
+
+    /// A species of the registry, kitties included
+    pub fn species_info(species: SpeciesId) -> Option<Species> {
+        if species == KITTY_SPECIES {
+            Some(Species::kitties())
+        } else {
+            Self::registered_species(species)
+        }
+    }
+
+    /// The constants of the module metadata, for runtime code and the runtime API
+    pub fn module_constants() -> ModuleConstants<T::BlockNumber> {
+        ModuleConstants {
+            dispute_window: T::DisputeWindow::get(),
+            reveal_delay: T::RevealDelay::get(),
+            timestamp_cooldowns: T::TimestampCooldowns::get(),
+            max_breed_batch: T::MaxBreedBatch::get(),
+            max_price_batch: T::MaxPriceBatch::get(),
+            max_watchers_per_kitty: T::MaxWatchersPerKitty::get(),
+            max_heirs: T::MaxHeirs::get(),
+            max_royalty_beneficiaries: T::MaxRoyaltyBeneficiaries::get(),
+            max_royalty_percent: T::MaxRoyaltyPercent::get(),
+            loyalty_points_per_trade: T::LoyaltyPointsPerTrade::get(),
+            max_trade_kitties: T::MaxTradeKitties::get(),
+            max_page_size: MAX_PAGE_SIZE,
+        }
+    }
+
+    /// The message an account signs for its sponsored call with `nonce`, bound to the chain by its genesis hash
+    pub fn sponsored_call_payload(who: &T::AccountId, operation: &SponsoredOperationOf<T, I>, nonce: u64) -> Vec<u8> {
+        let genesis_hash = <system::Module<T>>::block_hash(T::BlockNumber::zero());
+        Self::tagged(b"kitties-sponsored-call", (genesis_hash, who, operation, nonce))
+    }
+
+    /// Check the sponsorship and signature of a sponsored call
+    fn ensure_valid_sponsored_call(
+        who: &T::AccountId,
+        operation: &SponsoredOperationOf<T, I>,
+        signature: &T::Signature
+    ) -> result::Result<SponsorshipOf<T, I>, &'static str> {
+        let sponsorship = Self::sponsorship(who).ok_or("This account has no sponsor")?;
+        let payload = Self::sponsored_call_payload(who, operation, Self::nonce_of(who, NoncePurpose::SponsoredCall));
+        ensure!(signature.verify(&payload[..], who), "The call is not signed by the sponsored account");
+        Ok(sponsorship)
+    }
+
+    fn do_sponsored_call(who: T::AccountId, operation: SponsoredOperationOf<T, I>, signature: T::Signature) -> Result {
+        let mut sponsorship = Self::ensure_valid_sponsored_call(&who, &operation, &signature)?;
+        Self::note_operation(&who)?;
+
+        // The fee is burned and the signature used up before the operation, so a failing operation
+        // is paid for like any transaction and can not be replayed
+        let _ = T::Currency::slash_reserved(&sponsorship.sponsor, sponsorship.fee);
+        Self::bump_nonce(&who, NoncePurpose::SponsoredCall);
+        sponsorship.remaining = sponsorship.remaining.saturating_sub(1);
+        if sponsorship.remaining == 0 {
+            Self::end_sponsorship_of(&who, sponsorship);
+        } else {
+            <Sponsorships<T, I>>::insert(&who, sponsorship);
+        }
+
+        match operation {
+            SponsoredOperation::Create => Self::do_buy_gen0(&who, None, KITTY_SPECIES).map(|_| ()),
+            SponsoredOperation::Transfer(to, kitty_id) => {
+                Self::ensure_not_co_owned(kitty_id)?;
+                Self::do_transfer(&who, to, kitty_id)
+            }
+        }
+    }
+
+    /// Remove a sponsorship, unreserving the fees of the operations left
+    fn end_sponsorship_of(who: &T::AccountId, sponsorship: SponsorshipOf<T, I>) {
+        let remaining: BalanceOf<T, I> = sponsorship.remaining.into();
+        let left = sponsorship.fee.saturating_mul(remaining);
+        let _ = T::Currency::unreserve(&sponsorship.sponsor, left);
+        <Sponsorships<T, I>>::remove(who);
+        Self::deposit_event(RawEvent::SponsorshipEnded(sponsorship.sponsor, who.clone()));
+    }
+
+    /// Check the consistency of the kitties storage:
+    /// - `KittiesCount` is above every kitty index
+    /// - every kitty has exactly one `KittyOwner` entry and only kitties have one
+    /// - every owned kitty is in its owner's list exactly once and the lists hold nothing else
+    /// - the price index holds exactly the kitties for sale, at their price
+    ///
+    /// Walks every kitty, for tests and the `try-state` debug hook only
+    pub fn do_try_state() -> Result {
+        let count = Self::kitties_count();
+        let max_steps: u64 = count.unique_saturated_into();
+        let max_steps = max_steps.saturating_add(1) as usize;
+
+        ensure!(!<Kitties<T, I>>::exists(count), "A kitty index is not below KittiesCount");
+        ensure!(!<KittyOwner<T, I>>::exists(count), "An owned index is not below KittiesCount");
+        let listed = <AllKittiesList<T, I>>::iter(&()).take(max_steps).collect::<Vec<_>>();
+        ensure!(listed.iter().all(|kitty_id| *kitty_id < count), "A kitty index is not below KittiesCount");
+
+        let mut owners: Vec<T::AccountId> = Vec::new();
+        let mut owned: u64 = 0;
+        let mut for_sale: u64 = 0;
+        let mut kitty_id = T::KittyIndex::zero();
+        while kitty_id < count {
+            if let Some(kitty) = Self::kitty(kitty_id).filter(|kitty| !kitty.price.is_zero()) {
+                ensure!(Self::listings_at_price(kitty.price).contains(&kitty_id), "A kitty for sale is not in the price index");
+                for_sale += 1;
+            }
+            match (<Kitties<T, I>>::exists(kitty_id), Self::owner_of(kitty_id)) {
+                (true, Some(owner)) => {
+                    if !owners.contains(&owner) {
+                        owners.push(owner);
+                    }
+                    owned += 1;
+                }
+                (true, None) => return Err("A kitty has no owner"),
+                (false, Some(_)) => return Err("An owner entry has no kitty"),
+                (false, None) => {}
+            }
+            kitty_id += 1.into();
+        }
+        ensure!(listed.len() as u64 == owned, "AllKitties does not list every kitty once");
+
+        let mut total_listed: u64 = 0;
+        for owner in owners {
+            let mut listed = <OwnedKittiesList<T, I>>::iter(&owner).take(max_steps).collect::<Vec<_>>();
+            ensure!(listed.len() < max_steps, "An owned kitties list has a cycle");
+            ensure!(
+                listed.iter().all(|kitty_id| Self::owner_of(*kitty_id).as_ref() == Some(&owner)),
+                "An owned kitties list holds a kitty of another account"
+            );
+            ensure!(Self::owned_kitties_count(&owner) == listed.len() as u64, "OwnedKittiesCount differs from the list");
+
+            let len = listed.len();
+            listed.sort();
+            listed.dedup();
+            ensure!(listed.len() == len, "An owned kitties list holds a kitty twice");
+            total_listed += len as u64;
+        }
+        ensure!(total_listed == owned, "An owned kitty is missing from its owner's list");
+
+        let ranges = Self::listed_price_ranges();
+        ensure!(ranges.windows(2).all(|pair| pair[0] < pair[1]), "ListedPriceRanges is not sorted");
+        for range in ranges {
+            let prices = Self::listed_prices_in_range(range);
+            ensure!(!prices.is_empty(), "ListedPriceRanges holds an empty range");
+            ensure!(prices.iter().all(|price| Self::price_range(*price) == range), "A listed price is in the wrong range");
+        }
+        let prices = Self::listed_prices();
+        ensure!(prices.windows(2).all(|pair| pair[0] < pair[1]), "The listed prices are not sorted");
+        let indexed = prices.iter().map(|price| Self::listings_at_price(price).len() as u64).sum::<u64>();
+        ensure!(indexed == for_sale, "The price index holds kitties which are not for sale");
+
+        Ok(())
+    }
+}
+
+impl<T: Trait<I>, I: Instance> NonFungible<T::AccountId> for Module<T, I> {
+    type TokenId = T::KittyIndex;
+
+    fn owner_of(kitty_id: &T::KittyIndex) -> Option<T::AccountId> {
+        Self::owner_of(*kitty_id)
+    }
+
+    fn transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: &T::KittyIndex) -> Result {
+        Self::ensure_not_co_owned(*kitty_id)?;
+        Self::do_transfer(from, to.clone(), *kitty_id)
+    }
+
+    fn mint(owner: &T::AccountId) -> result::Result<T::KittyIndex, &'static str> {
+        Self::do_create(owner)
+    }
+
+    fn burn(owner: &T::AccountId, kitty_id: &T::KittyIndex) -> Result {
+        Self::ensure_not_co_owned(*kitty_id)?;
+        Self::do_burn(owner, *kitty_id)
+    }
+}
+
+impl<T: Trait<I>, I: Instance> VoteWeight<T::AccountId> for Module<T, I> {
+    fn vote_weight(who: &T::AccountId) -> u64 {
+        Self::vote_weight_of(who)
+    }
+}
+
+impl<T: Trait<I>, I: Instance> support::unsigned::ValidateUnsigned for Module<T, I> {
+    type Call = Call<T, I>;
+
+    fn validate_unsigned(call: &Self::Call) -> TransactionValidity {
+        if let Call::submit_gen0_price(price, reported_at, oracle, signature) = call {
+            if !Self::gen0_price_oracles().contains(oracle) {
+                return InvalidTransaction::BadProof.into();
+            }
+            if !runtime_io::sr25519_verify(signature, &Self::gen0_price_payload(*price, *reported_at), oracle) {
+                return InvalidTransaction::BadProof.into();
+            }
+            if Self::ensure_valid_gen0_price(*price, *reported_at).is_err() {
+                return InvalidTransaction::Stale.into();
+            }
+
+            // One report per block makes it into the pool
+            let mut provides = Vec::new();
+            provides.push(Self::tagged(b"kitties-gen0-price", reported_at));
+
+            Ok(ValidTransaction {
+                priority: 0,
+                requires: Vec::new(),
+                provides,
+                longevity: TransactionLongevity::from(GEN0_PRICE_UPDATE_PERIOD),
+                propagate: true,
+            })
+        } else if let Call::collect_garbage(kitty_ids) = call {
+            if kitty_ids.is_empty() || kitty_ids.len() > MAX_GARBAGE_ITEMS {
+                return InvalidTransaction::Call.into();
+            }
+            // Only finished auctions, which no later call can bring back
+            if !kitty_ids.iter().all(|kitty_id| Self::is_finished_auction(*kitty_id)) {
+                return InvalidTransaction::Stale.into();
+            }
+
+            // The workers of every node find the same kitties, one transaction for each makes it into the pool
+            let provides = kitty_ids.iter().map(|kitty_id| Self::tagged(b"kitties-gc", kitty_id)).collect();
+
+            Ok(ValidTransaction {
+                priority: 0,
+                requires: Vec::new(),
+                provides,
+                longevity: TransactionLongevity::from(GC_LONGEVITY),
+                propagate: true,
+            })
+        } else if let Call::submit_reference_price(price, quoted_at, oracle, signature) = call {
+            if !Self::reference_price_oracles().contains(oracle) {
+                return InvalidTransaction::BadProof.into();
+            }
+            if !runtime_io::sr25519_verify(signature, &Self::reference_price_payload(*price, *quoted_at), oracle) {
+                return InvalidTransaction::BadProof.into();
+            }
+            if Self::ensure_valid_reference_price(*price, *quoted_at).is_err() {
+                return InvalidTransaction::Stale.into();
+            }
+
+            // One quote per block makes it into the pool
+            let mut provides = Vec::new();
+            provides.push(Self::tagged(b"kitties-reference-price", quoted_at));
+
+            Ok(ValidTransaction {
+                priority: 0,
+                requires: Vec::new(),
+                provides,
+                longevity: TransactionLongevity::from(REFERENCE_PRICE_MAX_AGE),
+                propagate: true,
+            })
+        } else if let Call::sponsored_call(who, operation, signature) = call {
+            let sponsorship = match Self::ensure_valid_sponsored_call(who, operation, signature) {
+                Ok(sponsorship) => sponsorship,
+                Err(_) => return InvalidTransaction::BadProof.into(),
+            };
+            if T::Currency::reserved_balance(&sponsorship.sponsor) < sponsorship.fee {
+                return InvalidTransaction::Payment.into();
+            }
+            if Self::max_operations_per_block().map_or(false, |max| Self::block_operations(who) >= max) {
+                return InvalidTransaction::ExhaustsResources.into();
+            }
+
+            let mut provides = Vec::new();
+            provides.push(Self::tagged(b"kitties-sponsored-call", (who, Self::nonce_of(who, NoncePurpose::SponsoredCall))));
+
+            Ok(ValidTransaction {
+                priority: 0,
+                requires: Vec::new(),
+                provides,
+                longevity: TransactionLongevity::from(SPONSORED_CALL_LONGEVITY),
+                propagate: true,
+            })
+        } else {
+            InvalidTransaction::Call.into()
+        }
+    }
+}
+
+/// tests for this module
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use runtime_io::with_externalities;
+    use primitives::{H256, Blake2Hasher, Pair};
+    use support::{impl_outer_origin, impl_outer_event, parameter_types, assert_ok, assert_noop, unsigned::ValidateUnsigned};
+    use sr_primitives::{traits::{BlakeTwo256, IdentityLookup, OnInitialize}, testing::{Header, TestXt}, weights::GetDispatchInfo};
+    use sr_primitives::weights::Weight;
+    use sr_primitives::{Perbill, BuildStorage};
+    use std::cell::RefCell;
+    use crate::linked_item::LinkedItemStorage;
+
+    impl_outer_origin! {
+		pub enum Origin for Test {}
+	}
+
+    mod kitties {
+        pub use crate::{Event, Instance1};
+    }
+
+    impl_outer_event! {
+		pub enum TestEvent for Test {
+			kitties<T>,
+			kitties Instance1<T>,
+		}
+	}
+
+    // For testing the module, we construct most of a mock runtime. This means
+    // first constructing a configuration type (`Test`) which `impl`s each of the
+    // configuration traits of modules we want to use.
+    #[derive(Clone, Eq, PartialEq, Debug)]
+    pub struct Test;
+    parameter_types! {
+		pub const BlockHashCount: u64 = 250;
+		pub const MaximumBlockWeight: Weight = 1024;
+		pub const MaximumBlockLength: u32 = 2 * 1024;
+		pub const AvailableBlockRatio: Perbill = Perbill::from_percent(75);
+	}
+    impl system::Trait for Test {
+        type Origin = Origin;
+        type Call = ();
+        type Index = u64;
+        type BlockNumber = u64;
+        type Hash = H256;
+        type Hashing = BlakeTwo256;
+        type AccountId = u64;
+        type Lookup = IdentityLookup<Self::AccountId>;
+        type Header = Header;
+        type WeightMultiplierUpdate = ();
+        type Event = TestEvent;
+        type BlockHashCount = BlockHashCount;
+        type MaximumBlockWeight = MaximumBlockWeight;
+        type MaximumBlockLength = MaximumBlockLength;
+        type AvailableBlockRatio = AvailableBlockRatio;
+        type Version = ();
+    }
+    parameter_types! {
+		pub const ExistentialDeposit: u64 = 0;
+		pub const TransferFee: u64 = 0;
+		pub const CreationFee: u64 = 0;
+		pub const TransactionBaseFee: u64 = 0;
+		pub const TransactionByteFee: u64 = 0;
+	}
+    impl balances::Trait for Test {
+        type Balance = u64;
+        type OnFreeBalanceZero = ();
+        type OnNewAccount = ();
+        type Event = ();
+        type TransactionPayment = ();
+        type TransferPayment = ();
+        type DustRemoval = ();
+        type ExistentialDeposit = ExistentialDeposit;
+        type TransferFee = TransferFee;
+        type CreationFee = CreationFee;
+        type TransactionBaseFee = TransactionBaseFee;
+        type TransactionByteFee = TransactionByteFee;
+        type WeightToFee = ();
+    }
+
+    thread_local! {
+        static MARKET_ENABLED: RefCell<bool> = RefCell::new(true);
+        static ACHIEVEMENT_BADGES: RefCell<bool> = RefCell::new(false);
+        static REVEAL_DELAY: RefCell<u64> = RefCell::new(0);
+        static TIMESTAMP_COOLDOWNS: RefCell<bool> = RefCell::new(false);
+        static NOW: RefCell<u64> = RefCell::new(0);
+        static DISPUTE_WINDOW: RefCell<u64> = RefCell::new(5);
+    }
+    pub struct MarketEnabled;
+    impl Get<bool> for MarketEnabled {
+        fn get() -> bool { MARKET_ENABLED.with(|v| *v.borrow()) }
+    }
+    pub struct AchievementBadges;
+    impl Get<bool> for AchievementBadges {
+        fn get() -> bool { ACHIEVEMENT_BADGES.with(|v| *v.borrow()) }
+    }
+    pub struct RevealDelay;
+    impl Get<u64> for RevealDelay {
+        fn get() -> u64 { REVEAL_DELAY.with(|v| *v.borrow()) }
+    }
+    pub struct DisputeWindow;
+    impl Get<u64> for DisputeWindow {
+        fn get() -> u64 { DISPUTE_WINDOW.with(|v| *v.borrow()) }
+    }
+    pub struct TimestampCooldowns;
+    impl Get<bool> for TimestampCooldowns {
+        fn get() -> bool { TIMESTAMP_COOLDOWNS.with(|v| *v.borrow()) }
+    }
+    pub struct MockTime;
+    impl Time for MockTime {
+        type Moment = u64;
+        fn now() -> u64 { NOW.with(|v| *v.borrow()) }
+    }
+
+    parameter_types! {
+		pub const MaxBreedBatch: u32 = 3;
+		pub const MaxPriceBatch: u32 = 3;
+		pub const IndexPoolAccount: u64 = 999;
+		pub const MaxWatchersPerKitty: u32 = 100;
+		pub const MaxHeirs: u32 = 10;
+		pub const MaxRoyaltyBeneficiaries: u32 = 10;
+		pub const MaxRoyaltyPercent: u32 = 10;
+		pub const LoyaltyPointsPerTrade: u64 = 10;
+		pub const MaxTradeKitties: u32 = 10;
+	}
+
+    type Extrinsic = TestXt<Call<Test>, ()>;
+
+    impl Trait for Test {
+        type Event = TestEvent;
+        type KittyIndex = u32;
+        type Currency = balances::Module<Test>;
+        type MarketEnabled = MarketEnabled;
+        type Call = Call<Test>;
+        type SubmitTransaction = system::offchain::TransactionSubmitter<(), Test, Extrinsic>;
+        type Signature = TestSignature;
+        type OnKittyCreated = RecordHooks;
+        type OnKittyTransfer = RecordHooks;
+        type OnKittyBurned = RecordHooks;
+        type GovernanceOrigin = system::EnsureRoot<u64>;
+        type EmergencyOrigin = system::EnsureRoot<u64>;
+        type RegistrarOrigin = system::EnsureRoot<u64>;
+        type ArbiterOrigin = system::EnsureRoot<u64>;
+        type ModeratorOrigin = system::EnsureRoot<u64>;
+        type DisputeWindow = DisputeWindow;
+        type SoulboundIssuerOrigin = system::EnsureRoot<u64>;
+        type AchievementBadges = AchievementBadges;
+        type RevealDelay = RevealDelay;
+        type BridgeOrigin = system::EnsureRoot<u64>;
+        type MaxBreedBatch = MaxBreedBatch;
+        type MaxPriceBatch = MaxPriceBatch;
+        type IndexPoolAccount = IndexPoolAccount;
+        type Time = MockTime;
+        type TimestampCooldowns = TimestampCooldowns;
+        type MaxWatchersPerKitty = MaxWatchersPerKitty;
+        type MaxHeirs = MaxHeirs;
+        type MaxRoyaltyBeneficiaries = MaxRoyaltyBeneficiaries;
+        type MaxRoyaltyPercent = MaxRoyaltyPercent;
+        type LoyaltyPointsPerTrade = LoyaltyPointsPerTrade;
+        type MaxTradeKitties = MaxTradeKitties;
+    }
+
+    type PuppiesExtrinsic = TestXt<Call<Test, Instance1>, ()>;
+
+    /// A second collection in the same runtime, configured like the first one
+    impl Trait<Instance1> for Test {
+        type Event = TestEvent;
+        type KittyIndex = u32;
+        type Currency = balances::Module<Test>;
+        type MarketEnabled = MarketEnabled;
+        type Call = Call<Test, Instance1>;
+        type SubmitTransaction = system::offchain::TransactionSubmitter<(), Test, PuppiesExtrinsic>;
+        type Signature = TestSignature;
+        type OnKittyCreated = ();
+        type OnKittyTransfer = ();
+        type OnKittyBurned = ();
+        type GovernanceOrigin = system::EnsureRoot<u64>;
+        type EmergencyOrigin = system::EnsureRoot<u64>;
+        type RegistrarOrigin = system::EnsureRoot<u64>;
+        type ArbiterOrigin = system::EnsureRoot<u64>;
+        type ModeratorOrigin = system::EnsureRoot<u64>;
+        type DisputeWindow = DisputeWindow;
+        type SoulboundIssuerOrigin = system::EnsureRoot<u64>;
+        type AchievementBadges = AchievementBadges;
+        type RevealDelay = RevealDelay;
+        type BridgeOrigin = system::EnsureRoot<u64>;
+        type MaxBreedBatch = MaxBreedBatch;
+        type MaxPriceBatch = MaxPriceBatch;
+        type IndexPoolAccount = IndexPoolAccount;
+        type Time = MockTime;
+        type TimestampCooldowns = TimestampCooldowns;
+        type MaxWatchersPerKitty = MaxWatchersPerKitty;
+        type MaxHeirs = MaxHeirs;
+        type MaxRoyaltyBeneficiaries = MaxRoyaltyBeneficiaries;
+        type MaxRoyaltyPercent = MaxRoyaltyPercent;
+        type LoyaltyPointsPerTrade = LoyaltyPointsPerTrade;
+        type MaxTradeKitties = MaxTradeKitties;
+    }
+
+    /// Signature by the account in `0` over the message in `1`
+    #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+    pub struct TestSignature(u64, Vec<u8>);
+    impl Verify for TestSignature {
+        type Signer = u64;
+        fn verify<L: sr_primitives::traits::Lazy<[u8]>>(&self, mut msg: L, signer: &u64) -> bool {
+            self.0 == *signer && &self.1[..] == msg.get()
+        }
+    }
+
+    thread_local! {
+        static HOOK_CALLS: RefCell<Vec<(&'static str, u64, u32)>> = RefCell::new(Vec::new());
+    }
+    /// Records the kitty hooks as `(hook, account, kitty_id)`, the account being the new owner for transfers
+    pub struct RecordHooks;
+    impl OnKittyCreated<u64, u32> for RecordHooks {
+        fn on_kitty_created(owner: &u64, kitty_id: u32) {
+            HOOK_CALLS.with(|calls| calls.borrow_mut().push(("created", *owner, kitty_id)));
+        }
+    }
+    impl OnKittyTransfer<u64, u32> for RecordHooks {
+        fn on_kitty_transfer(_from: &u64, to: &u64, kitty_id: u32) {
+            HOOK_CALLS.with(|calls| calls.borrow_mut().push(("transfer", *to, kitty_id)));
+        }
+    }
+    impl OnKittyBurned<u64, u32> for RecordHooks {
+        fn on_kitty_burned(owner: &u64, kitty_id: u32) {
+            HOOK_CALLS.with(|calls| calls.borrow_mut().push(("burned", *owner, kitty_id)));
+        }
+    }
+
+    type OwnedKittiesTest = OwnedKittiesItems;
+    pub type KittiesModule = Module<Test>;
+    pub type PuppiesModule = Module<Test, Instance1>;
+
+    /// Builds the genesis storage of the mock: endowed accounts and kitties seeded for them
+    #[derive(Default)]
+    pub struct ExtBuilder {
+        balances: Vec<(u64, u64)>,
+        kitties: Vec<u64>,
+    }
+
+    impl ExtBuilder {
+        /// Endow `account` with `balance`
+        pub fn balance(mut self, account: u64, balance: u64) -> Self {
+            self.balances.push((account, balance));
+            self
+        }
+
+        /// Seed a gen-0 kitty owned by `owner`, kitty ids follow the order of the calls
+        pub fn kitty(mut self, owner: u64) -> Self {
+            self.kitties.push(owner);
+            self
+        }
+
+        pub fn build(self) -> runtime_io::TestExternalities<Blake2Hasher> {
+            let mut storage = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+            balances::GenesisConfig::<Test> {
+                balances: self.balances,
+                vesting: vec![],
+            }.assimilate_storage(&mut storage).unwrap();
+
+            let mut ext: runtime_io::TestExternalities<Blake2Hasher> = storage.into();
+            let kitties = self.kitties;
+            with_externalities(&mut ext, || {
+                for owner in kitties {
+                    assert_ok!(KittiesModule::create(Origin::signed(owner)));
+                }
+                // Seeding is not part of what the tests observe
+                HOOK_CALLS.with(|calls| calls.borrow_mut().clear());
+                <ChangedKitties<Test>>::kill();
+            });
+            ext
+        }
+    }
+
+    #[test]
+    fn owned_kitties_can_append_values() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            OwnedKittiesList::<Test>::append(&0, 1);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &None), Some(KittyLinkedItem::<Test> {
+                prev: Some(1),
+                next: Some(1),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(1)), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: None,
+            }));
+
+            OwnedKittiesList::<Test>::append(&0, 2);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &None), Some(KittyLinkedItem::<Test> {
+                prev: Some(2),
+                next: Some(1),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(1)), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: Some(2),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(2)), Some(KittyLinkedItem::<Test> {
+                prev: Some(1),
+                next: None,
+            }));
+
+            OwnedKittiesList::<Test>::append(&0, 3);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &None), Some(KittyLinkedItem::<Test> {
+                prev: Some(3),
+                next: Some(1),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(1)), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: Some(2),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(2)), Some(KittyLinkedItem::<Test> {
+                prev: Some(1),
+                next: Some(3),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(3)), Some(KittyLinkedItem::<Test> {
+                prev: Some(2),
+                next: None,
+            }));
+        });
+    }
+
+    #[test]
+    fn owned_kitties_can_remove_values() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            OwnedKittiesList::<Test>::append(&0, 1);
+            OwnedKittiesList::<Test>::append(&0, 2);
+            OwnedKittiesList::<Test>::append(&0, 3);
+
+            OwnedKittiesList::<Test>::remove(&0, 2);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &None), Some(KittyLinkedItem::<Test> {
+                prev: Some(3),
+                next: Some(1),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(1)), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: Some(3),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(2)), None);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(3)), Some(KittyLinkedItem::<Test> {
+                prev: Some(1),
+                next: None,
+            }));
+
+            OwnedKittiesList::<Test>::remove(&0, 1);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &None), Some(KittyLinkedItem::<Test> {
+                prev: Some(3),
+                next: Some(3),
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(1)), None);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(2)), None);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(3)), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: None,
+            }));
+
+            OwnedKittiesList::<Test>::remove(&0, 3);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &None), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: None,
+            }));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(1)), None);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(2)), None);
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(2)), None);
+        });
+    }
+
+    #[test]
+    fn fractionalized_kitty_is_redeemed_with_all_shares() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::fractionalize(Origin::signed(1), 0, 10));
+
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 0), "This cat is fractionalized");
+            assert_noop!(KittiesModule::transfer_shares(Origin::signed(1), 2, 0, 11), "Not enough shares");
+
+            assert_ok!(KittiesModule::transfer_shares(Origin::signed(1), 2, 0, 4));
+            assert_eq!(KittiesModule::kitty_shares(&(0, 1)), 6);
+            assert_eq!(KittiesModule::kitty_shares(&(0, 2)), 4);
+            assert_noop!(KittiesModule::redeem(Origin::signed(2), 0), "You need all shares to redeem this cat");
+
+            assert_ok!(KittiesModule::transfer_shares(Origin::signed(1), 2, 0, 6));
+            assert_ok!(KittiesModule::redeem(Origin::signed(2), 0));
+
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(KittiesModule::kitty_total_shares(0), None);
+            assert_eq!(KittiesModule::kitty_shares(&(0, 2)), 0);
+            assert_eq!(OwnedKittiesTest::get_item(&1, &None), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: None,
+            }));
+        });
+    }
+
+    #[test]
+    fn co_owned_kitty_transfers_after_threshold_approvals() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::set_co_owners(Origin::signed(1), 0, vec![1, 2, 3], 2));
+
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 4, 0), "This cat is co-owned, the action needs approvals");
+            assert_noop!(KittiesModule::propose_action(Origin::signed(4), 0, KittyAction::Transfer(4)), "You are not a co-owner of this cat");
+
+            assert_ok!(KittiesModule::propose_action(Origin::signed(2), 0, KittyAction::Transfer(4)));
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_noop!(KittiesModule::approve_action(Origin::signed(2), 0), "You already approved this action");
+
+            assert_ok!(KittiesModule::approve_action(Origin::signed(3), 0));
+            assert_eq!(KittiesModule::owner_of(0), Some(4));
+            assert_eq!(KittiesModule::kitty_co_owners(0), None);
+            assert_eq!(KittiesModule::pending_kitty_action(0), None);
+        });
+    }
+
+    #[test]
+    fn delegate_can_breed_but_not_transfer() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(2)));
+
+            assert_noop!(KittiesModule::breed(Origin::signed(2), 0, 1), "You can not breed with kitty_id_1");
+
+            assert_ok!(KittiesModule::delegate(Origin::signed(1), 0, 2));
+            assert_ok!(KittiesModule::breed(Origin::signed(2), 0, 1));
+            assert_eq!(KittiesModule::owner_of(2), Some(2));
+
+            assert_noop!(KittiesModule::transfer(Origin::signed(2), 3, 0), "Sender does not own this kitty");
+            assert_noop!(KittiesModule::set_price(Origin::signed(2), 0, 10), "You do not own this cat");
+
+            assert_ok!(KittiesModule::undelegate(Origin::signed(1), 0));
+            assert_noop!(KittiesModule::breed(Origin::signed(2), 0, 1), "You can not breed with kitty_id_1");
+        });
+    }
+
+    #[test]
+    fn accounts_can_watch_and_unwatch_kitties() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_noop!(KittiesModule::watch(Origin::signed(2), 0), "This cat does not exist");
+
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::watch(Origin::signed(2), 0));
+            assert_ok!(KittiesModule::watch(Origin::signed(3), 0));
+            assert_noop!(KittiesModule::watch(Origin::signed(2), 0), "You already watch this cat");
+            assert_eq!(KittiesModule::kitty_watchers(0), vec![2, 3]);
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+
+            assert_ok!(KittiesModule::unwatch(Origin::signed(2), 0));
+            assert_noop!(KittiesModule::unwatch(Origin::signed(2), 0), "You do not watch this cat");
+            assert_eq!(KittiesModule::kitty_watchers(0), vec![3]);
+        });
+    }
+
+    #[test]
+    fn leaderboards_are_sorted_and_bounded() {
+        let mut board = Vec::new();
+        for i in 0..15u64 {
+            update_leaderboard(&mut board, i, i + 1);
+        }
+        assert_eq!(board.len(), LEADERBOARD_SIZE);
+        assert_eq!(board[0], (14, 15));
+        assert_eq!(board[LEADERBOARD_SIZE - 1], (5, 6));
+
+        update_leaderboard(&mut board, 14, 0);
+        assert_eq!(board[0], (13, 14));
+
+        update_leaderboard(&mut board, 0, 100);
+        assert_eq!(board[0], (0, 100));
+    }
+
+    #[test]
+    fn largest_collections_follow_transfers() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(2)));
+            assert_eq!(KittiesModule::largest_collections(), vec![(1, 2), (2, 1)]);
+
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+            assert_eq!(KittiesModule::owned_kitties_count(1), 0);
+            assert_eq!(KittiesModule::largest_collections(), vec![(2, 3)]);
+        });
+    }
+
+    #[test]
+    fn owned_kitties_can_iterate_values() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_eq!(OwnedKittiesList::<Test>::iter(&0).collect::<Vec<_>>(), Vec::<u32>::new());
+
+            OwnedKittiesList::<Test>::append(&0, 1);
+            OwnedKittiesList::<Test>::append(&0, 2);
+            OwnedKittiesList::<Test>::append(&0, 3);
+            OwnedKittiesList::<Test>::remove(&0, 2);
+
+            assert_eq!(OwnedKittiesList::<Test>::iter(&0).collect::<Vec<_>>(), vec![1, 3]);
+        });
+    }
+
+    #[test]
+    fn owned_kitties_migrate_to_double_map() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            let old_key = |kitty_id: Option<u32>| raw_map_key(b"Kitties OwnedKitties", &(1u64, kitty_id));
+
+            runtime_io::set_storage(&raw_map_key(b"Kitties Kitties", &0u32), &KittyV1 { dna: [0; 16], price: 0u64 }.encode());
+            <KittyOwner<Test>>::insert(0, 1);
+            <KittiesCount<Test>>::put(1);
+            runtime_io::set_storage(&old_key(None), &KittyLinkedItem::<Test> { prev: Some(0), next: Some(0) }.encode());
+            runtime_io::set_storage(&old_key(Some(0)), &KittyLinkedItem::<Test> { prev: None, next: None }.encode());
+
+            KittiesModule::migrate_storage();
+
+            assert_eq!(KittiesModule::storage_version(), STORAGE_VERSION);
+            assert_eq!(KittiesModule::owned_kitties_count(1), 1);
+            assert_eq!(runtime_io::storage(&old_key(None)), None);
+            assert_eq!(runtime_io::storage(&old_key(Some(0))), None);
+            assert_eq!(OwnedKittiesList::<Test>::iter(&1).collect::<Vec<_>>(), vec![0]);
+        });
+    }
+
+    #[test]
+    fn lesson_4_layout_migrates_to_linked_list() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            for kitty_id in 0..3u32 {
+                runtime_io::set_storage(&raw_map_key(b"Kitties Kitties", &kitty_id), &[kitty_id as u8; 16]);
+                <KittyOwner<Test>>::insert(kitty_id, 1);
+                runtime_io::set_storage(&raw_map_key(b"Kitties OwnedKitties", &(1u64, kitty_id)), &kitty_id.encode());
+                runtime_io::set_storage(&raw_map_key(b"Kitties OwnedKittiesIndex", &kitty_id), &kitty_id.encode());
+            }
+            runtime_io::set_storage(&raw_map_key(b"Kitties OwnedKittiesCount", &1u64), &3u32.encode());
+            <KittiesCount<Test>>::put(3);
+
+            KittiesModule::migrate_storage();
+
+            assert_eq!(KittiesModule::kitty(2).map(|kitty| kitty.dna), Some([2; 16]));
+            assert_eq!(KittiesModule::owned_kitties_count(1), 3);
+            assert_eq!(OwnedKittiesList::<Test>::iter(&1).collect::<Vec<_>>(), vec![0, 1, 2]);
+            for kitty_id in 0..3u32 {
+                assert!(!runtime_io::exists_storage(&raw_map_key(b"Kitties OwnedKittiesIndex", &kitty_id)));
+                assert!(!runtime_io::exists_storage(&raw_map_key(b"Kitties OwnedKitties", &(1u64, kitty_id))));
+            }
+        });
+    }
+
+    #[test]
+    fn owned_kitties_iter_survives_cycles() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+            assert_eq!(KittiesModule::owned_kitties_iter(&1).collect::<Vec<_>>(), vec![0, 1, 2]);
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 2), vec![0, 1]);
+
+            // Corrupt the list so the last kitty points back to the first one
+            OwnedKittiesTest::insert_item(&1, &Some(2), KittyLinkedItem::<Test> { prev: Some(1), next: Some(0) });
+            assert_eq!(KittiesModule::owned_kitties_iter(&1).count(), 3);
+        });
+    }
+
+    #[test]
+    fn owned_kitties_count_follows_the_list() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::owned_kitties_count(1), 2);
+
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_eq!(KittiesModule::owned_kitties_count(1), 1);
+            assert_eq!(KittiesModule::owned_kitties_count(2), 1);
+
+            // Removing a kitty which is not in the list leaves the count alone
+            KittiesModule::remove_owned_kitty(&1, 0);
+            assert_eq!(KittiesModule::owned_kitties_count(1), 1);
+        });
+    }
+
+    #[test]
+    fn owned_kitties_ignore_double_append() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert!(OwnedKittiesList::<Test>::append(&0, 1));
+            assert!(OwnedKittiesList::<Test>::append(&0, 2));
+            assert!(!OwnedKittiesList::<Test>::append(&0, 1));
+
+            assert_eq!(OwnedKittiesTest::get_item(&0, &None), Some(KittyLinkedItem::<Test> {
+                prev: Some(2),
+                next: Some(1),
+            }));
+            assert_eq!(OwnedKittiesTest::get_item(&0, &Some(1)), Some(KittyLinkedItem::<Test> {
+                prev: None,
+                next: Some(2),
+            }));
+            assert_eq!(OwnedKittiesList::<Test>::iter(&0).collect::<Vec<_>>(), vec![1, 2]);
+        });
+    }
+
+    #[test]
+    fn owned_kitties_can_be_paged() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            for _ in 0..5 {
+                assert_ok!(KittiesModule::create(Origin::signed(1)));
+            }
+
+            assert_eq!(KittiesModule::owned_kitties_page(&1, None, 2), (vec![0, 1], Some(1)));
+            assert_eq!(KittiesModule::owned_kitties_page(&1, Some(1), 2), (vec![2, 3], Some(3)));
+            assert_eq!(KittiesModule::owned_kitties_page(&1, Some(3), 2), (vec![4], None));
+            assert_eq!(KittiesModule::owned_kitties_page(&1, Some(2), 2), (vec![3, 4], None));
+            assert_eq!(KittiesModule::owned_kitties_page(&2, None, 2), (vec![], None));
+        });
+    }
+
+    #[test]
+    fn all_kitties_follow_mint_and_burn() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(2)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::all_kitties_page(None, 10), (vec![0, 1, 2], None));
+
+            assert_noop!(KittiesModule::burn(Origin::signed(1), 1), "You do not own this cat");
+            assert_ok!(KittiesModule::burn(Origin::signed(2), 1));
+
+            assert_eq!(KittiesModule::kitty(1).is_none(), true);
+            assert_eq!(KittiesModule::owner_of(1), None);
+            assert_eq!(KittiesModule::owned_kitties_count(2), 0);
+            assert_eq!(KittiesModule::all_kitties_page(None, 1), (vec![0], Some(0)));
+            assert_eq!(KittiesModule::all_kitties_page(Some(0), 1), (vec![2], None));
+        });
+    }
+
+    #[test]
+    fn kitty_id_allocation_fails_on_overflow() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            <KittiesCount<Test>>::put(u32::max_value() - 1);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::owner_of(u32::max_value() - 1), Some(1));
+
+            assert_noop!(KittiesModule::create(Origin::signed(1)), "Kitties count overflow");
+            assert_eq!(KittiesModule::kitties_count(), u32::max_value());
+            assert_eq!(KittiesModule::kitty(u32::max_value()).is_none(), true);
+        });
+    }
+
+    #[test]
+    fn failed_co_owned_action_stays_pending() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::set_co_owners(Origin::signed(1), 0, vec![1, 2], 2));
+
+            // Breeding with a kitty which does not exist fails when the action executes
+            assert_ok!(KittiesModule::propose_action(Origin::signed(1), 0, KittyAction::Breed(5)));
+            assert_noop!(KittiesModule::approve_action(Origin::signed(2), 0), "Invalid kitty_id_2");
+            assert_eq!(KittiesModule::pending_kitty_action(0).map(|pending| pending.approvals), Some(vec![1]));
+        });
+    }
+
+    #[test]
+    fn buy_kitty_settles_through_reserve() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 10).balance(2, 100).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 60));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 60));
+
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 70);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 40);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+        });
+    }
+
+    #[test]
+    fn buy_kitty_pays_a_seller_without_balance() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 60));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 60));
+
+            // The seller account is created by the payment, the sale does not depend on it
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 60);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 40);
+        });
+    }
+
+    #[test]
+    fn market_can_be_disabled() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            MARKET_ENABLED.with(|v| *v.borrow_mut() = false);
+
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 0, 10), "The market is not enabled");
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10), "The market is not enabled");
+
+            // Everything outside the market keeps working
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        });
+    }
+
+    #[test]
+    fn bred_kitties_are_one_generation_older() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 2));
+
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.generation), Some(0));
+            assert_eq!(KittiesModule::kitty(2).map(|kitty| kitty.generation), Some(1));
+            assert_eq!(KittiesModule::kitty(3).map(|kitty| kitty.generation), Some(2));
+        });
+    }
+
+    #[test]
+    fn kitty_v1_records_migrate() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            runtime_io::set_storage(&raw_map_key(b"Kitties Kitties", &0u32), &KittyV1 { dna: [7; 16], price: 5u64 }.encode());
+            <KittiesCount<Test>>::put(1);
+            <StorageVersion>::put(2);
+
+            KittiesModule::migrate_storage();
+
+            let kitty = KittiesModule::kitty(0).unwrap();
+            assert_eq!((kitty.dna, kitty.price, kitty.generation), ([7; 16], 5, 0));
+            assert_eq!(KittiesModule::storage_version(), STORAGE_VERSION);
+        });
+    }
+
+    #[test]
+    fn owned_kitties_migrate_to_child_tries() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            for kitty_id in 0..3u32 {
+                <KittyOwner<Test>>::insert(kitty_id, 1);
+            }
+            LegacyOwnedKittiesList::<Test>::append(&1, 2);
+            LegacyOwnedKittiesList::<Test>::append(&1, 0);
+            LegacyOwnedKittiesList::<Test>::append(&1, 1);
+            <KittiesCount<Test>>::put(3);
+            <StorageVersion>::put(3);
+
+            KittiesModule::migrate_storage();
+
+            assert_eq!(OwnedKittiesList::<Test>::iter(&1).collect::<Vec<_>>(), vec![2, 0, 1]);
+            assert!(!<OwnedKitties<Test>>::exists(&1, &None));
+            assert!(!<OwnedKitties<Test>>::exists(&1, &Some(0)));
+            assert_eq!(KittiesModule::storage_version(), STORAGE_VERSION);
+        });
+    }
+
+    #[test]
+    fn kitty_v2_records_migrate_to_compact() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            let key = raw_map_key(b"Kitties Kitties", &0u32);
+            runtime_io::set_storage(&key, &KittyV2 { dna: [7; 16], price: 5u64, generation: 1 }.encode());
+            <KittiesCount<Test>>::put(1);
+            <StorageVersion>::put(4);
+
+            KittiesModule::migrate_storage();
+
+            let kitty = KittiesModule::kitty(0).unwrap();
+            assert_eq!((kitty.dna, kitty.price, kitty.generation), ([7; 16], 5, 1));
+            // dna, one byte each for the small price and generation, the soulbound flag and the empty season
+            assert_eq!(runtime_io::storage(&key).map(|raw| raw.len()), Some(20));
+        });
+    }
+
+    #[test]
+    fn changed_kitties_are_noted_for_the_indexer() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_eq!(KittiesModule::changed_kitties(), vec![0, 1]);
+
+            KittiesModule::on_initialize(2);
+            assert_eq!(KittiesModule::changed_kitties(), Vec::<u32>::new());
+
+            assert_ok!(KittiesModule::burn(Origin::signed(1), 1));
+            assert_eq!(KittiesModule::changed_kitties(), vec![1]);
+        });
+    }
+
+    #[test]
+    fn gen0_minting_pays_the_reported_price() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            <Gen0PriceOracle<Test>>::put((10, 1));
+
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 80);
+            assert_eq!(KittiesModule::gen0_minted(), 2);
+
+            // Bred kitties are not gen-0
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 80);
+
+            system::Module::<Test>::set_block_number(2 + GEN0_PRICE_MAX_AGE as u64);
+            assert_noop!(KittiesModule::create(Origin::signed(1)), "The gen-0 price is stale");
+        });
+    }
+
+    #[test]
+    fn gen0_price_reports_need_an_oracle_signature() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            let oracle = sr25519::Pair::from_seed(&[1; 32]);
+            let stranger = sr25519::Pair::from_seed(&[2; 32]);
+            assert_ok!(KittiesModule::set_gen0_price_oracles(system::RawOrigin::Root.into(), vec![oracle.public()]));
+            system::Module::<Test>::set_block_number(5);
+
+            // A report signed for another chain or module instance doesn't pass
+            assert!(KittiesModule::validate_unsigned(
+                &Call::submit_gen0_price(10, 5, oracle.public(), oracle.sign(&(10u64, 5u64).encode()))
+            ).is_err());
+
+            let report = KittiesModule::gen0_price_payload(10, 5);
+            let call = Call::submit_gen0_price(10, 5, oracle.public(), oracle.sign(&report));
+            assert!(KittiesModule::validate_unsigned(&call).is_ok());
+            let forged = Call::submit_gen0_price(10, 5, stranger.public(), stranger.sign(&report));
+            assert!(KittiesModule::validate_unsigned(&forged).is_err());
+
+            assert_ok!(KittiesModule::submit_gen0_price(system::RawOrigin::None.into(), 10, 5, oracle.public(), oracle.sign(&report)));
+            assert_eq!(KittiesModule::gen0_price_oracle(), Some((10, 5)));
+
+            system::Module::<Test>::set_block_number(6);
+            let report = KittiesModule::gen0_price_payload(110, 6);
+            assert_noop!(
+                KittiesModule::submit_gen0_price(system::RawOrigin::None.into(), 110, 6, oracle.public(), oracle.sign(&report)),
+                "The gen-0 price moves too far"
+            );
+        });
+    }
+
+    #[test]
+    fn market_listings_page_skips_kitties_not_for_sale() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            for _ in 0..4 {
+                assert_ok!(KittiesModule::create(Origin::signed(1)));
+            }
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 3, 30));
+
+            assert_eq!(KittiesModule::market_listings_page(None, 10), (vec![(1, 10), (3, 30)], None));
+            assert_eq!(KittiesModule::market_listings_page(None, 1), (vec![(1, 10)], Some(1)));
+            assert_eq!(KittiesModule::market_listings_page(Some(1), 1), (vec![(3, 30)], None));
+        });
+    }
+
+    #[test]
+    fn kitties_work_as_non_fungible_tokens() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_eq!(<KittiesModule as NonFungible<u64>>::mint(&1), Ok(0));
+            assert_eq!(<KittiesModule as NonFungible<u64>>::owner_of(&0), Some(1));
+
+            assert_ok!(<KittiesModule as NonFungible<u64>>::transfer(&1, &2, &0));
+            assert_eq!(<KittiesModule as NonFungible<u64>>::owner_of(&0), Some(2));
+            assert_eq!(<KittiesModule as NonFungible<u64>>::burn(&1, &0), Err("You do not own this cat"));
+
+            assert_ok!(<KittiesModule as NonFungible<u64>>::burn(&2, &0));
+            assert_eq!(<KittiesModule as NonFungible<u64>>::owner_of(&0), None);
+        });
+    }
+
+    #[test]
+    fn kitty_hooks_are_called() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_noop!(KittiesModule::burn(Origin::signed(1), 0), "You do not own this cat");
+            assert_ok!(KittiesModule::burn(Origin::signed(2), 0));
+
+            assert_eq!(
+                HOOK_CALLS.with(|calls| calls.borrow().clone()),
+                vec![("created", 1, 0), ("transfer", 2, 0), ("burned", 2, 0)]
+            );
+        });
+    }
+
+    #[test]
+    fn transfer_with_signature_uses_each_voucher_once() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            system::Module::<Test>::set_block_number(5);
+
+            let voucher = TransferVoucher { kitty_id: 0, to: 2, nonce: 0, deadline: 10 };
+            let payload = KittiesModule::transfer_voucher_payload(&voucher);
+
+            assert_noop!(
+                KittiesModule::transfer_with_signature(Origin::signed(3), voucher.clone(), TestSignature(3, payload.clone())),
+                "The voucher is not signed by the owner"
+            );
+            assert_ok!(KittiesModule::transfer_with_signature(Origin::signed(3), voucher.clone(), TestSignature(1, payload.clone())));
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(KittiesModule::nonce_of(&1, NoncePurpose::TransferVoucher), 1);
+
+            // Back to 1, the voucher can not bring the kitty to 2 again
+            assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 0));
+            assert_noop!(
+                KittiesModule::transfer_with_signature(Origin::signed(3), voucher, TestSignature(1, payload)),
+                "The voucher nonce is not the owner's next nonce"
+            );
+
+            let expired = TransferVoucher { kitty_id: 0, to: 2, nonce: 1, deadline: 4 };
+            let payload = KittiesModule::transfer_voucher_payload(&expired);
+            assert_noop!(
+                KittiesModule::transfer_with_signature(Origin::signed(3), expired, TestSignature(1, payload)),
+                "The voucher has expired"
+            );
+
+            // Signed for another chain
+            let voucher = TransferVoucher { kitty_id: 0, to: 2, nonce: 1, deadline: 10 };
+            let payload = (b"kitties-transfer-voucher", H256::zero(), &voucher).encode();
+            assert_noop!(
+                KittiesModule::transfer_with_signature(Origin::signed(3), voucher, TestSignature(1, payload)),
+                "The voucher is not signed by the owner"
+            );
+        });
+    }
+
+    #[test]
+    fn kitty_events_are_indexed_by_kitty_and_accounts() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            system::Module::<Test>::set_block_number(1);
+            let topic_events = |topic: H256| system::Module::<Test>::event_topics(&topic).len();
+            let kitty_topic = <Test as system::Trait>::Hashing::hash_of(&0u32);
+
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(topic_events(kitty_topic), 1);
+
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_eq!(topic_events(kitty_topic), 2);
+            assert_eq!(topic_events(<Test as system::Trait>::Hashing::hash_of(&1u64)), 2);
+            assert_eq!(topic_events(<Test as system::Trait>::Hashing::hash_of(&2u64)), 1);
+        });
+    }
+
+    #[test]
+    fn ext_builder_endows_accounts_and_seeds_kitties() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 50).kitty(1).kitty(2).kitty(1).build(), || {
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 50);
+            assert_eq!(KittiesModule::kitties_count(), 3);
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_eq!(KittiesModule::owner_of(1), Some(2));
+            assert_eq!(KittiesModule::owner_of(2), Some(1));
+            assert_eq!(KittiesModule::changed_kitties(), Vec::<u32>::new());
+        });
+    }
+
+    #[test]
+    fn set_price_lists_and_unlists_a_kitty() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            assert_noop!(KittiesModule::set_price(Origin::signed(2), 0, 10), "You do not own this cat");
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 1, 10), "This cat does not exist");
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(10));
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 0));
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(0));
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10), "The cat you want to buy is not for sale");
+        });
+    }
+
+    #[test]
+    fn buy_kitty_moves_funds_owner_and_lists() {
+        let ext = ExtBuilder::default().balance(1, 10).balance(2, 100).kitty(1).kitty(1).kitty(2);
+        with_externalities(&mut ext.build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 50));
+
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 40);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 70);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(0));
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![1]);
+            assert_eq!(KittiesModule::owned_kitty_ids(&2, 10), vec![2, 0]);
+            assert_eq!(KittiesModule::recent_sales(), vec![30]);
+        });
+    }
+
+    #[test]
+    fn buy_kitty_rejects_invalid_purchases() {
+        let ext = ExtBuilder::default().balance(1, 100).balance(2, 20).kitty(1).kitty(2);
+        with_externalities(&mut ext.build(), || {
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 5, 50), "This cat does not exist");
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, 50), "The cat you want to buy is not for sale");
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(1), 0, 30), "You can't buy your own cat");
+            assert_noop!(
+                KittiesModule::buy_kitty(Origin::signed(2), 0, 29),
+                "The cat you want to buy costs more than your max price"
+            );
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, 30), "not enough free funds");
+
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 20);
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![0]);
+            assert_eq!(KittiesModule::owned_kitty_ids(&2, 10), vec![1]);
+        });
+    }
+
+    #[test]
+    fn sold_kitty_can_be_resold() {
+        let ext = ExtBuilder::default().balance(2, 100).balance(3, 100).kitty(1);
+        with_externalities(&mut ext.build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 40));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 40));
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(3), 0, 40), "The cat you want to buy is not for sale");
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(2), 0, 50));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(3), 0, 50));
+
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 40);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 110);
+            assert_eq!(balances::Module::<Test>::free_balance(&3), 50);
+            assert_eq!(KittiesModule::owner_of(0), Some(3));
+            assert!(KittiesModule::owned_kitty_ids(&1, 10).is_empty());
+            assert!(KittiesModule::owned_kitty_ids(&2, 10).is_empty());
+            assert_eq!(KittiesModule::owned_kitty_ids(&3, 10), vec![0]);
+        });
+    }
+
+    /// Deterministic xorshift generator for the randomized tests, a failing case replays from its seed
+    struct Rng(u64);
+    impl Rng {
+        fn below(&mut self, bound: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % bound
+        }
+    }
+
+    /// Walk the list of `account` from its head checking both links of every node, returns the values in order
+    fn checked_owned_list(account: u64, seed: u64) -> Vec<u32> {
+        let head = OwnedKittiesTest::get_item(&account, &None).unwrap_or(KittyLinkedItem::<Test> { prev: None, next: None });
+        let mut values = Vec::new();
+        let mut prev = None;
+        let mut current = head.next;
+        while let Some(value) = current {
+            assert!(values.len() < 1_000, "seed {}: list of {} has a cycle", seed, account);
+            let item = OwnedKittiesTest::get_item(&account, &Some(value))
+                .unwrap_or_else(|| panic!("seed {}: dangling link to {} in list of {}", seed, value, account));
+            assert_eq!(item.prev, prev, "seed {}: wrong prev of {} in list of {}", seed, value, account);
+            values.push(value);
+            prev = Some(value);
+            current = item.next;
+        }
+        assert_eq!(head.prev, prev, "seed {}: head of list of {} does not point to its tail", seed, account);
+        values
+    }
+
+    #[test]
+    fn owned_list_matches_a_model_under_random_operations() {
+        for seed in 1..=200u64 {
+            with_externalities(&mut ExtBuilder::default().build(), || {
+                let mut rng = Rng(seed);
+                let mut model: Vec<Vec<u32>> = vec![Vec::new(); 3];
+                for _ in 0..50 {
+                    let account = rng.below(3);
+                    let value = rng.below(8) as u32;
+                    let list = &mut model[account as usize];
+                    if rng.below(2) == 0 {
+                        let appended = OwnedKittiesList::<Test>::append(&account, value);
+                        assert_eq!(appended, !list.contains(&value), "seed {}", seed);
+                        if appended {
+                            list.push(value);
+                        }
+                    } else {
+                        let removed = OwnedKittiesList::<Test>::remove(&account, value);
+                        assert_eq!(removed, list.contains(&value), "seed {}", seed);
+                        list.retain(|v| *v != value);
+                    }
+
+                    for account in 0..3 {
+                        assert_eq!(checked_owned_list(account, seed), model[account as usize], "seed {}", seed);
+                    }
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn owned_lists_follow_kitty_owner_under_random_operations() {
+        for seed in 1..=100u64 {
+            with_externalities(&mut ExtBuilder::default().build(), || {
+                let mut rng = Rng(seed);
+                for _ in 0..40 {
+                    let account = rng.below(4);
+                    let count = KittiesModule::kitties_count() as u64;
+                    match rng.below(3) {
+                        0 => { let _ = KittiesModule::create(Origin::signed(account)); }
+                        1 if count > 0 => {
+                            let kitty_id = rng.below(count) as u32;
+                            if let Some(owner) = KittiesModule::owner_of(kitty_id) {
+                                let _ = KittiesModule::transfer(Origin::signed(owner), account, kitty_id);
+                            }
+                        }
+                        2 if count > 0 => {
+                            let kitty_id = rng.below(count) as u32;
+                            if let Some(owner) = KittiesModule::owner_of(kitty_id) {
+                                let _ = <KittiesModule as NonFungible<u64>>::burn(&owner, &kitty_id);
+                            }
+                        }
+                        _ => {}
+                    }
+
+                    for account in 0..4 {
+                        let mut listed = checked_owned_list(account, seed);
+                        listed.sort();
+                        let owned: Vec<u32> = (0..KittiesModule::kitties_count())
+                            .filter(|kitty_id| KittiesModule::owner_of(*kitty_id) == Some(account))
+                            .collect();
+                        assert_eq!(listed, owned, "seed {}: list of {} differs from KittyOwner", seed, account);
+                        assert_eq!(KittiesModule::owned_kitties_count(account), owned.len() as u64, "seed {}", seed);
+                    }
+                    assert_ok!(KittiesModule::do_try_state());
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn combine_dna_takes_every_bit_from_the_selected_parent() {
+        for dna1 in 0..=255u8 {
+            for dna2 in 0..=255u8 {
+                for selector in 0..=255u8 {
+                    let dna = combine_dna(dna1, dna2, selector);
+                    for bit in 0..8 {
+                        let parent = if selector >> bit & 1 == 1 { dna1 } else { dna2 };
+                        assert_eq!(
+                            dna >> bit & 1, parent >> bit & 1,
+                            "combine_dna({}, {}, {}) bit {}", dna1, dna2, selector, bit
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bred_dna_only_has_bits_of_the_parents() {
+        let mut rng = Rng(0x5eed);
+        let mut random_dna = || {
+            let mut dna = [0u8; 16];
+            for byte in dna.iter_mut() {
+                *byte = rng.below(256) as u8;
+            }
+            dna
+        };
+        for _ in 0..10_000 {
+            let (dna1, dna2, selector) = (random_dna(), random_dna(), random_dna());
+            let dna = breed_dna(&dna1, &dna2, &selector);
+            for i in 0..16 {
+                // Bits shared by the parents are inherited as is, the others follow the selector
+                assert_eq!(dna[i] & !(dna1[i] ^ dna2[i]), dna1[i] & dna2[i]);
+                assert_eq!((dna[i] ^ dna1[i]) & selector[i], 0);
+                assert_eq!((dna[i] ^ dna2[i]) & !selector[i], 0);
+            }
+        }
+    }
+
+    #[test]
+    fn bred_kitties_inherit_their_bits_from_the_parents() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            for block in 1..50 {
+                system::Module::<Test>::set_block_number(block);
+                let (dna1, dna2) = (KittiesModule::kitty(0).unwrap().dna, KittiesModule::kitty(1).unwrap().dna);
+
+                assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+                let child = KittiesModule::kitty(KittiesModule::kitties_count() - 1).unwrap();
+                for i in 0..16 {
+                    let from_a_parent = (child.dna[i] & dna1[i]) | (!child.dna[i] & !dna1[i])
+                        | (child.dna[i] & dna2[i]) | (!child.dna[i] & !dna2[i]);
+                    assert_eq!(from_a_parent, 0xff, "byte {} of the child of block {}", i, block);
+                }
+                assert_eq!(child.generation, 1);
+            }
+        });
+    }
+
+    #[test]
+    fn try_state_accepts_a_consistent_storage() {
+        let ext = ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).kitty(2);
+        with_externalities(&mut ext.build(), || {
+            assert_ok!(KittiesModule::do_try_state());
+
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 3, 0));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 1, 10));
+            assert_ok!(<KittiesModule as NonFungible<u64>>::burn(&2, &2));
+            assert_ok!(KittiesModule::do_try_state());
+        });
+    }
+
+    #[test]
+    fn try_state_detects_corruptions() {
+        let ext = || ExtBuilder::default().kitty(1).kitty(1).kitty(2).build();
+
+        with_externalities(&mut ext(), || {
+            <KittiesCount<Test>>::put(2);
+            assert_eq!(KittiesModule::do_try_state(), Err("A kitty index is not below KittiesCount"));
+        });
+        with_externalities(&mut ext(), || {
+            <KittyOwner<Test>>::remove(1);
+            assert_eq!(KittiesModule::do_try_state(), Err("A kitty has no owner"));
+        });
+        with_externalities(&mut ext(), || {
+            <Kitties<Test>>::remove(1);
+            assert_eq!(KittiesModule::do_try_state(), Err("An owner entry has no kitty"));
+        });
+        with_externalities(&mut ext(), || {
+            <KittyOwner<Test>>::insert(1, 2);
+            assert_eq!(KittiesModule::do_try_state(), Err("An owned kitties list holds a kitty of another account"));
+        });
+        with_externalities(&mut ext(), || {
+            OwnedKittiesList::<Test>::remove(&1, 1);
+            assert_eq!(KittiesModule::do_try_state(), Err("OwnedKittiesCount differs from the list"));
+        });
+        with_externalities(&mut ext(), || {
+            OwnedKittiesList::<Test>::remove(&1, 1);
+            <OwnedKittiesCount<Test>>::insert(1, 1);
+            assert_eq!(KittiesModule::do_try_state(), Err("An owned kitty is missing from its owner's list"));
+        });
+    }
+
+    #[test]
+    fn only_governance_can_set_parameters() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_noop!(
+                KittiesModule::set_parameter(Origin::signed(1), KittiesParameter::BreedingFee(5)),
+                "Invalid origin"
+            );
+
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::BreedingFee(5)));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketFee(Permill::from_percent(10))));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::BreedingCooldown(3)));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxKittiesPerAccount(Some(2))));
+            assert_eq!(KittiesModule::breeding_fee(), 5);
+            assert_eq!(KittiesModule::market_fee(), Permill::from_percent(10));
+            assert_eq!(KittiesModule::breeding_cooldown(), 3);
+            assert_eq!(KittiesModule::max_kitties_per_account(), Some(2));
+
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxKittiesPerAccount(None)));
+            assert_eq!(KittiesModule::max_kitties_per_account(), None);
+        });
+    }
+
+    #[test]
+    fn breeding_burns_the_fee_and_respects_the_cooldown() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 12).kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::BreedingFee(5)));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::BreedingCooldown(3)));
+
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 7);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 7);
+            assert_eq!(KittiesModule::breeding_ready_at(0), 4);
+
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), "kitty_id_1 is cooling down");
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 2, 1), "kitty_id_2 is cooling down");
+
+            system::Module::<Test>::set_block_number(4);
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 2);
+
+            system::Module::<Test>::set_block_number(7);
+            assert!(KittiesModule::breed(Origin::signed(1), 0, 1).is_err());
+            assert_eq!(KittiesModule::kitties_count(), 4);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 2);
+        });
+    }
+
+    #[test]
+    fn market_fee_goes_to_the_treasury() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketFee(Permill::from_percent(10))));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 50));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 50));
+
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 45);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 50);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+            assert_eq!(balances::Module::<Test>::free_balance(&KittiesModule::treasury_account()), 5);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 100);
+        });
+    }
+
+    #[test]
+    fn accounts_can_not_own_more_kitties_than_the_cap() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).kitty(2).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxKittiesPerAccount(Some(2))));
+
+            assert_noop!(KittiesModule::create(Origin::signed(1)), "The account owns too many kitties");
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), "The account owns too many kitties");
+            assert_noop!(KittiesModule::transfer(Origin::signed(2), 1, 2), "The account owns too many kitties");
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 1, 10), "The account owns too many kitties");
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 1), "The account owns too many kitties");
+
+            assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 2));
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![1, 2]);
+        });
+    }
+
+    #[test]
+    fn blacklisted_kitties_can_not_be_traded_or_bred() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_noop!(KittiesModule::set_kitty_blacklisted(Origin::signed(1), 0, true), "Invalid origin");
+            assert_ok!(KittiesModule::set_kitty_blacklisted(Origin::ROOT, 0, true));
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(0));
+
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 0, 10), "This cat is blacklisted");
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 1, 0), "This cat is blacklisted");
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+
+            assert_ok!(KittiesModule::set_kitty_blacklisted(Origin::ROOT, 0, false));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10));
+        });
+    }
+
+    #[test]
+    fn barred_accounts_can_not_use_the_marketplace() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_ok!(KittiesModule::set_account_barred(Origin::ROOT, 1, true));
+
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 1, 10), "This account is barred from the marketplace");
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10), "This account is barred from the marketplace");
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+
+            assert_ok!(KittiesModule::set_account_barred(Origin::ROOT, 1, false));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10));
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_ok!(KittiesModule::set_account_barred(Origin::ROOT, 2, true));
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 1, 10), "This account is barred from the marketplace");
+        });
+    }
+
+    #[test]
+    fn subsystems_pause_independently() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).build(), || {
+            assert_noop!(KittiesModule::set_paused(Origin::signed(1), Subsystem::Breeding, true), "Invalid origin");
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Breeding, true));
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), "This subsystem is paused");
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Minting, true));
+            assert_noop!(KittiesModule::create(Origin::signed(1)), "This subsystem is paused");
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Market, true));
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10), "This subsystem is paused");
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 1, 10), "This subsystem is paused");
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Transfers, true));
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 2), "This subsystem is paused");
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Transfers, false));
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Market, false));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10));
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Breeding, false));
+            assert_ok!(KittiesModule::breed(Origin::signed(2), 0, 1));
+        });
+    }
+
+    #[test]
+    fn verified_breeders_flag_their_mints_and_listings() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_noop!(KittiesModule::set_verified_breeder(Origin::signed(1), 1, true), "Invalid origin");
+            assert_ok!(KittiesModule::set_verified_breeder(Origin::ROOT, 1, true));
+            assert!(KittiesModule::is_verified_breeder(1));
+
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(2)));
+            assert!(KittiesModule::is_minted_by_verified_breeder(0));
+            assert!(!KittiesModule::is_minted_by_verified_breeder(1));
+
+            let listed = |seller: u64, kitty_id: u32, verified: bool| system::Module::<Test>::events().into_iter()
+                .any(|record| record.event == TestEvent::kitties(RawEvent::Listed(seller, kitty_id, 10, verified)));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_ok!(KittiesModule::set_price(Origin::signed(2), 1, 10));
+            assert!(listed(1, 0, true));
+            assert!(listed(2, 1, false));
+
+            assert_ok!(KittiesModule::set_verified_breeder(Origin::ROOT, 1, false));
+            assert!(!KittiesModule::is_verified_breeder(1));
+            assert!(KittiesModule::is_minted_by_verified_breeder(0));
+        });
+    }
+
+    #[test]
+    fn escrowed_trade_without_a_dispute_window_settles_the_next_block() {
+        DISPUTE_WINDOW.with(|v| *v.borrow_mut() = 0);
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_ok!(KittiesModule::buy_kitty_escrowed(Origin::signed(2), 0, 30));
+            assert_eq!(KittiesModule::escrowed_trade(0).map(|trade| trade.release_at), Some(2));
+
+            KittiesModule::on_initialize(2);
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 30);
+        });
+    }
+
+    #[test]
+    fn escrowed_trade_settles_after_the_dispute_window() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_ok!(KittiesModule::buy_kitty_escrowed(Origin::signed(2), 0, 30));
+
+            assert_eq!(KittiesModule::escrowed_trade(0).map(|trade| trade.release_at), Some(6));
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(0));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 30);
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 3, 0), "This cat is in escrow");
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 0, 40), "This cat is in escrow");
+            assert_noop!(KittiesModule::burn(Origin::signed(1), 0), "This cat is in escrow");
+
+            KittiesModule::on_initialize(5);
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+
+            KittiesModule::on_initialize(6);
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(KittiesModule::escrowed_trade(0), None);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 30);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 70);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+        });
+    }
+
+    #[test]
+    fn disputed_trades_wait_for_the_arbiter() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 20));
+            assert_ok!(KittiesModule::buy_kitty_escrowed(Origin::signed(2), 0, 30));
+            assert_ok!(KittiesModule::buy_kitty_escrowed(Origin::signed(2), 1, 20));
+
+            assert_noop!(KittiesModule::dispute_trade(Origin::signed(3), 0), "Only the buyer or the seller can dispute a trade");
+            assert_ok!(KittiesModule::dispute_trade(Origin::signed(2), 0));
+            assert_ok!(KittiesModule::dispute_trade(Origin::signed(1), 1));
+            assert_noop!(KittiesModule::dispute_trade(Origin::signed(1), 1), "This trade is already disputed");
+
+            KittiesModule::on_initialize(6);
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 50);
+
+            assert_noop!(KittiesModule::resolve_dispute(Origin::signed(2), 0, true), "Invalid origin");
+            assert_ok!(KittiesModule::resolve_dispute(Origin::ROOT, 0, false));
+            assert_ok!(KittiesModule::resolve_dispute(Origin::ROOT, 1, true));
+
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_eq!(KittiesModule::owner_of(1), Some(2));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 20);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 80);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+            assert_noop!(KittiesModule::resolve_dispute(Origin::ROOT, 1, true), "This cat is not in escrow");
+        });
+    }
+
+    #[test]
+    fn escrowed_trades_wait_while_settlement_is_paused() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 20));
+            assert_ok!(KittiesModule::buy_kitty_escrowed(Origin::signed(2), 0, 30));
+            assert_ok!(KittiesModule::buy_kitty_escrowed(Origin::signed(2), 1, 20));
+            assert_ok!(KittiesModule::dispute_trade(Origin::signed(1), 1));
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Settlement, true));
+
+            KittiesModule::on_initialize(6);
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_eq!(KittiesModule::escrowed_trade(0).map(|trade| trade.release_at), Some(7));
+            assert_noop!(KittiesModule::resolve_dispute(Origin::ROOT, 1, true), "This subsystem is paused");
+            assert_ok!(KittiesModule::resolve_dispute(Origin::ROOT, 1, false));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 30);
+
+            KittiesModule::on_initialize(7);
+            assert_eq!(KittiesModule::escrowed_trade(0).map(|trade| trade.release_at), Some(8));
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Settlement, false));
+            KittiesModule::on_initialize(8);
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(KittiesModule::escrowed_trade(0), None);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 30);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+        });
+    }
+
+    #[test]
+    fn scheduled_transfers_run_at_their_block() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_noop!(
+                KittiesModule::schedule_transfer(Origin::signed(1), 2, 0, 1),
+                "The transfer must be scheduled for a future block"
+            );
+            assert_noop!(KittiesModule::schedule_transfer(Origin::signed(2), 3, 0, 5), "Sender does not own this kitty");
+
+            assert_ok!(KittiesModule::schedule_transfer(Origin::signed(1), 2, 0, 5));
+            assert_ok!(KittiesModule::schedule_transfer(Origin::signed(1), 3, 1, 5));
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(0));
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 3, 0), "This cat is scheduled for a transfer");
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 0, 10), "This cat is scheduled for a transfer");
+
+            assert_noop!(KittiesModule::cancel_scheduled_transfer(Origin::signed(2), 1), "You do not own this cat");
+            assert_ok!(KittiesModule::cancel_scheduled_transfer(Origin::signed(1), 1));
+
+            KittiesModule::on_initialize(4);
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+
+            KittiesModule::on_initialize(5);
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(KittiesModule::owner_of(1), Some(1));
+            assert_eq!(KittiesModule::scheduled_transfer(0), None);
+            assert_eq!(KittiesModule::owned_kitty_ids(&2, 10), vec![0]);
+        });
+    }
+
+    #[test]
+    fn scheduled_transfer_is_called_off_when_no_longer_allowed() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            assert_ok!(KittiesModule::schedule_transfer(Origin::signed(1), 2, 0, 5));
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Transfers, true));
+
+            KittiesModule::on_initialize(5);
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_eq!(KittiesModule::scheduled_transfer(0), None);
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Transfers, false));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+        });
+    }
+
+    #[test]
+    fn heirs_inherit_the_collection_of_inactive_accounts() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_noop!(KittiesModule::set_heirs(Origin::signed(1), vec![1], 10), "You can not be your own heir");
+            assert_ok!(KittiesModule::set_heirs(Origin::signed(1), vec![2, 3], 10));
+            assert_ok!(KittiesModule::schedule_transfer(Origin::signed(1), 4, 2, 100));
+
+            system::Module::<Test>::set_block_number(10);
+            assert_noop!(KittiesModule::claim_inheritance(Origin::signed(2), 1), "The owner is still active");
+            system::Module::<Test>::set_block_number(11);
+            assert_noop!(KittiesModule::claim_inheritance(Origin::signed(4), 1), "You are not an heir of this account");
+            assert_ok!(KittiesModule::claim_inheritance(Origin::signed(2), 1));
+            assert_noop!(KittiesModule::claim_inheritance(Origin::signed(3), 1), "The collection is already claimed");
+
+            system::Module::<Test>::set_block_number(20);
+            assert_noop!(KittiesModule::complete_inheritance(Origin::signed(2), 1), "The veto delay is not over");
+            system::Module::<Test>::set_block_number(21);
+            assert_noop!(
+                KittiesModule::complete_inheritance(Origin::signed(3), 1),
+                "Only the claiming heir can complete the inheritance"
+            );
+            assert_ok!(KittiesModule::complete_inheritance(Origin::signed(2), 1));
+
+            assert_eq!(KittiesModule::owned_kitty_ids(&2, 10), vec![0, 1]);
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![2]);
+            assert_eq!(KittiesModule::inheritance_claim(1), None);
+        });
+    }
+
+    #[test]
+    fn inheritance_batches_count_the_kitties_left_behind() {
+        let builder = (0..=INHERITANCE_BATCH).fold(ExtBuilder::default(), |builder, _| builder.kitty(1));
+        with_externalities(&mut builder.build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_heirs(Origin::signed(1), vec![2], 5));
+            assert_ok!(KittiesModule::schedule_transfer(Origin::signed(1), 4, 0, 100));
+            system::Module::<Test>::set_block_number(6);
+            assert_ok!(KittiesModule::claim_inheritance(Origin::signed(2), 1));
+            system::Module::<Test>::set_block_number(11);
+
+            // The scheduled kitty uses up a place of the first batch
+            assert_ok!(KittiesModule::complete_inheritance(Origin::signed(2), 1));
+            assert_eq!(KittiesModule::owned_kitties_count(2), INHERITANCE_BATCH as u64 - 1);
+            assert_eq!(KittiesModule::inheritance_cursor(1), Some(0));
+            assert!(KittiesModule::inheritance_claim(1).is_some());
+
+            assert_ok!(KittiesModule::complete_inheritance(Origin::signed(2), 1));
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![0]);
+            assert_eq!(KittiesModule::inheritance_claim(1), None);
+            assert_eq!(KittiesModule::inheritance_cursor(1), None);
+        });
+    }
+
+    #[test]
+    fn owners_veto_claims_by_being_active() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_heirs(Origin::signed(1), vec![2], 5));
+
+            system::Module::<Test>::set_block_number(6);
+            assert_ok!(KittiesModule::claim_inheritance(Origin::signed(2), 1));
+            assert_ok!(KittiesModule::veto_inheritance(Origin::signed(1)));
+            assert_noop!(KittiesModule::complete_inheritance(Origin::signed(2), 1), "The collection is not claimed");
+
+            system::Module::<Test>::set_block_number(11);
+            assert_ok!(KittiesModule::claim_inheritance(Origin::signed(2), 1));
+            system::Module::<Test>::set_block_number(12);
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 3, 1));
+
+            system::Module::<Test>::set_block_number(16);
+            assert_noop!(KittiesModule::complete_inheritance(Origin::signed(2), 1), "The owner was active since the claim");
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+        });
+    }
+
+    #[test]
+    fn soulbound_kitties_never_move() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            assert_noop!(KittiesModule::mint_soulbound(Origin::signed(1), 1), "Invalid origin");
+            assert_ok!(KittiesModule::mint_soulbound(Origin::ROOT, 1));
+            assert_eq!(KittiesModule::kitty(1).map(|kitty| kitty.is_soulbound()), Some(true));
+
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 1), "This cat is soulbound");
+            assert_noop!(KittiesModule::set_price(Origin::signed(1), 1, 10), "This cat is soulbound");
+            assert_noop!(KittiesModule::burn(Origin::signed(1), 1), "This cat is soulbound");
+            assert_noop!(KittiesModule::fractionalize(Origin::signed(1), 1, 10), "This cat is soulbound");
+            assert_noop!(KittiesModule::schedule_transfer(Origin::signed(1), 2, 1, 10), "This cat is soulbound");
+
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(KittiesModule::kitty(2).map(|kitty| kitty.is_soulbound()), Some(false));
+
+            assert_noop!(KittiesModule::burn_soulbound(Origin::ROOT, 0), "This cat is not soulbound");
+            assert_ok!(KittiesModule::burn_soulbound(Origin::ROOT, 1));
+            assert_eq!(KittiesModule::owner_of(1), None);
+        });
+    }
+
+    #[test]
+    fn kitty_v3_records_migrate_to_soulbound() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            let key = raw_map_key(b"Kitties Kitties", &0u32);
+            runtime_io::set_storage(&key, &KittyV3 { dna: [7; 16], price: 5u64, generation: 1 }.encode());
+            <KittiesCount<Test>>::put(1);
+            <StorageVersion>::put(5);
+
+            KittiesModule::migrate_storage();
+
+            let kitty = KittiesModule::kitty(0).unwrap();
+            assert_eq!((kitty.dna, kitty.price, kitty.generation, kitty.soulbound), ([7; 16], 5, 1, false));
+            assert_eq!(KittiesModule::storage_version(), STORAGE_VERSION);
+        });
+    }
+
+    #[test]
+    fn achievements_unlock_once_and_mint_badges() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            ACHIEVEMENT_BADGES.with(|v| *v.borrow_mut() = true);
+
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(KittiesModule::achievements_of(1), ACHIEVEMENT_FIRST_BREED);
+            // The bred kitty and the badge
+            assert_eq!(KittiesModule::kitties_count(), 4);
+            assert_eq!(KittiesModule::kitty(3).map(|kitty| kitty.is_soulbound()), Some(true));
+
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(KittiesModule::kitties_count(), 5);
+        });
+    }
+
+    #[test]
+    fn breeding_generation_5_unlocks_an_achievement() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            <Kitties<Test>>::mutate(0, |kitty| kitty.as_mut().unwrap().generation = 4);
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(KittiesModule::achievements_of(1), ACHIEVEMENT_FIRST_BREED | ACHIEVEMENT_FIRST_GEN5);
+            assert_eq!(KittiesModule::kitties_count(), 3);
+        });
+    }
+
+    #[test]
+    fn owning_a_hundred_kitties_unlocks_an_achievement() {
+        with_externalities(&mut ExtBuilder::default().kitty(2).build(), || {
+            for _ in 0..99 {
+                assert_ok!(KittiesModule::create(Origin::signed(1)));
+            }
+            assert_eq!(KittiesModule::achievements_of(1), 0);
+
+            assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 0));
+            assert_eq!(KittiesModule::achievements_of(1), ACHIEVEMENT_HUNDRED_KITTIES);
+        });
+    }
+
+    #[test]
+    fn quests_track_progress_and_pay_rewards() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_noop!(
+                KittiesModule::add_quest(Origin::signed(1), QuestObjective::Sales(2), QuestReward::Balance(50), 10),
+                "Invalid origin"
+            );
+            assert_ok!(KittiesModule::add_quest(Origin::ROOT, QuestObjective::Sales(2), QuestReward::Balance(50), 10));
+            assert_ok!(KittiesModule::add_quest(Origin::ROOT, QuestObjective::Breeds(1), QuestReward::Kitty, 10));
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10));
+            assert_noop!(KittiesModule::claim_quest_reward(Origin::signed(1), 0), "The quest is not completed");
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 1, 10));
+            // Balance rewards are paid out of the quest pool, not issued
+            assert_noop!(KittiesModule::claim_quest_reward(Origin::signed(1), 0), "The quest pool is short of the reward");
+            assert_ok!(KittiesModule::fund_quest_pool(Origin::signed(2), 60));
+            assert_ok!(KittiesModule::claim_quest_reward(Origin::signed(1), 0));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 70);
+            assert_eq!(KittiesModule::quest_pool(), 10);
+            assert_noop!(KittiesModule::claim_quest_reward(Origin::signed(1), 0), "The reward is already claimed");
+
+            assert_ok!(KittiesModule::breed(Origin::signed(2), 0, 1));
+            assert_ok!(KittiesModule::claim_quest_reward(Origin::signed(2), 1));
+            assert_eq!(KittiesModule::owned_kitty_ids(&2, 10), vec![0, 1, 3, 4]);
+        });
+    }
+
+    #[test]
+    fn quests_stop_counting_when_they_end() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            let dna = KittiesModule::kitty(0).unwrap().dna;
+            let objective = QuestObjective::BreedWithTrait { byte: 0, mask: 0, value: 0 };
+            assert_ok!(KittiesModule::add_quest(Origin::ROOT, objective, QuestReward::Balance(5), 2));
+            assert_noop!(
+                KittiesModule::add_quest(Origin::ROOT, QuestObjective::BreedWithTrait { byte: 16, mask: 1, value: 1 }, QuestReward::Kitty, 5),
+                "The DNA has 16 bytes"
+            );
+            assert!(dna_has_trait(&dna, 3, 0xff, dna[3]));
+
+            system::Module::<Test>::set_block_number(3);
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(KittiesModule::quest_progress(0, 1), 0);
+            assert_noop!(KittiesModule::claim_quest_reward(Origin::signed(1), 0), "The quest is not completed");
+        });
+    }
+
+    #[test]
+    fn kitty_v4_records_migrate_to_seasons() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            let key = raw_map_key(b"Kitties Kitties", &0u32);
+            runtime_io::set_storage(&key, &KittyV4 { dna: [7; 16], price: 5u64, generation: 1, soulbound: true }.encode());
+            <KittiesCount<Test>>::put(1);
+            <StorageVersion>::put(6);
+
+            KittiesModule::migrate_storage();
+
+            let kitty = KittiesModule::kitty(0).unwrap();
+            assert_eq!((kitty.dna, kitty.generation, kitty.soulbound, kitty.season), ([7; 16], 1, true, None));
+            assert_eq!(KittiesModule::storage_version(), STORAGE_VERSION);
+        });
+    }
+
+    #[test]
+    fn seasons_tag_kitties_and_shape_their_genes() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            let genes = vec![GeneRange { byte: 0, min: 200, max: 209 }, GeneRange { byte: 15, min: 7, max: 7 }];
+            assert_noop!(
+                KittiesModule::add_season(Origin::signed(1), 2, 5, genes.clone(), Some(2)),
+                "Invalid origin"
+            );
+            assert_noop!(
+                KittiesModule::add_season(Origin::ROOT, 2, 5, vec![GeneRange { byte: 0, min: 3, max: 2 }], None),
+                "Invalid gene range"
+            );
+            assert_ok!(KittiesModule::add_season(Origin::ROOT, 2, 5, genes, Some(2)));
+            assert_noop!(KittiesModule::add_season(Origin::ROOT, 5, 8, Vec::new(), None), "Seasons can not overlap");
+            assert_ok!(KittiesModule::add_season(Origin::ROOT, 10, 12, Vec::new(), None));
+
+            // Not started yet
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::kitty(1).and_then(|kitty| kitty.season()), None);
+
+            system::Module::<Test>::set_block_number(3);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            for kitty_id in 2..4 {
+                let kitty = KittiesModule::kitty(kitty_id).unwrap();
+                assert_eq!(kitty.season(), Some(0));
+                assert!(kitty.dna[0] >= 200 && kitty.dna[0] <= 209);
+                assert_eq!(kitty.dna[15], 7);
+            }
+            assert_eq!(KittiesModule::season_supply(0), 2);
+
+            // The supply is reached
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::kitty(4).and_then(|kitty| kitty.season()), None);
+
+            system::Module::<Test>::set_block_number(11);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::kitty(5).and_then(|kitty| kitty.season()), Some(1));
+        });
+    }
+
+    #[test]
+    fn fuse_dna_weights_by_generation() {
+        assert_eq!(fuse_dna(&[10; 16], 0, &[20; 16], 0), [15; 16]);
+        assert_eq!(fuse_dna(&[0; 16], 0, &[255; 16], 2), [191; 16]);
+        assert_eq!(fuse_dna(&[255; 16], u32::max_value(), &[255; 16], u32::max_value()), [255; 16]);
+    }
+
+    #[test]
+    fn fuse_burns_both_kitties_into_a_new_one() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 12).kitty(1).kitty(1).kitty(2).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::FusionFee(5)));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::FusionCooldown(3)));
+
+            assert_noop!(KittiesModule::fuse(Origin::signed(1), 0, 0), "Needs different kitties");
+            assert_noop!(KittiesModule::fuse(Origin::signed(1), 0, 2), "You do not own kitty_id_2");
+
+            assert_ok!(KittiesModule::fuse(Origin::signed(1), 0, 1));
+            assert!(KittiesModule::kitty(0).is_none() && KittiesModule::kitty(1).is_none());
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![3]);
+            assert_eq!(KittiesModule::kitty(3).map(|kitty| kitty.generation()), Some(1));
+            assert_eq!(
+                HOOK_CALLS.with(|calls| calls.borrow().clone()),
+                vec![("created", 1, 3), ("burned", 1, 0), ("burned", 1, 1)]
+            );
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 7);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 7);
+            assert_eq!(KittiesModule::breeding_ready_at(3), 4);
+            assert_ok!(KittiesModule::do_try_state());
+        });
+    }
+
+    #[test]
+    fn created_kitties_are_revealed_after_the_delay() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            REVEAL_DELAY.with(|v| *v.borrow_mut() = 3);
+            system::Module::<Test>::set_block_number(1);
+
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::kitty(1).map(|kitty| kitty.dna()), Some([0; 16]));
+            assert!(KittiesModule::pending_reveal(1).is_some());
+            assert_eq!(KittiesModule::reveal_queue(4), vec![1]);
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), "This cat is not revealed yet");
+            assert_noop!(KittiesModule::fuse(Origin::signed(1), 1, 0), "This cat is not revealed yet");
+
+            KittiesModule::on_initialize(4);
+            assert!(KittiesModule::pending_reveal(1).is_none());
+            assert_ne!(KittiesModule::kitty(1).map(|kitty| kitty.dna()), Some([0; 16]));
+            assert_eq!(KittiesModule::changed_kitties(), vec![1]);
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+        });
+    }
+
+    #[test]
+    fn presale_admits_whitelisted_accounts_and_mint_pass_holders() {
+        with_externalities(&mut ExtBuilder::default().kitty(3).build(), || {
+            assert_noop!(KittiesModule::set_drop_phase(Origin::signed(1), DropPhase::Presale, Some(1)), "Invalid origin");
+            assert_ok!(KittiesModule::set_drop_phase(Origin::ROOT, DropPhase::Presale, Some(1)));
+            assert_eq!(KittiesModule::current_drop(), 1);
+            assert_ok!(KittiesModule::set_presale_whitelisted(Origin::ROOT, 1, true));
+            assert_ok!(KittiesModule::set_mint_pass(Origin::ROOT, 0, true));
+
+            assert_noop!(
+                KittiesModule::create(Origin::signed(2)),
+                "Only whitelisted accounts and mint-pass holders can buy in the presale"
+            );
+            assert_noop!(
+                KittiesModule::buy_gen0(Origin::signed(2), Some(0)),
+                "Only whitelisted accounts and mint-pass holders can buy in the presale"
+            );
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_noop!(KittiesModule::create(Origin::signed(1)), "The mint limit of this phase is reached");
+            assert_ok!(KittiesModule::buy_gen0(Origin::signed(3), Some(0)));
+
+            assert_ok!(KittiesModule::set_drop_phase(Origin::ROOT, DropPhase::Public, Some(2)));
+            assert_ok!(KittiesModule::create(Origin::signed(2)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_noop!(KittiesModule::create(Origin::signed(1)), "The mint limit of this phase is reached");
+
+            // A new presale starts the counters over
+            assert_ok!(KittiesModule::set_drop_phase(Origin::ROOT, DropPhase::Presale, Some(1)));
+            assert_eq!(KittiesModule::current_drop(), 2);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+        });
+    }
+
+    #[test]
+    fn mint_pass_tokens_are_redeemed_for_gen0_kitties() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_noop!(KittiesModule::issue_mint_passes(Origin::signed(1), 1, 2), "Invalid origin");
+            assert_ok!(KittiesModule::issue_mint_passes(Origin::ROOT, 1, 2));
+            assert_noop!(KittiesModule::transfer_mint_passes(Origin::signed(1), 2, 3), "Not enough mint-pass tokens");
+            assert_ok!(KittiesModule::transfer_mint_passes(Origin::signed(1), 2, 1));
+            assert_eq!((KittiesModule::mint_pass_tokens(1), KittiesModule::mint_pass_tokens(2)), (1, 1));
+
+            // Neither the presale nor a stale gen-0 price stop a pass
+            assert_ok!(KittiesModule::set_drop_phase(Origin::ROOT, DropPhase::Presale, Some(0)));
+            <Gen0PriceOracle<Test>>::put((10, 0));
+            system::Module::<Test>::set_block_number(1_000);
+            assert!(KittiesModule::create(Origin::signed(2)).is_err());
+
+            assert_ok!(KittiesModule::redeem_pass(Origin::signed(2)));
+            assert_eq!(KittiesModule::owned_kitty_ids(&2, 10), vec![0]);
+            assert_eq!(KittiesModule::mint_pass_tokens(2), 0);
+            assert_eq!(KittiesModule::gen0_minted(), 0);
+            assert_noop!(KittiesModule::redeem_pass(Origin::signed(2)), "Not enough mint-pass tokens");
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Minting, true));
+            assert_noop!(KittiesModule::redeem_pass(Origin::signed(1)), "This subsystem is paused");
+        });
+    }
+
+    #[test]
+    fn gen0_creation_is_rate_limited_per_block_and_per_era() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxMintsPerBlock(Some(2))));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxMintsPerEra(Some(2))));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MintEraLength(10)));
+
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_ok!(KittiesModule::create(Origin::signed(2)));
+            assert_noop!(KittiesModule::create(Origin::signed(3)), "Too many kitties created in this block");
+
+            KittiesModule::on_initialize(1);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_noop!(KittiesModule::create(Origin::signed(1)), "Too many kitties created by this account in this era");
+            assert_ok!(KittiesModule::issue_mint_passes(Origin::ROOT, 1, 1));
+            assert_noop!(KittiesModule::redeem_pass(Origin::signed(1)), "Too many kitties created by this account in this era");
+
+            KittiesModule::on_initialize(10);
+            assert_eq!(KittiesModule::mint_era(), 1);
+            assert_ok!(KittiesModule::redeem_pass(Origin::signed(1)));
+            assert_eq!(KittiesModule::era_mints(1), (1, 1));
+        });
+    }
+
+    #[test]
+    fn creation_follows_the_supply_curve_and_burns_refund_the_reserve() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::CreationBasePrice(10)));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::CreationPriceSlope(4)));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::CreationReserveShare(Permill::from_percent(50))));
+
+            assert_eq!(KittiesModule::creation_price(), 10);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::creation_price(), 14);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 76);
+            assert_eq!((KittiesModule::creation_reserve(), KittiesModule::reserve_backed_kitties()), (12, 2));
+            assert_eq!(balances::Module::<Test>::free_balance(&KittiesModule::creation_reserve_account()), 12);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 88);
+
+            assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 82);
+            assert_ok!(KittiesModule::burn(Origin::signed(1), 1));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 88);
+            assert_eq!((KittiesModule::creation_reserve(), KittiesModule::reserve_backed_kitties()), (0, 0));
+            assert_eq!(balances::Module::<Test>::total_issuance(), 88);
+
+            // Passes are free of the curve
+            assert_ok!(KittiesModule::issue_mint_passes(Origin::ROOT, 1, 1));
+            assert_ok!(KittiesModule::redeem_pass(Origin::signed(1)));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 88);
+        });
+    }
+
+    #[test]
+    fn buyback_spends_market_fees_on_cheap_listings() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketFee(Permill::from_percent(50))));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::BuybackFloor(10)));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 20));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 20));
+            assert_eq!(KittiesModule::market_treasury(), 10);
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 4));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 2, 8));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 3, 30));
+            assert_noop!(KittiesModule::buyback(Origin::signed(1), 10, 10), "Invalid origin");
+
+            // Looks at kitties 0 and 1 only
+            assert_ok!(KittiesModule::buyback(Origin::ROOT, 10, 2));
+            assert!(KittiesModule::kitty(1).is_none());
+            assert_eq!(KittiesModule::buyback_cursor(), Some(0));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 14);
+            assert_eq!(KittiesModule::market_treasury(), 6);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 100);
+
+            // Kitty 2 is over the budget left and kitty 3 over the floor
+            assert_ok!(KittiesModule::buyback(Origin::ROOT, 10, 10));
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![2, 3]);
+            assert_eq!(KittiesModule::buyback_cursor(), None);
+            assert_eq!(KittiesModule::market_treasury(), 6);
+            assert_ok!(KittiesModule::do_try_state());
+        });
+    }
+
+    #[test]
+    fn sales_donate_the_opted_in_share_to_the_charity() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 50));
+            assert_noop!(
+                KittiesModule::set_listing_donation(Origin::signed(1), 0, Some(Permill::from_percent(20))),
+                "No charity account is configured"
+            );
+            assert_ok!(KittiesModule::set_charity_account(Origin::ROOT, Some(9)));
+            assert_noop!(
+                KittiesModule::set_listing_donation(Origin::signed(1), 1, Some(Permill::from_percent(20))),
+                "This cat is not for sale"
+            );
+            assert_ok!(KittiesModule::set_listing_donation(Origin::signed(1), 0, Some(Permill::from_percent(20))));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketFee(Permill::from_percent(10))));
+
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 50));
+            // 45 after the fee, 9 of it donated
+            assert_eq!(balances::Module::<Test>::free_balance(&9), 9);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 36);
+            assert_eq!(KittiesModule::donated_by(1), 9);
+            assert_eq!(KittiesModule::top_donors(), vec![(1, 9)]);
+            assert_eq!(KittiesModule::achievements_of(1) & ACHIEVEMENT_FIRST_DONATION, ACHIEVEMENT_FIRST_DONATION);
+            assert_eq!(KittiesModule::listing_donation(0), None);
+
+            // Delisting drops the donation
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_ok!(KittiesModule::set_listing_donation(Origin::signed(1), 1, Some(Permill::from_percent(50))));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 0));
+            assert_eq!(KittiesModule::listing_donation(1), None);
+        });
+    }
+
+    #[test]
+    fn tips_go_to_the_current_owner() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).balance(3, 100).kitty(1).build(), || {
+            assert_noop!(KittiesModule::tip(Origin::signed(2), 0, 0), "The tip must be greater than zero");
+            assert_noop!(KittiesModule::tip(Origin::signed(2), 1, 5), "This cat does not exist");
+            assert_noop!(KittiesModule::tip(Origin::signed(1), 0, 5), "You can not tip your own cat");
+
+            assert_ok!(KittiesModule::tip(Origin::signed(2), 0, 5));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 3, 0));
+            assert_ok!(KittiesModule::tip(Origin::signed(2), 0, 7));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 5);
+            assert_eq!(balances::Module::<Test>::free_balance(&3), 107);
+            assert_eq!(KittiesModule::tips_of(0), 12);
+
+            let tipped = TestEvent::kitties(RawEvent::Tipped(2, 3, 0, 7));
+            let record = system::Module::<Test>::events().into_iter().find(|record| record.event == tipped).unwrap();
+            let hash_of_account = |account: u64| <Test as system::Trait>::Hashing::hash_of(&account);
+            assert_eq!(record.topics, vec![<Test as system::Trait>::Hashing::hash_of(&0u32), hash_of_account(2), hash_of_account(3)]);
+        });
+    }
+
+    #[test]
+    fn featured_listings_burn_a_fee_and_expire() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(1);
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::FeaturedListingFee(2)));
+            assert_noop!(KittiesModule::feature_listing(Origin::signed(1), 0, 5), "This cat is not for sale");
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_ok!(KittiesModule::feature_listing(Origin::signed(1), 0, 5));
+            assert_ok!(KittiesModule::feature_listing(Origin::signed(1), 1, 3));
+            // Extending goes on from the current end
+            assert_ok!(KittiesModule::feature_listing(Origin::signed(1), 0, 2));
+            assert_eq!(KittiesModule::featured_listings(), vec![(0, 8), (1, 4)]);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 80);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 80);
+
+            KittiesModule::on_initialize(4);
+            assert_eq!(KittiesModule::featured_listings(), vec![(0, 8)]);
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 0));
+            KittiesModule::on_initialize(5);
+            assert!(KittiesModule::featured_listings().is_empty());
+        });
+    }
+
+    #[test]
+    fn price_index_follows_listings() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).kitty(1).kitty(1).build(), || {
+            assert_eq!(KittiesModule::floor_price(), None);
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 10));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 2, 20));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 3, 10));
+            assert_eq!(KittiesModule::floor_price(), Some(10));
+            assert_eq!(KittiesModule::listed_prices(), vec![10, 20, 30]);
+            assert_eq!(KittiesModule::listed_price_ranges(), vec![4, 5]);
+            assert_eq!(KittiesModule::listed_prices_in_range(5), vec![20, 30]);
+            assert_eq!(KittiesModule::listings_under(20, 10), vec![(1, 10), (3, 10), (2, 20)]);
+            assert_eq!(KittiesModule::listings_under(20, 2), vec![(1, 10), (3, 10)]);
+
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 1, 10));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 3, 25));
+            assert_eq!(KittiesModule::floor_price(), Some(20));
+            assert_ok!(KittiesModule::set_kitty_blacklisted(Origin::ROOT, 2, true));
+            assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+            assert_eq!(KittiesModule::listings_under(100, 10), vec![(3, 25)]);
+            assert_ok!(KittiesModule::do_try_state());
+        });
+    }
+
+    #[test]
+    fn price_index_is_rebuilt_by_the_migration() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            <Kitties<Test>>::mutate(1, |kitty| kitty.as_mut().unwrap().price = 7);
+            assert!(KittiesModule::do_try_state().is_err());
+            <StorageVersion>::put(7);
+
+            KittiesModule::migrate_storage();
+
+            assert_eq!(KittiesModule::listings_under(100, 10), vec![(1, 7)]);
+            assert_ok!(KittiesModule::do_try_state());
+        });
+    }
+
+    #[test]
+    fn listed_prices_are_split_into_ranges_by_the_migration() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 3));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 300));
+            <ListedPriceRanges>::kill();
+            <ListedPricesInRange<Test>>::remove(2);
+            <ListedPricesInRange<Test>>::remove(9);
+            runtime_io::set_storage(&twox_128(b"Kitties ListedPrices"), &vec![3u64, 300].encode());
+            <StorageVersion>::put(11);
+
+            KittiesModule::migrate_storage();
+
+            assert_eq!(runtime_io::storage(&twox_128(b"Kitties ListedPrices")), None);
+            assert_eq!(KittiesModule::listed_price_ranges(), vec![2, 9]);
+            assert_eq!(KittiesModule::listings_under(300, 10), vec![(0, 3), (1, 300)]);
+            assert_ok!(KittiesModule::do_try_state());
+        });
+    }
+
+    #[test]
+    fn trait_indexes_follow_mints_breeds_and_burns() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            let eye_color_of = |kitty_id: u32| TraitKind::EyeColor.value_in(&KittiesModule::kitty(kitty_id).unwrap().dna);
+            let with_eye_color = |kitty_id: u32| KittiesModule::kitties_with_trait((TraitKind::EyeColor, eye_color_of(kitty_id)));
+
+            assert!(with_eye_color(0).contains(&0));
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert!(with_eye_color(2).contains(&2));
+            for kind in TraitKind::ALL.iter().cloned() {
+                let total: usize = (0..16).map(|value| KittiesModule::kitties_with_trait((kind, value)).len()).sum();
+                assert_eq!(total, 3);
+            }
+
+            let eye_color = eye_color_of(0);
+            assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+            assert!(!KittiesModule::kitties_with_trait((TraitKind::EyeColor, eye_color)).contains(&0));
+        });
+    }
+
+    #[test]
+    fn trait_indexes_move_on_reveal() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            REVEAL_DELAY.with(|v| *v.borrow_mut() = 1);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(KittiesModule::kitties_with_trait((TraitKind::Body, 0)), vec![0]);
+
+            KittiesModule::on_initialize(1 + MIN_REVEAL_DELAY as u64);
+            assert!(KittiesModule::pending_reveal(0).is_none());
+            let body = TraitKind::Body.value_in(&KittiesModule::kitty(0).unwrap().dna);
+            assert_eq!(KittiesModule::kitties_with_trait((TraitKind::Body, body)), vec![0]);
+            let total: usize = (0..16).map(|value| KittiesModule::kitties_with_trait((TraitKind::Body, value)).len()).sum();
+            assert_eq!(total, 1);
+        });
+    }
+
+    #[test]
+    fn kitties_are_counted_per_generation() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 2));
+            assert_eq!(
+                (0..3).map(KittiesModule::kitties_per_generation).collect::<Vec<_>>(),
+                vec![2, 1, 1]
+            );
+
+            assert_ok!(KittiesModule::burn(Origin::signed(1), 2));
+            assert_eq!(KittiesModule::kitties_per_generation(1), 0);
+
+            // The migration counts from scratch
+            <KittiesPerGeneration>::insert(0, 7);
+            <StorageVersion>::put(9);
+            KittiesModule::migrate_storage();
+            assert_eq!(
+                (0..3).map(KittiesModule::kitties_per_generation).collect::<Vec<_>>(),
+                vec![2, 0, 1]
+            );
+        });
+    }
+
+    #[test]
+    fn provenance_records_every_change_of_owner() {
+        with_externalities(&mut ExtBuilder::default().balance(3, 100).kitty(1).kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(2);
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 2));
+            system::Module::<Test>::set_block_number(3);
+            assert_ok!(KittiesModule::set_price(Origin::signed(2), 2, 10));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(3), 2, 10));
+
+            let acquisitions = KittiesModule::provenance_of(2).into_iter()
+                .map(|record| (record.owner, record.previous_owner, record.at, record.acquisition))
+                .collect::<Vec<_>>();
+            assert_eq!(acquisitions, vec![
+                (1, None, 2, Acquisition::Breed),
+                (2, Some(1), 2, Acquisition::Transfer),
+                (3, Some(2), 3, Acquisition::Sale),
+            ]);
+            assert_eq!(KittiesModule::provenance_of(0)[0].acquisition, Acquisition::Mint);
+        });
+    }
+
+    #[test]
+    fn provenance_keeps_the_origin_when_full() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            for round in 0..MAX_PROVENANCE_RECORDS as u64 {
+                let (from, to) = if round % 2 == 0 { (1, 2) } else { (2, 1) };
+                assert_ok!(KittiesModule::transfer(Origin::signed(from), to, 0));
+            }
+
+            let records = KittiesModule::provenance_of(0);
+            assert_eq!(records.len(), MAX_PROVENANCE_RECORDS);
+            assert_eq!(records[0].acquisition, Acquisition::Mint);
+            assert_eq!(records.last().map(|record| record.owner), Some(1));
+        });
+    }
+
+    #[test]
+    fn transfer_memos_go_into_the_event() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            assert_noop!(
+                KittiesModule::transfer_with_memo(Origin::signed(1), 2, 0, [0; MAX_MEMO_LENGTH + 1].to_vec()),
+                "The memo is too long"
+            );
+            assert_ok!(KittiesModule::transfer_with_memo(Origin::signed(1), 2, 0, b"deposit 42".to_vec()));
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+
+            let with_memo = TestEvent::kitties(RawEvent::TransferredWithMemo(1, 2, 0, b"deposit 42".to_vec()));
+            assert!(system::Module::<Test>::events().into_iter().any(|record| record.event == with_memo));
+        });
+    }
+
+    #[test]
+    fn kitties_move_between_chains_through_exports() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            let kitty = KittiesModule::kitty(2).unwrap();
+            assert_noop!(KittiesModule::export_kitty(Origin::signed(1), 2, Vec::new()), "Invalid target chain account");
+            assert_noop!(KittiesModule::export_kitty(Origin::signed(2), 2, 5u64.encode()), "You do not own this cat");
+
+            assert_ok!(KittiesModule::export_kitty(Origin::signed(1), 2, 5u64.encode()));
+            assert_eq!(KittiesModule::owner_of(2), None);
+            let export = KittyExport { export_id: 0, dna: kitty.dna, generation: 1, recipient: 5u64.encode() };
+            assert_eq!(KittiesModule::export_commitment(0), Some(<Test as system::Trait>::Hashing::hash_of(&export)));
+
+            // The same module on the other chain
+            assert_noop!(KittiesModule::import_kitty(Origin::signed(1), 7, export.clone()), "Invalid origin");
+            assert_ok!(KittiesModule::import_kitty(Origin::ROOT, 7, export.clone()));
+            let imported = KittiesModule::kitty(3).unwrap();
+            assert_eq!((imported.dna, imported.generation), (kitty.dna, 1));
+            assert_eq!(KittiesModule::owner_of(3), Some(5));
+            assert_noop!(KittiesModule::import_kitty(Origin::ROOT, 7, export), "This export is already imported");
+            assert_ok!(KittiesModule::do_try_state());
+        });
+    }
+
+    #[test]
+    fn owner_storage_key_points_to_the_owner() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(2).build(), || {
+            let key = KittiesModule::owner_storage_key(1);
+            assert_eq!(runtime_io::storage(&key).map(|owner| u64::decode(&mut &owner[..]).unwrap()), Some(2));
+            assert_eq!(runtime_io::storage(&KittiesModule::owner_storage_key(2)), None);
+        });
+    }
+
+    #[test]
+    fn kitties_page_walks_every_kitty_once() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(2).kitty(1).build(), || {
+            assert_ok!(KittiesModule::transfer(Origin::signed(2), 3, 1));
+            assert_eq!(KittiesModule::kitties_page(None, 2), (vec![0, 1], Some(1)));
+            assert_eq!(KittiesModule::kitties_page(Some(1), 2), (vec![2], None));
+            assert_eq!(KittiesModule::kitties_page(None, 10), (vec![0, 1, 2], None));
+        });
+    }
+
+    #[test]
+    fn art_descriptor_derives_from_dna() {
+        let mut dna = [0u8; 16];
+        dna[..7].copy_from_slice(&[0x12, 0xf3, 0x04, 0x25, 31, 32, 70]);
+        assert_eq!(ArtDescriptor::from_dna(&dna), ArtDescriptor { parts: [2, 3, 4, 5], palette: [31, 0, 6] });
+
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            let kitty = KittiesModule::kitty(0).unwrap();
+            assert_eq!(KittiesModule::art_of(0), Some(ArtDescriptor::from_dna(&kitty.dna)));
+            assert_eq!(KittiesModule::art_of(1), None);
+        });
+    }
+
+    #[test]
+    fn kitty_names_are_unique() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).kitty(1).kitty(2).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::NamingFee(5)));
+            assert_noop!(KittiesModule::set_name(Origin::signed(2), 0, b"Tom".to_vec()), "You do not own this cat");
+            assert_noop!(KittiesModule::set_name(Origin::signed(1), 0, [b'a'; DEFAULT_MAX_NAME_LENGTH as usize + 1].to_vec()), "The name is too long");
+
+            assert_ok!(KittiesModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 95);
+            assert_eq!(KittiesModule::kitty_by_name(b"Tom".to_vec()), Some(0));
+            assert_noop!(KittiesModule::set_name(Origin::signed(2), 1, b"Tom".to_vec()), "This name is taken");
+            assert_ok!(KittiesModule::set_name(Origin::signed(1), 0, b"Tom".to_vec()));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 95);
+
+            // Renaming frees the old name
+            assert_ok!(KittiesModule::set_name(Origin::signed(1), 0, b"Thomas".to_vec()));
+            assert_eq!(KittiesModule::kitty_by_name(b"Tom".to_vec()), None);
+            assert_ok!(KittiesModule::set_name(Origin::signed(1), 0, Vec::new()));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 90);
+            assert_eq!((KittiesModule::name_of(0), KittiesModule::kitty_by_name(b"Thomas".to_vec())), (None, None));
+        });
+    }
+
+    #[test]
+    fn names_and_metadata_are_validated() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            assert_noop!(KittiesModule::set_name(Origin::signed(1), 0, vec![0xff, 0xfe]), "The name is not valid UTF-8");
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxNameLength(4)));
+            assert_noop!(KittiesModule::set_name(Origin::signed(1), 0, b"Felix".to_vec()), "The name is too long");
+
+            assert_noop!(KittiesModule::set_banned_name_prefixes(Origin::signed(1), vec![b"adm".to_vec()]), "Invalid origin");
+            assert_noop!(KittiesModule::set_banned_name_prefixes(Origin::ROOT, vec![Vec::new()]), "Invalid banned prefix");
+            assert_ok!(KittiesModule::set_banned_name_prefixes(Origin::ROOT, vec![b"adm".to_vec()]));
+            assert_noop!(KittiesModule::set_name(Origin::signed(1), 0, b"Admn".to_vec()), "The name starts with a banned prefix");
+            assert_ok!(KittiesModule::set_name(Origin::signed(1), 0, b"Ad".to_vec()));
+
+            assert_noop!(KittiesModule::set_metadata(Origin::signed(1), 0, vec![0xc3]), "The metadata is not valid UTF-8");
+            assert_noop!(
+                KittiesModule::set_metadata(Origin::signed(1), 0, [b'a'; DEFAULT_MAX_METADATA_LENGTH as usize + 1].to_vec()),
+                "The metadata is too long"
+            );
+            assert_ok!(KittiesModule::set_metadata(Origin::signed(1), 0, "très chat".as_bytes().to_vec()));
+            assert_eq!(KittiesModule::metadata_of(0), Some("très chat".as_bytes().to_vec()));
+            assert_ok!(KittiesModule::set_metadata(Origin::signed(1), 0, Vec::new()));
+            assert_eq!(KittiesModule::metadata_of(0), None);
+        });
+    }
+
+    #[test]
+    fn vote_weight_counts_kitties_and_rare_traits() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(2).build(), || {
+            // Fewer than `RARE_TRAIT_DIVISOR` kitties, so no trait is rare
+            assert_eq!(KittiesModule::compute_vote_weight(&1), 2);
+            assert_eq!(<KittiesModule as VoteWeight<u64>>::vote_weight(&1), 0);
+
+            // Only the account itself snapshots its weight
+            assert_ok!(KittiesModule::snapshot_vote_weight(Origin::signed(1)));
+            assert_eq!(<KittiesModule as VoteWeight<u64>>::vote_weight(&1), 2);
+            assert_eq!(<KittiesModule as VoteWeight<u64>>::vote_weight(&3), 0);
+
+            // Moving a kitty away drops the snapshot, so it can't be counted twice
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_eq!(<KittiesModule as VoteWeight<u64>>::vote_weight(&1), 0);
+            assert_ok!(KittiesModule::snapshot_vote_weight(Origin::signed(1)));
+            assert_eq!(<KittiesModule as VoteWeight<u64>>::vote_weight(&1), 1);
+        });
+    }
+
+    #[test]
+    fn rare_traits_are_counted_past_the_trait_buckets() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            let kitty = KittiesModule::kitty(0).unwrap();
+            let traits = KittiesModule::traits_of(&kitty);
+            assert!(traits.iter().all(|key| KittiesModule::kitties_per_trait(key) == 1));
+
+            // Two kitties with a trait value are rare among 64 kitty ids
+            <KittiesCount<Test>>::put(64);
+            assert_eq!(KittiesModule::compute_vote_weight(&1), 1 + TraitKind::ALL.len() as u64);
+
+            // More kitties have the trait than its bucket holds, so it isn't rare
+            <KittiesPerTrait>::insert(traits[0], MAX_TRAIT_BUCKET_SIZE as u64 + 1);
+            assert_eq!(KittiesModule::kitties_with_trait(traits[0]).len(), 1);
+            assert_eq!(KittiesModule::compute_vote_weight(&1), TraitKind::ALL.len() as u64);
+
+            // The migration counts from scratch
+            <StorageVersion>::put(12);
+            KittiesModule::migrate_storage();
+            assert_eq!(KittiesModule::kitties_per_trait(traits[0]), 1);
+
+            assert_ok!(KittiesModule::burn(Origin::signed(1), 0));
+            assert!(traits.iter().all(|key| KittiesModule::kitties_per_trait(key) == 0));
+        });
+    }
+
+    #[test]
+    fn breed_batch_reports_each_pair() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(1).kitty(1).build(), || {
+            assert_noop!(KittiesModule::breed_batch(Origin::signed(1), Vec::new()), "The batch is empty");
+            assert_noop!(KittiesModule::breed_batch(Origin::signed(1), vec![(0, 1); 4]), "Too many pairs in the batch");
+            // Weighed by the pairs in the batch, up to the largest batch the runtime allows
+            assert_eq!(Call::<Test>::breed_batch(vec![(0, 1)]).get_dispatch_info().weight, 80_000);
+            assert_eq!(Call::<Test>::breed_batch(vec![(0, 1); 4]).get_dispatch_info().weight, 3 * 80_000);
+
+            assert_ok!(KittiesModule::breed_batch(Origin::signed(1), vec![(0, 1), (2, 2), (2, 3)]));
+            assert_eq!(KittiesModule::owned_kitties_count(&1), 6);
+
+            let reports: Vec<_> = system::Module::<Test>::events().into_iter()
+                .filter_map(|record| match record.event {
+                    TestEvent::kitties(RawEvent::BredInBatch(1, id1, id2, child)) => Some((id1, id2, Ok(child))),
+                    TestEvent::kitties(RawEvent::BreedInBatchFailed(1, id1, id2, reason)) => Some((id1, id2, Err(reason))),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(reports, vec![(0, 1, Ok(4)), (2, 2, Err(b"Needs different parent".to_vec())), (2, 3, Ok(5))]);
+        });
+    }
+
+    #[test]
+    fn boost_cooldown_charges_for_the_blocks_left() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::BreedingCooldown(10)));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::CooldownBoostFee(2)));
+            assert_noop!(KittiesModule::boost_cooldown(Origin::signed(1), 0), "This cat is not cooling down");
+
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            system::Module::<Test>::set_block_number(4);
+            assert_noop!(KittiesModule::boost_cooldown(Origin::signed(2), 0), "You do not own this cat");
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Breeding, true));
+            assert_noop!(KittiesModule::boost_cooldown(Origin::signed(1), 0), "This subsystem is paused");
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Breeding, false));
+            assert_ok!(KittiesModule::set_kitty_blacklisted(Origin::ROOT, 1, true));
+            assert_noop!(KittiesModule::boost_cooldown(Origin::signed(1), 1), "This cat is blacklisted");
+            assert_ok!(KittiesModule::boost_cooldown(Origin::signed(1), 0));
+            // Bred at block 1 so ready at 11, 7 blocks left
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 86);
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 2));
+        });
+    }
+
+    #[test]
+    fn set_price_many_prices_all_or_nothing() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(2).build(), || {
+            assert_noop!(KittiesModule::set_price_many(Origin::signed(1), Vec::new()), "No kitties to price");
+            assert_noop!(KittiesModule::set_price_many(Origin::signed(1), vec![(0, 5); 4]), "Too many kitties to price");
+            assert_eq!(Call::<Test>::set_price_many(vec![(0, 5); 2]).get_dispatch_info().weight, 2 * 50_000);
+            assert_eq!(Call::<Test>::set_price_many(vec![(0, 5); 4]).get_dispatch_info().weight, 3 * 50_000);
+            assert_noop!(KittiesModule::set_price_many(Origin::signed(1), vec![(0, 5), (2, 5)]), "You do not own this cat");
+            assert_eq!(KittiesModule::kitty(0).unwrap().price, 0);
+
+            assert_ok!(KittiesModule::set_price_many(Origin::signed(1), vec![(0, 5), (1, 7)]));
+            assert_eq!(KittiesModule::market_listings_page(None, 10), (vec![(0, 5), (1, 7)], None));
+            assert_eq!(KittiesModule::floor_price(), Some(5));
+        });
+    }
+
+    #[test]
+    fn decaying_listings_sell_at_the_current_price() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            assert_noop!(KittiesModule::set_decaying_price(Origin::signed(1), 0, 10, 0, 11), "The price must fall to a non-zero price");
+            assert_noop!(KittiesModule::set_decaying_price(Origin::signed(1), 0, 50, 10, 1), "The decay must end in the future");
+            assert_ok!(KittiesModule::set_decaying_price(Origin::signed(1), 0, 50, 10, 11));
+
+            system::Module::<Test>::set_block_number(6);
+            assert_eq!(KittiesModule::current_price(0, 50), 30);
+            assert_noop!(
+                KittiesModule::buy_kitty(Origin::signed(2), 0, 29),
+                "The cat you want to buy costs more than your max price"
+            );
+
+            system::Module::<Test>::set_block_number(20);
+            assert_eq!(KittiesModule::current_price(0, 50), 10);
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 10));
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 90);
+            assert_eq!(KittiesModule::price_decay(0), None);
+        });
+    }
+
+    #[test]
+    fn repricing_drops_the_decay() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_decaying_price(Origin::signed(1), 0, 50, 10, 11));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 40));
+            system::Module::<Test>::set_block_number(11);
+            assert_eq!(KittiesModule::current_price(0, 40), 40);
+        });
+    }
+
+    #[test]
+    fn royalties_are_split_on_sales() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 1000).kitty(1).build(), || {
+            let splits = vec![(5, 600), (6, 400)];
+            assert_noop!(
+                KittiesModule::set_royalty(Origin::signed(2), 0, Permill::from_percent(5), splits.clone()),
+                "Only the creator of this cat can set its royalty"
+            );
+            assert_noop!(
+                KittiesModule::set_royalty(Origin::signed(1), 0, Permill::from_percent(11), splits.clone()),
+                "The royalty rate is too high"
+            );
+            assert_noop!(
+                KittiesModule::set_royalty(Origin::signed(1), 0, Permill::from_percent(5), vec![(5, 600), (6, 300)]),
+                "The royalty shares must sum to 1000"
+            );
+            // Would wrap around to 1000 in a `u32` sum
+            assert_noop!(
+                KittiesModule::set_royalty(Origin::signed(1), 0, Permill::from_percent(5), vec![(5, u32::max_value()), (6, 1001)]),
+                "A royalty share is over 1000"
+            );
+            assert_ok!(KittiesModule::set_royalty(Origin::signed(1), 0, Permill::from_percent(10), splits));
+
+            // The creator's first sale pays royalties to the beneficiaries too
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 500));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 500));
+            let balance = |who| balances::Module::<Test>::free_balance(&who);
+            assert_eq!((balance(1), balance(5), balance(6)), (450, 30, 20));
+
+            // The royalty follows the kitty, only its creator changes it
+            assert_noop!(
+                KittiesModule::set_royalty(Origin::signed(2), 0, Permill::zero(), Vec::new()),
+                "Only the creator of this cat can set its royalty"
+            );
+        });
+    }
+
+    #[test]
+    fn sales_burn_part_of_the_price() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 1000).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketFee(Permill::from_percent(10))));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::SaleBurnRate(Permill::from_percent(2))));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 500));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 500));
+
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 440);
+            assert_eq!(KittiesModule::market_treasury(), 50);
+            assert_eq!(KittiesModule::total_sale_burned(), 10);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 990);
+        });
+    }
+
+    #[test]
+    fn loyalty_points_waive_market_fees() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).balance(2, 1000).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketFee(Permill::from_percent(10))));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 100));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 100));
+            assert_eq!((KittiesModule::loyalty_points(&1), KittiesModule::loyalty_points(&2)), (10, 10));
+
+            assert_noop!(KittiesModule::redeem_loyalty_points(Origin::signed(2), 5), "Loyalty points can not be redeemed");
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::LoyaltyPointValue(3)));
+            assert_noop!(KittiesModule::redeem_loyalty_points(Origin::signed(2), 11), "Not enough loyalty points");
+            assert_ok!(KittiesModule::redeem_loyalty_points(Origin::signed(2), 5));
+            assert_eq!((KittiesModule::loyalty_points(&2), KittiesModule::market_fee_credit(&2)), (5, 15));
+
+            // The fee of 10 is waived, 5 credit is left for the next sale
+            assert_ok!(KittiesModule::set_price(Origin::signed(2), 0, 100));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(1), 0, 100));
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 1000);
+            assert_eq!(KittiesModule::market_fee_credit(&2), 5);
+        });
+    }
+
+    #[test]
+    fn vouchers_buy_the_cheapest_listing() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).kitty(2).kitty(2).kitty(3).build(), || {
+            let code_hash = <Test as system::Trait>::Hashing::hash(b"happy birthday");
+            assert_ok!(KittiesModule::buy_voucher(Origin::signed(1), code_hash, 40, 10));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 40);
+            assert_noop!(KittiesModule::buy_voucher(Origin::signed(1), code_hash, 10, 10), "This voucher code is taken");
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(2), 0, 50));
+            assert_ok!(KittiesModule::set_price(Origin::signed(2), 1, 30));
+            // The cheapest listing belongs to the recipient, so the next one is bought
+            assert_ok!(KittiesModule::set_price(Origin::signed(3), 2, 20));
+
+            assert_noop!(KittiesModule::redeem_voucher(Origin::signed(3), b"happy".to_vec()), "No such voucher");
+            let claim_hash = KittiesModule::voucher_claim_hash(b"happy birthday", &3);
+            assert_ok!(KittiesModule::commit_voucher_claim(Origin::signed(3), claim_hash));
+            assert_noop!(
+                KittiesModule::redeem_voucher(Origin::signed(3), b"happy birthday".to_vec()),
+                "The voucher claim must be committed in an earlier block"
+            );
+
+            // Whoever sees the code in the pool has no commitment of its own
+            system::Module::<Test>::set_block_number(2);
+            assert_noop!(KittiesModule::redeem_voucher(Origin::signed(2), b"happy birthday".to_vec()), "No voucher claim is committed");
+            assert_ok!(KittiesModule::commit_voucher_claim(Origin::signed(2), claim_hash));
+            assert_noop!(
+                KittiesModule::redeem_voucher(Origin::signed(2), b"happy birthday".to_vec()),
+                "The voucher claim is for another code"
+            );
+            assert_ok!(KittiesModule::redeem_voucher(Origin::signed(3), b"happy birthday".to_vec()));
+            assert_eq!(KittiesModule::owner_of(1), Some(3));
+            assert_eq!(balances::Module::<Test>::free_balance(&3), 10);
+            assert_eq!(balances::Module::<Test>::total_balance(&1), 60);
+            assert_eq!(KittiesModule::voucher(code_hash), None);
+            assert_eq!(KittiesModule::voucher_claim(3), None);
+        });
+    }
+
+    #[test]
+    fn vouchers_report_a_failed_purchase() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).build(), || {
+            let code_hash = <Test as system::Trait>::Hashing::hash(b"gift");
+            assert_ok!(KittiesModule::buy_voucher(Origin::signed(1), code_hash, 40, 10));
+            assert_ok!(KittiesModule::commit_voucher_claim(Origin::signed(2), KittiesModule::voucher_claim_hash(b"gift", &2)));
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Minting, true));
+
+            system::Module::<Test>::set_block_number(2);
+            assert_ok!(KittiesModule::redeem_voucher(Origin::signed(2), b"gift".to_vec()));
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 40);
+            let failed = TestEvent::kitties(RawEvent::VoucherPurchaseFailed(2, code_hash, b"This subsystem is paused".to_vec()));
+            assert!(system::Module::<Test>::events().into_iter().any(|record| record.event == failed));
+        });
+    }
+
+    #[test]
+    fn expired_vouchers_are_refunded() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).build(), || {
+            let code_hash = <Test as system::Trait>::Hashing::hash(b"gift");
+            assert_ok!(KittiesModule::buy_voucher(Origin::signed(1), code_hash, 40, 10));
+            assert_noop!(KittiesModule::refund_voucher(Origin::signed(2), code_hash), "The voucher has not expired");
+
+            system::Module::<Test>::set_block_number(11);
+            assert_noop!(KittiesModule::redeem_voucher(Origin::signed(2), b"gift".to_vec()), "The voucher has expired");
+            assert_ok!(KittiesModule::refund_voucher(Origin::signed(2), code_hash));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 100);
+        });
+    }
+
+    #[test]
+    fn otc_trades_execute_on_both_confirmations() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).kitty(2).build(), || {
+            assert_noop!(KittiesModule::open_trade(Origin::signed(1), 1), "You can't trade with yourself");
+            assert_ok!(KittiesModule::open_trade(Origin::signed(1), 2));
+            assert_noop!(KittiesModule::set_trade_offer(Origin::signed(3), 0, Vec::new(), 0), "You are not part of this trade");
+            assert_noop!(KittiesModule::set_trade_offer(Origin::signed(1), 0, vec![2], 0), "You do not own this cat");
+            assert_noop!(KittiesModule::set_trade_offer(Origin::signed(1), 0, vec![0, 0], 0), "A kitty is offered twice");
+
+            assert_ok!(KittiesModule::set_trade_offer(Origin::signed(1), 0, vec![0, 1], 0));
+            assert_ok!(KittiesModule::set_trade_offer(Origin::signed(2), 0, vec![2], 40));
+            assert_ok!(KittiesModule::confirm_trade(Origin::signed(2), 0));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 40);
+
+            // A new offer resets the confirmations
+            assert_ok!(KittiesModule::set_trade_offer(Origin::signed(2), 0, vec![2], 30));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&2), 0);
+            assert_ok!(KittiesModule::confirm_trade(Origin::signed(1), 0));
+            assert_noop!(KittiesModule::confirm_trade(Origin::signed(1), 0), "You already confirmed this trade");
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+
+            assert_ok!(KittiesModule::confirm_trade(Origin::signed(2), 0));
+            assert_eq!(KittiesModule::owned_kitty_ids(&2, 10), vec![0, 1]);
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![2]);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 30);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 70);
+            assert_eq!(KittiesModule::otc_trade(0), None);
+        });
+    }
+
+    #[test]
+    fn otc_trades_can_be_cancelled() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            assert_ok!(KittiesModule::open_trade(Origin::signed(1), 2));
+            assert_ok!(KittiesModule::set_trade_offer(Origin::signed(2), 0, Vec::new(), 50));
+            assert_ok!(KittiesModule::confirm_trade(Origin::signed(2), 0));
+            assert_ok!(KittiesModule::cancel_trade(Origin::signed(1), 0));
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 100);
+            assert_noop!(KittiesModule::confirm_trade(Origin::signed(1), 0), "No such trade");
+        });
+    }
+
+    #[test]
+    fn otc_trades_follow_the_market_rules() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).balance(2, 1000).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::TradeDeposit(10)));
+            assert_ok!(KittiesModule::set_account_barred(Origin::ROOT, 3, true));
+            assert_noop!(KittiesModule::open_trade(Origin::signed(1), 3), "This account is barred from the marketplace");
+            assert_ok!(KittiesModule::open_trade(Origin::signed(1), 2));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 10);
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 50));
+            assert_noop!(KittiesModule::set_trade_offer(Origin::signed(1), 0, vec![1], 0), "This cat is listed for sale");
+            assert_ok!(KittiesModule::set_kitty_blacklisted(Origin::ROOT, 0, true));
+            assert_noop!(KittiesModule::set_trade_offer(Origin::signed(1), 0, vec![0], 0), "This cat is blacklisted");
+            assert_ok!(KittiesModule::set_kitty_blacklisted(Origin::ROOT, 0, false));
+
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketFee(Permill::from_percent(10))));
+            assert_ok!(KittiesModule::set_royalty(Origin::signed(1), 0, Permill::from_percent(10), vec![(5, 1000)]));
+            assert_ok!(KittiesModule::set_trade_offer(Origin::signed(1), 0, vec![0], 0));
+            assert_ok!(KittiesModule::set_trade_offer(Origin::signed(2), 0, Vec::new(), 500));
+            assert_ok!(KittiesModule::confirm_trade(Origin::signed(2), 0));
+            // A kitty listed after it was offered keeps the trade from executing
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 50));
+            assert_noop!(KittiesModule::confirm_trade(Origin::signed(1), 0), "This cat is listed for sale");
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 0));
+            assert_ok!(KittiesModule::confirm_trade(Origin::signed(1), 0));
+
+            // The balance paid for the kitty pays the market fee and the royalty like a sale,
+            // counts for the market era and the deposit is back
+            let balance = |who| balances::Module::<Test>::free_balance(&who);
+            assert_eq!((balance(1), balance(2), balance(5)), (100 + 405, 500, 45));
+            assert_eq!(balance(KittiesModule::treasury_account()), 50);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 0);
+            assert_eq!(KittiesModule::market_stats().volume, 500);
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+
+            assert_ok!(KittiesModule::open_trade(Origin::signed(1), 2));
+            assert_eq!(KittiesModule::trade_deposit_of(1), 10);
+            assert_ok!(KittiesModule::cancel_trade(Origin::signed(2), 1));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 0);
+            assert_eq!(KittiesModule::trade_deposit_of(1), 0);
+        });
+    }
+
+    #[test]
+    fn kitties_wrap_into_index_tokens() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_noop!(KittiesModule::wrap_kitty(Origin::signed(2), 0), "You do not own this cat");
+            assert_ok!(KittiesModule::wrap_kitty(Origin::signed(1), 0));
+            assert_ok!(KittiesModule::wrap_kitty(Origin::signed(1), 1));
+            assert_eq!(KittiesModule::owner_of(0), Some(999));
+            assert_eq!(KittiesModule::kitty(0).unwrap().price, 0);
+            assert_eq!((KittiesModule::index_tokens(&1), KittiesModule::index_token_supply()), (2, 2));
+
+            assert_noop!(KittiesModule::unwrap_kitty(Origin::signed(2), 1), "Not enough index tokens");
+            assert_ok!(KittiesModule::transfer_index_tokens(Origin::signed(1), 2, 1));
+            assert_ok!(KittiesModule::unwrap_kitty(Origin::signed(2), 1));
+            assert_eq!(KittiesModule::owner_of(1), Some(2));
+            assert_noop!(KittiesModule::unwrap_kitty(Origin::signed(1), 1), "This cat is not in the index pool");
+            assert_eq!((KittiesModule::index_tokens(&2), KittiesModule::index_token_supply()), (0, 1));
+        });
+    }
+
+    #[test]
+    fn cooldowns_can_count_milliseconds() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).kitty(1).build(), || {
+            TIMESTAMP_COOLDOWNS.with(|v| *v.borrow_mut() = true);
+            NOW.with(|v| *v.borrow_mut() = 60_000);
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::BreedingCooldown(30_000)));
+
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 1));
+            assert_eq!(KittiesModule::breeding_ready_at(0), 90_000);
+
+            // Blocks don't count, time does
+            system::Module::<Test>::set_block_number(100_000);
+            NOW.with(|v| *v.borrow_mut() = 89_999);
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 2), "kitty_id_1 is cooling down");
+            NOW.with(|v| *v.borrow_mut() = 90_000);
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 0, 2));
+
+            TIMESTAMP_COOLDOWNS.with(|v| *v.borrow_mut() = false);
+            NOW.with(|v| *v.borrow_mut() = 0);
+        });
+    }
+
+    #[test]
+    fn cleanup_removes_entries_of_missing_kitties() {
+        with_externalities(&mut ExtBuilder::default().balance(3, 100).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::CleanupReward(5)));
+            assert_ok!(KittiesModule::fund_maintenance_pot(Origin::signed(3), 8));
+            assert_eq!(KittiesModule::maintenance_pot(), 8);
+
+            assert_noop!(KittiesModule::cleanup(Origin::signed(2), 1), "This cat exists");
+            assert_noop!(KittiesModule::cleanup(Origin::signed(2), 5), "Nothing to clean up");
+
+            // A kitty removed without its owner entries
+            <Kitties<Test>>::remove(1);
+            assert_ok!(KittiesModule::cleanup(Origin::signed(2), 1));
+
+            assert_eq!(KittiesModule::owner_of(1), None);
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![0]);
+            assert_eq!(KittiesModule::owned_kitties_count(&1), 1);
+            // Two entries are removed, the pot only pays 8
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 8);
+            assert_eq!(KittiesModule::maintenance_pot(), 0);
+            assert_eq!(balances::Module::<Test>::total_issuance(), 100);
+            assert_eq!(KittiesModule::do_try_state(), Ok(()));
+            assert_noop!(KittiesModule::cleanup(Origin::signed(2), 1), "Nothing to clean up");
+        });
+    }
+
+    #[test]
+    fn cleanup_account_removes_kitties_of_other_accounts() {
+        with_externalities(&mut ExtBuilder::default().balance(3, 100).kitty(1).kitty(2).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::CleanupReward(2)));
+            assert_ok!(KittiesModule::fund_maintenance_pot(Origin::signed(3), 50));
+            assert_noop!(KittiesModule::cleanup_account(Origin::signed(3), 1), "Nothing to clean up");
+
+            OwnedKittiesList::<Test>::append(&1, 1);
+            OwnedKittiesList::<Test>::append(&1, 7);
+            <OwnedKittiesCount<Test>>::insert(1, 5);
+            assert_ok!(KittiesModule::cleanup_account(Origin::signed(3), 1));
+
+            assert_eq!(KittiesModule::owned_kitty_ids(&1, 10), vec![0]);
+            assert_eq!(KittiesModule::owned_kitties_count(&1), 1);
+            assert_eq!(KittiesModule::maintenance_pot(), 44);
+            assert_eq!(KittiesModule::do_try_state(), Ok(()));
+        });
+    }
+
+    #[test]
+    fn collect_garbage_settles_finished_auctions() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_decaying_price(Origin::signed(1), 0, 50, 10, 11));
+            assert_ok!(KittiesModule::set_decaying_price(Origin::signed(1), 1, 50, 20, 21));
+            let call = Call::collect_garbage(vec![0, 1]);
+            assert!(KittiesModule::validate_unsigned(&call).is_err());
+
+            system::Module::<Test>::set_block_number(11);
+            assert!(KittiesModule::validate_unsigned(&call).is_err());
+            assert!(KittiesModule::validate_unsigned(&Call::collect_garbage(vec![0])).is_ok());
+            assert!(KittiesModule::validate_unsigned(&Call::collect_garbage(Vec::new())).is_err());
+
+            assert_noop!(KittiesModule::collect_garbage(Origin::signed(1), vec![0]), "bad origin: expected to be no origin");
+            assert_ok!(KittiesModule::collect_garbage(system::RawOrigin::None.into(), vec![0, 1]));
+
+            assert_eq!(KittiesModule::price_decay(0), None);
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(10));
+            assert_eq!(KittiesModule::listings_at_price(10), vec![0]);
+            // Still running, it is left alone
+            assert!(KittiesModule::price_decay(1).is_some());
+            assert_eq!(KittiesModule::do_try_state(), Ok(()));
+        });
+    }
+
+    #[test]
+    fn privileged_calls_are_audited() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            system::Module::<Test>::set_block_number(3);
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Market, true));
+            assert_ok!(KittiesModule::set_kitty_blacklisted(Origin::ROOT, 0, true));
+            assert_noop!(KittiesModule::set_kitty_blacklisted(Origin::signed(1), 0, false), "Invalid origin");
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::NamingFee(4)));
+
+            let log = KittiesModule::audit_log(10);
+            assert_eq!(log.len(), 3);
+            assert_eq!(log[0], AuditEntry {
+                caller: None,
+                call: Call::<Test>::set_parameter(KittiesParameter::NamingFee(4)).encode(),
+                at: 3,
+            });
+            assert_eq!(log[2].call, Call::<Test>::set_paused(Subsystem::Market, true).encode());
+            assert_eq!(KittiesModule::audit_log(1), vec![log[0].clone()]);
+
+            // A call failing after the origin check is not recorded
+            assert_noop!(KittiesModule::set_mint_pass(Origin::ROOT, 7, true), "This cat does not exist");
+            assert_eq!(KittiesModule::audit_log_count(), 3);
+        });
+    }
+
+    #[test]
+    fn audit_log_keeps_the_latest_calls() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            for count in 0..AUDIT_LOG_SIZE + 5 {
+                assert_ok!(KittiesModule::issue_mint_passes(Origin::ROOT, 1, count));
+            }
+
+            let log = KittiesModule::audit_log(AUDIT_LOG_SIZE + 5);
+            assert_eq!(log.len(), AUDIT_LOG_SIZE as usize);
+            assert_eq!(log[0].call, Call::<Test>::issue_mint_passes(1, AUDIT_LOG_SIZE + 4).encode());
+            assert_eq!(log[AUDIT_LOG_SIZE as usize - 1].call, Call::<Test>::issue_mint_passes(1, 5).encode());
+        });
+    }
+
+    #[test]
+    fn species_are_registered_by_governance() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_noop!(KittiesModule::register_species(Origin::signed(1), b"Puppy".to_vec(), 8, [0, 1, 2, 3]), "Invalid origin");
+            assert_noop!(KittiesModule::register_species(Origin::ROOT, Vec::new(), 8, [0, 1, 2, 3]), "Invalid species name");
+            assert_noop!(KittiesModule::register_species(Origin::ROOT, b"Puppy".to_vec(), 17, [0, 1, 2, 3]), "The DNA has 1 to 16 bytes");
+            assert_noop!(KittiesModule::register_species(Origin::ROOT, b"Puppy".to_vec(), 8, [0, 1, 2, 8]), "A trait is read past the DNA");
+
+            assert_ok!(KittiesModule::register_species(Origin::ROOT, b"Puppy".to_vec(), 8, [7, 6, 5, 4]));
+            assert_eq!(KittiesModule::species_info(1), Some(Species { name: b"Puppy".to_vec(), dna_length: 8, trait_bytes: [7, 6, 5, 4] }));
+            assert_eq!(KittiesModule::species_info(KITTY_SPECIES), Some(Species::kitties()));
+            assert_eq!(KittiesModule::species_info(2), None);
+        });
+    }
+
+    #[test]
+    fn creatures_carry_their_species() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            assert_ok!(KittiesModule::register_species(Origin::ROOT, b"Puppy".to_vec(), 8, [7, 6, 5, 4]));
+            assert_noop!(KittiesModule::create_creature(Origin::signed(1), 2), "No such species");
+            assert_ok!(KittiesModule::create_creature(Origin::signed(1), 1));
+            assert_ok!(KittiesModule::create_creature(Origin::signed(1), 1));
+
+            let puppy = KittiesModule::kitty(1).unwrap();
+            assert_eq!(puppy.species(), 1);
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.species()), Some(KITTY_SPECIES));
+            assert!(puppy.dna()[8..].iter().all(|byte| *byte == 0));
+            // The body of a puppy is read from its byte 7
+            let body = puppy.dna()[7] & 0x0f;
+            assert!(KittiesModule::kitties_with_trait((TraitKind::Body, body)).contains(&1));
+
+            assert_noop!(KittiesModule::breed(Origin::signed(1), 0, 1), "Creatures of different species can not breed");
+            assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+            let pup = KittiesModule::kitty(3).unwrap();
+            assert_eq!(pup.species(), 1);
+            assert!(pup.dna()[8..].iter().all(|byte| *byte == 0));
+            assert_noop!(KittiesModule::export_kitty(Origin::signed(1), 1, vec![1]), "Only kitties can be exported");
+            assert_eq!(KittiesModule::do_try_state(), Ok(()));
+        });
+    }
+
+    #[test]
+    fn kitty_v5_records_migrate_to_species() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            let key = raw_map_key(b"Kitties Kitties", &0u32);
+            let old = KittyV5 { dna: [7; 16], price: 5u64, generation: 1, soulbound: false, season: Some(2) };
+            runtime_io::set_storage(&key, &old.encode());
+            <KittiesCount<Test>>::put(1);
+            <StorageVersion>::put(10);
+
+            KittiesModule::migrate_storage();
+
+            let kitty = KittiesModule::kitty(0).unwrap();
+            assert_eq!((kitty.dna, kitty.season, kitty.species), ([7; 16], Some(2), KITTY_SPECIES));
+            assert_eq!(KittiesModule::storage_version(), STORAGE_VERSION);
+        });
+    }
+
+    #[test]
+    fn module_constants_follow_the_runtime() {
+        let constants = KittiesModule::module_constants();
+        assert_eq!(constants.dispute_window, 5);
+        assert_eq!(constants.max_breed_batch, 3);
+        assert_eq!(constants.max_trade_kitties, 10);
+        assert_eq!(constants.max_page_size, MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn sponsored_call_moves_a_gifted_kitty() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::SponsoredCallFee(5)));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_ok!(KittiesModule::sponsor(Origin::signed(1), 2, 2));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 10);
+
+            // Account 2 has no balance, its signed call is submitted unsigned
+            let operation = SponsoredOperation::Transfer(3, 0);
+            let payload = KittiesModule::sponsored_call_payload(&2, &operation, 0);
+            let call = Call::sponsored_call(2, operation.clone(), TestSignature(3, payload.clone()));
+            assert!(KittiesModule::validate_unsigned(&call).is_err());
+            let other_chain = (b"kitties-sponsored-call", H256::zero(), 2u64, &operation, 0u64).encode();
+            let call = Call::sponsored_call(2, operation.clone(), TestSignature(2, other_chain));
+            assert!(KittiesModule::validate_unsigned(&call).is_err());
+            let call = Call::sponsored_call(2, operation.clone(), TestSignature(2, payload.clone()));
+            assert!(KittiesModule::validate_unsigned(&call).is_ok());
+
+            assert_ok!(KittiesModule::sponsored_call(system::RawOrigin::None.into(), 2, operation.clone(), TestSignature(2, payload.clone())));
+            assert_eq!(KittiesModule::owner_of(0), Some(3));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 5);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 90);
+            assert_eq!(KittiesModule::sponsorship(2).map(|s| s.remaining), Some(1));
+
+            // The signature was for nonce 0
+            assert_noop!(
+                KittiesModule::sponsored_call(system::RawOrigin::None.into(), 2, operation, TestSignature(2, payload)),
+                "The call is not signed by the sponsored account"
+            );
+
+            assert_noop!(KittiesModule::end_sponsorship(Origin::signed(2), 2), "Only the sponsor can end a sponsorship");
+            assert_ok!(KittiesModule::end_sponsorship(Origin::signed(1), 2));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 0);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 95);
+        });
+    }
+
+    #[test]
+    fn sponsorship_ends_after_its_operations() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).kitty(2).build(), || {
+            assert_ok!(KittiesModule::sponsor(Origin::signed(1), 2, 1));
+            assert_noop!(KittiesModule::sponsor(Origin::signed(3), 2, 1), "This account already has a sponsor");
+
+            // A failing operation still uses up the sponsored call
+            let operation = SponsoredOperation::Transfer(3, 5);
+            let payload = KittiesModule::sponsored_call_payload(&2, &operation, 0);
+            assert!(KittiesModule::sponsored_call(system::RawOrigin::None.into(), 2, operation, TestSignature(2, payload)).is_err());
+            assert_eq!(KittiesModule::nonce_of(&2, NoncePurpose::SponsoredCall), 1);
+            assert_eq!(KittiesModule::sponsorship(2), None);
+
+            let operation = SponsoredOperation::Transfer(3, 0);
+            let payload = KittiesModule::sponsored_call_payload(&2, &operation, 1);
+            assert_noop!(
+                KittiesModule::sponsored_call(system::RawOrigin::None.into(), 2, operation, TestSignature(2, payload)),
+                "This account has no sponsor"
+            );
+        });
+    }
+
+    #[test]
+    fn sale_offers_are_filled_once() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            let offer = SaleOffer { kitty_id: 0, price: 30, nonce: 0, deadline: 10 };
+            let payload = KittiesModule::sale_offer_payload(&offer);
+
+            assert_noop!(
+                KittiesModule::fill_sale_offer(Origin::signed(2), offer.clone(), TestSignature(2, payload.clone())),
+                "The offer is not signed by the owner"
+            );
+            let other_chain = (b"kitties-sale-offer", H256::zero(), &offer).encode();
+            assert_noop!(
+                KittiesModule::fill_sale_offer(Origin::signed(2), offer.clone(), TestSignature(1, other_chain)),
+                "The offer is not signed by the owner"
+            );
+            let gift = SaleOffer { price: 0, ..offer.clone() };
+            let gift_payload = KittiesModule::sale_offer_payload(&gift);
+            assert_noop!(
+                KittiesModule::fill_sale_offer(Origin::signed(2), gift, TestSignature(1, gift_payload)),
+                "The offer price must be greater than zero"
+            );
+            assert_ok!(KittiesModule::fill_sale_offer(Origin::signed(2), offer.clone(), TestSignature(1, payload.clone())));
+            assert_eq!(KittiesModule::owner_of(0), Some(2));
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 30);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 70);
+
+            // Back to 1, the offer can not sell the kitty again
+            assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 0));
+            assert_noop!(
+                KittiesModule::fill_sale_offer(Origin::signed(2), offer, TestSignature(1, payload)),
+                "The offer nonce is not the owner's next nonce"
+            );
+        });
+    }
+
+    #[test]
+    fn cancel_all_orders_moves_every_nonce_on() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            // A voucher nonce from before `Nonces` still counts
+            <TransferNonces<Test>>::insert(1, 3);
+            assert_eq!(KittiesModule::nonce_of(&1, NoncePurpose::TransferVoucher), 3);
+
+            let offer = SaleOffer { kitty_id: 0, price: 30, nonce: 0, deadline: 10 };
+            let payload = KittiesModule::sale_offer_payload(&offer);
+            assert_ok!(KittiesModule::cancel_all_orders(Origin::signed(1)));
+            assert_noop!(
+                KittiesModule::fill_sale_offer(Origin::signed(2), offer, TestSignature(1, payload)),
+                "The offer nonce is not the owner's next nonce"
+            );
+
+            assert_eq!(KittiesModule::nonce_of(&1, NoncePurpose::TransferVoucher), 4);
+            assert_eq!(KittiesModule::nonce_of(&1, NoncePurpose::SponsoredCall), 1);
+            assert_eq!(KittiesModule::nonce_of(&1, NoncePurpose::SaleOffer), 1);
+            assert!(!<TransferNonces<Test>>::exists(1));
+        });
+    }
+
+    #[test]
+    fn reference_price_quotes_need_an_oracle_signature() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            let oracle = sr25519::Pair::from_seed(&[1; 32]);
+            assert_ok!(KittiesModule::set_reference_price_oracles(system::RawOrigin::Root.into(), vec![oracle.public()]));
+            system::Module::<Test>::set_block_number(5);
+
+            let quote = KittiesModule::reference_price_payload(3, 5);
+            let call = Call::submit_reference_price(3, 5, oracle.public(), oracle.sign(&quote));
+            assert!(KittiesModule::validate_unsigned(&call).is_ok());
+            // A gen-0 price report of the same key is not a quote
+            let report = (3u64, 5u64).encode();
+            let replayed = Call::submit_reference_price(3, 5, oracle.public(), oracle.sign(&report));
+            assert!(KittiesModule::validate_unsigned(&replayed).is_err());
+
+            assert_ok!(KittiesModule::submit_reference_price(system::RawOrigin::None.into(), 3, 5, oracle.public(), oracle.sign(&quote)));
+            assert_eq!(KittiesModule::reference_to_balance(10), Ok(30));
+            assert_eq!(KittiesModule::balance_to_reference(31), Ok(10));
+
+            system::Module::<Test>::set_block_number(6 + REFERENCE_PRICE_MAX_AGE as u64);
+            assert_eq!(KittiesModule::reference_to_balance(10), Err("The reference price is stale"));
+        });
+    }
+
+    #[test]
+    fn reference_listings_are_converted_when_bought() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).build(), || {
+            assert_noop!(KittiesModule::set_reference_price_listing(Origin::signed(1), 0, 10), "No reference price is quoted");
+            <ReferencePrice<Test>>::put((2, 1));
+            assert_ok!(KittiesModule::set_reference_price_listing(Origin::signed(1), 0, 10));
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.price), Some(20));
+
+            // The reference price doubles before the sale
+            system::Module::<Test>::set_block_number(2);
+            <ReferencePrice<Test>>::put((4, 2));
+            assert_noop!(
+                KittiesModule::buy_kitty(Origin::signed(2), 0, 20),
+                "The cat you want to buy costs more than your max price"
+            );
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 40));
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 60);
+            assert_eq!(KittiesModule::reference_listing(0), None);
+        });
+    }
+
+    #[test]
+    fn circuit_breaker_pauses_settlement_on_volume() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 100).kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketVolumeLimit(Some(50))));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 30));
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 1, 30));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 30));
+            assert!(!KittiesModule::is_paused(Subsystem::Settlement));
+
+            // The sale over the limit goes through, the next ones wait for governance
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 1, 30));
+            assert!(KittiesModule::is_paused(Subsystem::Settlement));
+            assert_ok!(KittiesModule::set_price(Origin::signed(2), 0, 10));
+            assert_noop!(KittiesModule::buy_kitty(Origin::signed(1), 0, 10), "This subsystem is paused");
+            assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 1));
+
+            assert_ok!(KittiesModule::set_paused(Origin::ROOT, Subsystem::Settlement, false));
+            assert_eq!(KittiesModule::market_stats(), MarketEraStats::default());
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(1), 0, 10));
+        });
+    }
+
+    #[test]
+    fn circuit_breaker_counts_abnormal_sales() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MarketDeviationLimit(Some(Permill::from_percent(50)))));
+            <RecentSales<Test>>::put(vec![100]);
+
+            KittiesModule::watch_market(120);
+            assert_eq!(KittiesModule::market_stats().abnormal_sales, 0);
+            for _ in 0..MAX_ABNORMAL_SALES {
+                KittiesModule::watch_market(10);
+            }
+            assert_eq!(KittiesModule::market_stats().abnormal_sales, MAX_ABNORMAL_SALES);
+            assert!(KittiesModule::is_paused(Subsystem::Settlement));
+
+            // A new era starts over
+            system::Module::<Test>::set_block_number(MARKET_ERA_LENGTH as u64);
+            KittiesModule::watch_market(10);
+            assert_eq!(KittiesModule::market_stats().era, 1);
+            assert_eq!(KittiesModule::market_stats().abnormal_sales, 1);
+        });
+    }
+
+    #[test]
+    fn calls_per_block_are_limited_for_each_account() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).kitty(1).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxOperationsPerBlock(Some(2))));
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            // A failing call counts too
+            assert_eq!(KittiesModule::transfer(Origin::signed(1), 2, 5), Err("No owner for this kitty"));
+            assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 1), "Too many kitty calls by this account in this block");
+            assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 0));
+
+            system::Module::<Test>::set_block_number(2);
+            KittiesModule::on_initialize(2);
+            assert_eq!(KittiesModule::block_operations(1), 0);
+            assert!(KittiesModule::block_operators().is_empty());
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        });
+    }
+
+    #[test]
+    fn burning_a_kitty_pays_its_creation_deposit_to_the_owner() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).balance(3, 5).build(), || {
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::CreationDeposit(10)));
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 10);
+            assert_eq!(KittiesModule::kitty_deposit(0), Some((1, 10)));
+
+            assert!(KittiesModule::create(Origin::signed(3)).is_err());
+            assert_eq!(KittiesModule::kitties_count(), 1);
+            assert_eq!(balances::Module::<Test>::reserved_balance(&3), 0);
+
+            assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 0));
+            assert_ok!(KittiesModule::burn(Origin::signed(2), 0));
+            assert_eq!(balances::Module::<Test>::reserved_balance(&1), 0);
+            assert_eq!(balances::Module::<Test>::free_balance(&1), 90);
+            assert_eq!(balances::Module::<Test>::free_balance(&2), 10);
+            assert_eq!(KittiesModule::kitty_deposit(0), None);
+        });
+    }
+
+    #[test]
+    fn dna_is_drawn_from_a_block_after_the_creation() {
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            REVEAL_DELAY.with(|v| *v.borrow_mut() = 1);
+            assert_ok!(KittiesModule::create(Origin::signed(1)));
+            // The hash of block 2 is only known at block 3
+            assert_eq!(KittiesModule::reveal_queue(2), Vec::<u32>::new());
+            assert_eq!(KittiesModule::reveal_queue(3), vec![0]);
+
+            let commitment = KittiesModule::pending_reveal(0).unwrap();
+            <system::BlockHash<Test>>::insert(2, H256::repeat_byte(7));
+            KittiesModule::on_initialize(3);
+            let dna = (commitment, H256::repeat_byte(7), 0u32).using_encoded(blake2_128);
+            assert_eq!(KittiesModule::kitty(0).map(|kitty| kitty.dna()), Some(dna));
+        });
+    }
+
+    #[test]
+    fn royalty_payouts_never_exceed_the_royalty() {
+        with_externalities(&mut ExtBuilder::default().balance(2, 1000).kitty(1).build(), || {
+            // Stored before the shares were checked
+            <Royalties<Test>>::insert(0, Royalty { rate: Permill::from_percent(10), splits: vec![(5, u32::max_value()), (6, 1001)] });
+            assert_eq!(KittiesModule::royalty_payouts(0, 500), vec![(5, 50)]);
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 500));
+            assert_ok!(KittiesModule::buy_kitty(Origin::signed(2), 0, 500));
+            let balance = |who| balances::Module::<Test>::free_balance(&who);
+            assert_eq!((balance(1), balance(5), balance(6)), (450, 50, 0));
+        });
+    }
+
+    #[test]
+    fn sponsored_calls_count_as_operations_of_the_sponsored_account() {
+        with_externalities(&mut ExtBuilder::default().balance(1, 100).kitty(2).kitty(2).build(), || {
+            system::Module::<Test>::set_block_number(2);
+            assert_ok!(KittiesModule::set_heirs(Origin::signed(2), vec![4], 10));
+            assert_ok!(KittiesModule::set_parameter(Origin::ROOT, KittiesParameter::MaxOperationsPerBlock(Some(1))));
+            assert_ok!(KittiesModule::sponsor(Origin::signed(1), 2, 3));
+            KittiesModule::on_initialize(3);
+            system::Module::<Test>::set_block_number(3);
+
+            let operation = SponsoredOperation::Transfer(3, 0);
+            let payload = KittiesModule::sponsored_call_payload(&2, &operation, 0);
+            assert_ok!(KittiesModule::sponsored_call(system::RawOrigin::None.into(), 2, operation, TestSignature(2, payload)));
+            assert_eq!(KittiesModule::last_active(&2), 3);
+
+            // The cap of account 2 is used up for this block, the next call is not even pooled
+            let operation = SponsoredOperation::Transfer(3, 1);
+            let payload = KittiesModule::sponsored_call_payload(&2, &operation, 1);
+            let call = Call::sponsored_call(2, operation.clone(), TestSignature(2, payload.clone()));
+            assert!(KittiesModule::validate_unsigned(&call).is_err());
+            assert_eq!(
+                KittiesModule::sponsored_call(system::RawOrigin::None.into(), 2, operation, TestSignature(2, payload)),
+                Err("Too many kitty calls by this account in this block")
+            );
+            assert_eq!(KittiesModule::nonce_of(&2, NoncePurpose::SponsoredCall), 1);
+            assert_eq!(KittiesModule::sponsorship(2).map(|s| s.remaining), Some(2));
+        });
+    }
+
+    #[test]
+    fn instances_keep_their_collections_apart() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            assert_ok!(PuppiesModule::create(Origin::signed(2)));
+            assert_ok!(PuppiesModule::create(Origin::signed(2)));
+
+            assert_eq!(KittiesModule::kitties_count(), 1);
+            assert_eq!(PuppiesModule::kitties_count(), 2);
+            assert_eq!(KittiesModule::owner_of(0), Some(1));
+            assert_eq!(PuppiesModule::owner_of(0), Some(2));
+            assert_eq!(KittiesModule::owned_kitties_count(&2), 0);
+            assert_eq!(PuppiesModule::owned_kitties_count(&2), 2);
+            assert_eq!(OwnedKittiesList::<Test, Instance1>::iter(&2).collect::<Vec<_>>(), vec![0, 1]);
+            assert_eq!(OwnedKittiesList::<Test>::iter(&2).next(), None);
+
+            assert_ok!(KittiesModule::set_price(Origin::signed(1), 0, 10));
+            assert_eq!(PuppiesModule::kitty(0).unwrap().price, 0);
+        });
+    }
+
+    #[test]
+    fn default_instance_keeps_the_single_instance_layout() {
+        assert_eq!(OwnedKittiesChildPrefix::<DefaultInstance>::get(), b"kitties_owned_".to_vec());
+        assert_eq!(KittiesModule::offchain_key(b"indexer-url"), b"kitties::indexer-url".to_vec());
+        assert_eq!(KittiesModule::storage_prefix(b"Kitties"), b"Kitties Kitties".to_vec());
+
+        assert_ne!(OwnedKittiesChildPrefix::<Instance1>::get(), OwnedKittiesChildPrefix::<DefaultInstance>::get());
+        assert_ne!(PuppiesModule::offchain_key(b"indexer-url"), KittiesModule::offchain_key(b"indexer-url"));
+        assert_ne!(PuppiesModule::storage_prefix(b"Kitties"), KittiesModule::storage_prefix(b"Kitties"));
+        assert_ne!(PuppiesModule::treasury_account(), KittiesModule::treasury_account());
+        assert_ne!(PuppiesModule::creation_reserve_account(), KittiesModule::creation_reserve_account());
+
+        let offer = SaleOffer { kitty_id: 0u32, price: 5u64, nonce: 0, deadline: 10u64 };
+        with_externalities(&mut ExtBuilder::default().build(), || {
+            assert_ne!(PuppiesModule::sale_offer_payload(&offer), KittiesModule::sale_offer_payload(&offer));
+        });
+    }
+
+    #[test]
+    fn migration_leaves_chains_with_too_many_kitties_alone() {
+        with_externalities(&mut ExtBuilder::default().kitty(1).build(), || {
+            let key = KittiesModule::traits_of(&KittiesModule::kitty(0).unwrap())[0];
+            <KittiesPerTrait>::remove(key);
+            <StorageVersion>::put(12);
+            <KittiesCount<Test>>::put(MAX_MIGRATED_KITTIES as u32 + 1);
+            KittiesModule::migrate_storage();
+            assert_eq!(KittiesModule::storage_version(), 12);
+            assert_eq!(KittiesModule::kitties_per_trait(key), 0);
+
+            <KittiesCount<Test>>::put(MAX_MIGRATED_KITTIES as u32);
+            KittiesModule::migrate_storage();
+            assert_eq!(KittiesModule::storage_version(), STORAGE_VERSION);
+            assert_eq!(KittiesModule::kitties_per_trait(key), 1);
+        });
+    }
+
+    /// A mock runtime with `u128` kitty indexes, sharing the origins and balances of `Test`
+    mod wide_index {
+        use super::*;
+
+        mod kitties {
+            pub use crate::Event;
+        }
+
+        impl_outer_event! {
+			pub enum WideEvent for WideTest {
+				kitties<T>,
+			}
+		}
+
+        #[derive(Clone, Eq, PartialEq, Debug)]
+        pub struct WideTest;
+        impl system::Trait for WideTest {
+            type Origin = Origin;
+            type Call = ();
+            type Index = u64;
+            type BlockNumber = u64;
+            type Hash = H256;
+            type Hashing = BlakeTwo256;
+            type AccountId = u64;
+            type Lookup = IdentityLookup<Self::AccountId>;
+            type Header = Header;
+            type WeightMultiplierUpdate = ();
+            type Event = WideEvent;
+            type BlockHashCount = BlockHashCount;
+            type MaximumBlockWeight = MaximumBlockWeight;
+            type MaximumBlockLength = MaximumBlockLength;
+            type AvailableBlockRatio = AvailableBlockRatio;
+            type Version = ();
+        }
+
+        impl Trait for WideTest {
+            type Event = WideEvent;
+            type KittyIndex = u128;
+            type Currency = balances::Module<Test>;
+            type MarketEnabled = MarketEnabled;
+            type Call = Call<WideTest>;
+            type SubmitTransaction = system::offchain::TransactionSubmitter<(), WideTest, TestXt<Call<WideTest>, ()>>;
+            type Signature = TestSignature;
+            type OnKittyCreated = ();
+            type OnKittyTransfer = ();
+            type OnKittyBurned = ();
+            type GovernanceOrigin = system::EnsureRoot<u64>;
+            type EmergencyOrigin = system::EnsureRoot<u64>;
+            type RegistrarOrigin = system::EnsureRoot<u64>;
+            type ArbiterOrigin = system::EnsureRoot<u64>;
+            type ModeratorOrigin = system::EnsureRoot<u64>;
+            type DisputeWindow = DisputeWindow;
+            type SoulboundIssuerOrigin = system::EnsureRoot<u64>;
+            type AchievementBadges = AchievementBadges;
+            type RevealDelay = RevealDelay;
+            type BridgeOrigin = system::EnsureRoot<u64>;
+            type MaxBreedBatch = MaxBreedBatch;
+            type MaxPriceBatch = MaxPriceBatch;
+            type IndexPoolAccount = IndexPoolAccount;
+            type Time = MockTime;
+            type TimestampCooldowns = TimestampCooldowns;
+            type MaxWatchersPerKitty = MaxWatchersPerKitty;
+            type MaxHeirs = MaxHeirs;
+            type MaxRoyaltyBeneficiaries = MaxRoyaltyBeneficiaries;
+            type MaxRoyaltyPercent = MaxRoyaltyPercent;
+            type LoyaltyPointsPerTrade = LoyaltyPointsPerTrade;
+            type MaxTradeKitties = MaxTradeKitties;
+        }
+
+        type WideKitties = Module<WideTest>;
+
+        #[test]
+        fn indexes_above_u64_work() {
+            with_externalities(&mut ExtBuilder::default().balance(2, 100).build(), || {
+                let first = u128::from(u64::max_value()) + 1;
+                <KittiesCount<WideTest>>::put(first);
+
+                assert_ok!(WideKitties::create(Origin::signed(1)));
+                assert_ok!(WideKitties::create(Origin::signed(1)));
+                assert_ok!(WideKitties::breed(Origin::signed(1), first, first + 1));
+                assert_eq!(WideKitties::owned_kitty_ids(&1, 10), vec![first, first + 1, first + 2]);
+                assert_eq!(WideKitties::kitty(first + 2).map(|kitty| kitty.generation), Some(1));
+
+                assert_ok!(WideKitties::set_price(Origin::signed(1), first + 2, 10));
+                assert_eq!(WideKitties::market_listings_page(None, 10), (vec![(first + 2, 10)], None));
+                assert_ok!(WideKitties::buy_kitty(Origin::signed(2), first + 2, 10));
+                assert_eq!(WideKitties::owner_of(first + 2), Some(2));
+                assert_eq!(WideKitties::kitties_page(Some(first), 10), (vec![first + 1, first + 2], None));
+                assert_ok!(WideKitties::do_try_state());
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod benchmarking;
@@ -0,0 +1,235 @@
+use support::{StorageDoubleMap, Parameter, storage::child, traits::Get};
+use sr_primitives::traits::Member;
+use codec::{Codec, Encode, Decode, Input, Output, Error};
+use rstd::prelude::*;
+
+#[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+pub struct LinkedItem<Value> {
+	pub prev: Option<Value>,
+	pub next: Option<Value>,
+}
+
+impl<H: Encode> Encode for LinkedItem<H> {
+	fn encode_to<T: Output>(&self, dest: &mut T) {
+		self.prev.encode_to(dest);
+		self.next.encode_to(dest);
+	}
+}
+
+impl<H: Decode> Decode for LinkedItem<H> {
+	fn decode<I: Input>(input: &mut I) -> Result<Self, Error> {
+		let prev = match <Option<H>>::decode(input) {
+			Ok(x) => x,
+			Err(e) => return Err(e),
+		};
+		let next = match <Option<H>>::decode(input) {
+			Ok(x) => x,
+			Err(e) => return Err(e),
+		};
+		Ok(LinkedItem {
+			prev,
+			next,
+		})
+	}
+}
+
+/// Where the items of a `LinkedList` are kept, addressed by the list `Key` and the item `Option<Value>`
+pub trait LinkedItemStorage<Key, Value> {
+	fn get_item(key: &Key, value: &Option<Value>) -> Option<LinkedItem<Value>>;
+	fn insert_item(key: &Key, value: &Option<Value>, item: LinkedItem<Value>);
+	fn take_item(key: &Key, value: &Option<Value>) -> Option<LinkedItem<Value>>;
+	fn contains_item(key: &Key, value: &Option<Value>) -> bool;
+	/// Remove all the items of `key`
+	fn clear_items(key: &Key);
+}
+
+/// Items in a double map keyed by `Key, Option<Value>`
+impl<Storage, Key, Value> LinkedItemStorage<Key, Value> for Storage where
+	Key: Encode,
+	Value: Codec,
+	Storage: StorageDoubleMap<Key, Option<Value>, LinkedItem<Value>, Query = Option<LinkedItem<Value>>>,
+{
+	fn get_item(key: &Key, value: &Option<Value>) -> Option<LinkedItem<Value>> {
+		Storage::get(key, value)
+	}
+
+	fn insert_item(key: &Key, value: &Option<Value>, item: LinkedItem<Value>) {
+		Storage::insert(key, value, item);
+	}
+
+	fn take_item(key: &Key, value: &Option<Value>) -> Option<LinkedItem<Value>> {
+		Storage::take(key, value)
+	}
+
+	fn contains_item(key: &Key, value: &Option<Value>) -> bool {
+		Storage::exists(key, value)
+	}
+
+	fn clear_items(key: &Key) {
+		Storage::remove_prefix(key);
+	}
+}
+
+/// Items in one child trie per `Key`, so a long list neither grows the main trie nor the proofs of other entries.
+/// The child trie of a key is `:child_storage:default:` ++ `Prefix` ++ encoded key, items are keyed by the encoded `Option<Value>`
+pub struct ChildTrieItems<Prefix>(rstd::marker::PhantomData<Prefix>);
+
+impl<Prefix: Get<Vec<u8>>> ChildTrieItems<Prefix> {
+	/// Name of the child trie holding the items of `key`
+	pub fn child_storage_key<Key: Encode>(key: &Key) -> Vec<u8> {
+		let mut storage_key = b":child_storage:default:".to_vec();
+		storage_key.extend_from_slice(&Prefix::get());
+		key.encode_to(&mut storage_key);
+		storage_key
+	}
+}
+
+impl<Prefix, Key, Value> LinkedItemStorage<Key, Value> for ChildTrieItems<Prefix> where
+	Prefix: Get<Vec<u8>>,
+	Key: Encode,
+	Value: Codec,
+{
+	fn get_item(key: &Key, value: &Option<Value>) -> Option<LinkedItem<Value>> {
+		child::get(&Self::child_storage_key(key), &value.encode())
+	}
+
+	fn insert_item(key: &Key, value: &Option<Value>, item: LinkedItem<Value>) {
+		child::put(&Self::child_storage_key(key), &value.encode(), &item);
+	}
+
+	fn take_item(key: &Key, value: &Option<Value>) -> Option<LinkedItem<Value>> {
+		child::take(&Self::child_storage_key(key), &value.encode())
+	}
+
+	fn contains_item(key: &Key, value: &Option<Value>) -> bool {
+		child::exists(&Self::child_storage_key(key), &value.encode())
+	}
+
+	fn clear_items(key: &Key) {
+		child::kill_storage(&Self::child_storage_key(key));
+	}
+}
+
+/// A doubly linked list per `Key` with its items in `Storage`.
+/// The `None` entry is the head, its `prev` is the last item and its `next` the first one.
+pub struct LinkedList<Storage, Key, Value>(rstd::marker::PhantomData<(Storage, Key, Value)>);
+
+impl<Storage, Key, Value> LinkedList<Storage, Key, Value> where
+	Value: Parameter + Member + Copy,
+	Key: Parameter,
+	Storage: LinkedItemStorage<Key, Value>,
+{
+	fn read_head(key: &Key) -> LinkedItem<Value> {
+		Self::read(key, None)
+	}
+
+	fn write_head(account: &Key, item: LinkedItem<Value>) {
+		Self::write(account, None, item);
+	}
+
+	fn read(key: &Key, value: Option<Value>) -> LinkedItem<Value> {
+		Storage::get_item(key, &value).unwrap_or_else(|| LinkedItem {
+			prev: None,
+			next: None,
+		})
+	}
+
+	fn write(key: &Key, value: Option<Value>, item: LinkedItem<Value>) {
+		Storage::insert_item(key, &value, item);
+	}
+
+	/// Append `value` to the list of `key`, returns false and leaves the list untouched if it is already in it
+	pub fn append(key: &Key, value: Value) -> bool {
+		if Storage::contains_item(key, &Some(value)) {
+			return false;
+		}
+
+		let head = Self::read_head(key);
+		let new_head = LinkedItem {
+			prev: Some(value),
+			next: head.next,
+		};
+
+		Self::write_head(key, new_head);
+
+		let prev = Self::read(key, head.prev);
+		let new_prev = LinkedItem {
+			prev: prev.prev,
+			next: Some(value),
+		};
+		Self::write(key, head.prev, new_prev);
+
+		let item = LinkedItem {
+			prev: head.prev,
+			next: None,
+		};
+		Self::write(key, Some(value), item);
+
+		true
+	}
+
+	/// Remove `value` from the list of `key`, returns whether it was in the list
+	pub fn remove(key: &Key, value: Value) -> bool {
+		if let Some(item) = Storage::take_item(key, &Some(value)) {
+			let prev = Self::read(key, item.prev);
+			let new_prev = LinkedItem {
+				prev: prev.prev,
+				next: item.next,
+			};
+
+			Self::write(key, item.prev, new_prev);
+
+			let next = Self::read(key, item.next);
+			let new_next = LinkedItem {
+				prev: item.prev,
+				next: next.next,
+			};
+
+			Self::write(key, item.next, new_next);
+
+			true
+		} else {
+			false
+		}
+	}
+
+	/// Remove the whole list of `key`
+	pub fn clear(key: &Key) {
+		Storage::clear_items(key);
+	}
+
+	/// Iterate over the values of `key` from the first appended to the last
+	pub fn iter(key: &Key) -> LinkedListIter<Storage, Key, Value> {
+		Self::iter_after(key, None)
+	}
+
+	/// Iterate over the values of `key` following `after`, from the first one if `after` is `None`.
+	/// Empty if `after` is not in the list
+	pub fn iter_after(key: &Key, after: Option<Value>) -> LinkedListIter<Storage, Key, Value> {
+		LinkedListIter {
+			key: key.clone(),
+			next: Self::read(key, after).next,
+			_marker: rstd::marker::PhantomData,
+		}
+	}
+}
+
+pub struct LinkedListIter<Storage, Key, Value> {
+	key: Key,
+	next: Option<Value>,
+	_marker: rstd::marker::PhantomData<Storage>,
+}
+
+impl<Storage, Key, Value> Iterator for LinkedListIter<Storage, Key, Value> where
+	Value: Parameter + Member + Copy,
+	Key: Parameter,
+	Storage: LinkedItemStorage<Key, Value>,
+{
+	type Item = Value;
+
+	fn next(&mut self) -> Option<Value> {
+		let value = self.next.take()?;
+		self.next = Storage::get_item(&self.key, &Some(value)).and_then(|item| item.next);
+		Some(value)
+	}
+}
@@ -0,0 +1,221 @@
+//! Off-chain worker mirroring the kitties changed in each block to an HTTP indexer.
+//!
+//! The indexer is configured per node in the persistent off-chain storage, e.g. with the
+//! `offchain_localStorageSet` RPC: `kitties::indexer-url` holds the endpoint URL. Nothing is
+//! sent while it is unset. The body is the SCALE encoded `(block_number, records)`, signed with
+//! the first `kitt` sr25519 key of the node keystore when there is one.
+//!
+//! Every `GEN0_PRICE_UPDATE_PERIOD` blocks the worker also reports the gen-0 price, signed with the
+//! first `kitt` key, as an unsigned `submit_gen0_price` transaction. The price is read from the
+//! endpoint in `kitties::price-url`, which answers with a decimal number, or else is the moving
+//! average of the recent sales. Nodes without a `kitt` key do not report.
+//!
+//! The keys are those of the default instance, the keys of other instances start with the instance
+//! name, e.g. `Instance1kitties::indexer-url`.
+//!
+//! In each block the worker also looks at the next `GC_SCAN_SIZE` kitties for Dutch auctions which
+//! ended, and submits them as an unsigned `collect_garbage` transaction. Gift vouchers are kept in
+//! a map which can not be walked, expired ones are still refunded with `refund_voucher`.
+
+use super::*;
+use primitives::{crypto::KeyTypeId, offchain::{Duration, StorageKind}};
+use sr_primitives::{offchain::http, traits::UniqueSaturatedFrom};
+
+/// Key type of the keys signing the indexer requests
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"kitt");
+
+/// Name of the off-chain storage key of the indexer endpoint URL
+const INDEXER_URL_KEY: &[u8] = b"indexer-url";
+/// Name of the off-chain storage key of the external gen-0 price endpoint URL
+const PRICE_URL_KEY: &[u8] = b"price-url";
+/// Name of the off-chain storage key of the last block mirrored to the indexer
+const LAST_MIRRORED_KEY: &[u8] = b"indexer-last-block";
+/// Name of the off-chain storage key of the last kitty the garbage collector looked at
+const GC_CURSOR_KEY: &[u8] = b"gc-cursor";
+/// How long the indexer and the price endpoint get to answer, in milliseconds
+const INDEXER_TIMEOUT_MS: u64 = 3_000;
+
+/// The state of a changed kitty sent to the indexer, `None` owner and kitty for a burned one
+type IndexedKitty<T, I = DefaultInstance> = (<T as Trait<I>>::KittyIndex, Option<<T as system::Trait>::AccountId>, Option<Kitty<BalanceOf<T, I>>>);
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// The off-chain storage key `name` of this instance: `kitties::` and `name` after the instance name
+    pub(crate) fn offchain_key(name: &[u8]) -> Vec<u8> {
+        [instance_name::<I>(), &b"kitties::"[..], name].concat()
+    }
+
+    /// Send the kitties changed in block `now` to the indexer, once per block
+    pub(crate) fn mirror_to_indexer(now: T::BlockNumber) {
+        let url = match runtime_io::local_storage_get(StorageKind::PERSISTENT, &Self::offchain_key(INDEXER_URL_KEY)) {
+            Some(url) => url,
+            None => return,
+        };
+
+        let last_mirrored = runtime_io::local_storage_get(StorageKind::PERSISTENT, &Self::offchain_key(LAST_MIRRORED_KEY))
+            .and_then(|raw| T::BlockNumber::decode(&mut &raw[..]).ok());
+        if last_mirrored.map_or(false, |last| last >= now) {
+            return;
+        }
+
+        let changed = Self::changed_kitties();
+        if !changed.is_empty() {
+            if let Err(e) = Self::post_to_indexer(&url, now, changed) {
+                runtime_io::print(e);
+                return;
+            }
+        }
+
+        runtime_io::local_storage_set(StorageKind::PERSISTENT, &Self::offchain_key(LAST_MIRRORED_KEY), &now.encode());
+    }
+
+    fn post_to_indexer(url: &[u8], now: T::BlockNumber, changed: Vec<T::KittyIndex>) -> Result {
+        let url = rstd::str::from_utf8(url).map_err(|_| "The kitties indexer URL is not valid UTF-8")?;
+
+        let records: Vec<IndexedKitty<T, I>> = changed.into_iter()
+            .map(|kitty_id| (kitty_id, Self::owner_of(kitty_id), Self::kitty(kitty_id)))
+            .collect();
+        let body = (now, records).encode();
+
+        // Hex of the signer and signature, empty when the node has no `kitt` key
+        let (signer_hex, signature_hex) = match runtime_io::sr25519_public_keys(KEY_TYPE).into_iter().next() {
+            Some(signer) => {
+                let signature = runtime_io::sr25519_sign(KEY_TYPE, &signer, &body)
+                    .ok_or("Failed to sign the kitties indexer request")?;
+                (to_hex(signer.as_ref()), to_hex(signature.as_ref()))
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        let signer_hex = rstd::str::from_utf8(&signer_hex).map_err(|_| "Invalid signer hex")?;
+        let signature_hex = rstd::str::from_utf8(&signature_hex).map_err(|_| "Invalid signature hex")?;
+
+        let mut chunks = Vec::new();
+        chunks.push(body);
+
+        let deadline = runtime_io::timestamp().add(Duration::from_millis(INDEXER_TIMEOUT_MS));
+        let mut request = http::Request::post(url, chunks)
+            .add_header("Content-Type", "application/octet-stream")
+            .deadline(deadline);
+        if !signer_hex.is_empty() {
+            request = request
+                .add_header("X-Kitties-Signer", signer_hex)
+                .add_header("X-Kitties-Signature", signature_hex);
+        }
+
+        let pending = request.send().map_err(|_| "Failed to send the kitties indexer request")?;
+        let response = pending.try_wait(deadline)
+            .map_err(|_| "The kitties indexer request timed out")?
+            .map_err(|_| "The kitties indexer request failed")?;
+        ensure!(response.code == 200, "The kitties indexer refused the update");
+
+        Ok(())
+    }
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// Submit a signed gen-0 price report every `GEN0_PRICE_UPDATE_PERIOD` blocks
+    pub(crate) fn report_gen0_price(now: T::BlockNumber) {
+        if !(now % GEN0_PRICE_UPDATE_PERIOD.into()).is_zero() {
+            return;
+        }
+        let oracle = match runtime_io::sr25519_public_keys(KEY_TYPE).into_iter().next() {
+            Some(oracle) => oracle,
+            None => return,
+        };
+
+        if let Err(e) = Self::submit_gen0_price_report(now, oracle) {
+            runtime_io::print(e);
+        }
+    }
+
+    fn submit_gen0_price_report(now: T::BlockNumber, oracle: sr25519::Public) -> Result {
+        let price = match runtime_io::local_storage_get(StorageKind::PERSISTENT, &Self::offchain_key(PRICE_URL_KEY)) {
+            Some(url) => Self::fetch_gen0_price(&url)?,
+            None => match Self::recent_sales_average() {
+                Some(price) => price,
+                None => return Ok(()),
+            },
+        };
+        // The rest of the checks need the chain state of a block being built, they run in `validate_unsigned`
+        ensure!(!price.is_zero(), "The gen-0 price must be greater than zero");
+
+        let signature = runtime_io::sr25519_sign(KEY_TYPE, &oracle, &Self::gen0_price_payload(price, now))
+            .ok_or("Failed to sign the gen-0 price report")?;
+        let call = Call::<T, I>::submit_gen0_price(price, now, oracle, signature);
+        T::SubmitTransaction::submit_unsigned(call).map_err(|_| "Failed to submit the gen-0 price report")
+    }
+
+    /// Moving average of the recent sales, `None` without any sale
+    fn recent_sales_average() -> Option<BalanceOf<T, I>> {
+        let sales = Self::recent_sales();
+        if sales.is_empty() {
+            return None;
+        }
+        let total = sales.iter().fold(BalanceOf::<T, I>::zero(), |total, price| total.saturating_add(*price));
+        Some(total / (sales.len() as u32).into())
+    }
+
+    /// Read the price from the endpoint at `url`, which answers with a decimal number
+    fn fetch_gen0_price(url: &[u8]) -> result::Result<BalanceOf<T, I>, &'static str> {
+        let url = rstd::str::from_utf8(url).map_err(|_| "The gen-0 price URL is not valid UTF-8")?;
+
+        let deadline = runtime_io::timestamp().add(Duration::from_millis(INDEXER_TIMEOUT_MS));
+        let pending = http::Request::get(url).deadline(deadline).send()
+            .map_err(|_| "Failed to send the gen-0 price request")?;
+        let response = pending.try_wait(deadline)
+            .map_err(|_| "The gen-0 price request timed out")?
+            .map_err(|_| "The gen-0 price request failed")?;
+        ensure!(response.code == 200, "The gen-0 price endpoint refused the request");
+
+        let body: Vec<u8> = response.body().collect();
+        let price = parse_decimal(&body).ok_or("The gen-0 price is not a decimal number")?;
+        Ok(BalanceOf::<T, I>::unique_saturated_from(price))
+    }
+}
+
+impl<T: Trait<I>, I: Instance> Module<T, I> {
+    /// Submit the finished auctions among the next `GC_SCAN_SIZE` kitties, going round all kitties
+    pub(crate) fn report_garbage() {
+        let cursor = runtime_io::local_storage_get(StorageKind::PERSISTENT, &Self::offchain_key(GC_CURSOR_KEY))
+            .and_then(|raw| Option::<T::KittyIndex>::decode(&mut &raw[..]).ok())
+            .unwrap_or(None);
+
+        let scanned: Vec<T::KittyIndex> = <AllKittiesList<T, I>>::iter_after(&(), cursor)
+            .take(GC_SCAN_SIZE as usize)
+            .collect();
+        // Start over from the first kitty once the end of the list is reached
+        let next = if scanned.len() < GC_SCAN_SIZE as usize { None } else { scanned.last().cloned() };
+        runtime_io::local_storage_set(StorageKind::PERSISTENT, &Self::offchain_key(GC_CURSOR_KEY), &next.encode());
+
+        let garbage: Vec<T::KittyIndex> = scanned.into_iter()
+            .filter(|kitty_id| Self::is_finished_auction(*kitty_id))
+            .take(MAX_GARBAGE_ITEMS)
+            .collect();
+        if garbage.is_empty() {
+            return;
+        }
+        if T::SubmitTransaction::submit_unsigned(Call::<T, I>::collect_garbage(garbage)).is_err() {
+            runtime_io::print("Failed to submit the kitties garbage collection");
+        }
+    }
+}
+
+/// Parse a decimal number surrounded by optional whitespace
+fn parse_decimal(text: &[u8]) -> Option<u128> {
+    let start = text.iter().position(|c| !c.is_ascii_whitespace())?;
+    let end = text.iter().rposition(|c| !c.is_ascii_whitespace())? + 1;
+    text[start..end].iter().try_fold(0u128, |value, c| match c {
+        b'0'..=b'9' => value.checked_mul(10)?.checked_add(u128::from(c - b'0')),
+        _ => None,
+    })
+}
+
+/// Lower case hex of `bytes`, for the request headers
+fn to_hex(bytes: &[u8]) -> Vec<u8> {
+    const DIGITS: &[u8] = b"0123456789abcdef";
+    let mut hex = Vec::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize]);
+        hex.push(DIGITS[(byte & 0xf) as usize]);
+    }
+    hex
+}
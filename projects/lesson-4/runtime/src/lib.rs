@@ -34,6 +34,7 @@ pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
 pub use sr_primitives::{Permill, Perbill};
 pub use support::{StorageValue, construct_runtime, parameter_types};
+use support::traits::Get;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -64,9 +65,6 @@ pub type DigestItem = generic::DigestItem<Hash>;
 /// Used for the module template in `./template.rs`
 mod template;
 
-/// Used for the module kitties in `./kitties.rs`
-mod kitties;
-
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
 /// of data like extrinsics, allowing for them to continue syncing the network through upgrades
@@ -259,8 +257,62 @@ impl template::Trait for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const KittiesMarketEnabled: bool = false;
+	pub const KittiesDisputeWindow: BlockNumber = DAYS;
+	pub const KittiesAchievementBadges: bool = true;
+	pub const KittiesRevealDelay: BlockNumber = MINUTES;
+	pub const KittiesMaxBreedBatch: u32 = 5;
+	pub const KittiesMaxPriceBatch: u32 = 10;
+	pub const KittiesTimestampCooldowns: bool = false;
+	pub const KittiesMaxWatchersPerKitty: u32 = 100;
+	pub const KittiesMaxHeirs: u32 = 10;
+	pub const KittiesMaxRoyaltyBeneficiaries: u32 = 10;
+	pub const KittiesMaxRoyaltyPercent: u32 = 10;
+	pub const KittiesLoyaltyPointsPerTrade: u64 = 10;
+	pub const KittiesMaxTradeKitties: u32 = 10;
+}
+
+/// Keyless account of the kitties index pool, the hash of a fixed seed has no known private key
+pub struct KittiesIndexPoolAccount;
+impl Get<AccountId> for KittiesIndexPoolAccount {
+	fn get() -> AccountId {
+		AccountId::from_raw(runtime_io::blake2_256(b"kitties/index-pool"))
+	}
+}
+
 impl kitties::Trait for Runtime {
+	type Event = Event;
 	type KittyIndex = u32;
+	type Currency = Balances;
+	type MarketEnabled = KittiesMarketEnabled;
+	type Call = Call;
+	type SubmitTransaction = system::offchain::TransactionSubmitter<(), Runtime, UncheckedExtrinsic>;
+	type Signature = Signature;
+	type OnKittyCreated = ();
+	type OnKittyTransfer = ();
+	type OnKittyBurned = ();
+	type GovernanceOrigin = system::EnsureRoot<AccountId>;
+	type EmergencyOrigin = system::EnsureRoot<AccountId>;
+	type RegistrarOrigin = system::EnsureRoot<AccountId>;
+	type ArbiterOrigin = system::EnsureRoot<AccountId>;
+	type ModeratorOrigin = system::EnsureRoot<AccountId>;
+	type DisputeWindow = KittiesDisputeWindow;
+	type SoulboundIssuerOrigin = system::EnsureRoot<AccountId>;
+	type AchievementBadges = KittiesAchievementBadges;
+	type RevealDelay = KittiesRevealDelay;
+	type BridgeOrigin = system::EnsureRoot<AccountId>;
+	type MaxBreedBatch = KittiesMaxBreedBatch;
+	type MaxPriceBatch = KittiesMaxPriceBatch;
+	type IndexPoolAccount = KittiesIndexPoolAccount;
+	type Time = Timestamp;
+	type TimestampCooldowns = KittiesTimestampCooldowns;
+	type MaxWatchersPerKitty = KittiesMaxWatchersPerKitty;
+	type MaxHeirs = KittiesMaxHeirs;
+	type MaxRoyaltyBeneficiaries = KittiesMaxRoyaltyBeneficiaries;
+	type MaxRoyaltyPercent = KittiesMaxRoyaltyPercent;
+	type LoyaltyPointsPerTrade = KittiesLoyaltyPointsPerTrade;
+	type MaxTradeKitties = KittiesMaxTradeKitties;
 }
 
 construct_runtime!(
@@ -279,7 +331,7 @@ construct_runtime!(
 		// Used for the module template in `./template.rs`
 		TemplateModule: template::{Module, Call, Storage, Event<T>},
 		// Substrate Kitties module
-		Kitties: kitties::{Module, Storage, Call},
+		Kitties: kitties::{Module, Storage, Call, Event<T>, ValidateUnsigned},
 	}
 );
 
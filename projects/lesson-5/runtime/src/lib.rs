@@ -24,6 +24,7 @@ use client::{
 	runtime_api as client_api, impl_runtime_apis
 };
 use version::RuntimeVersion;
+use kitties_runtime_api::{KittyDetails, KittyArt};
 #[cfg(feature = "std")]
 use version::NativeVersion;
 
@@ -34,6 +35,7 @@ pub use timestamp::Call as TimestampCall;
 pub use balances::Call as BalancesCall;
 pub use sr_primitives::{Permill, Perbill};
 pub use support::{StorageValue, construct_runtime, parameter_types};
+use support::traits::Get;
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -64,9 +66,6 @@ pub type DigestItem = generic::DigestItem<Hash>;
 /// Used for the module template in `./template.rs`
 mod template;
 
-/// Used for the module kitties in `./kitties.rs`
-mod kitties;
-
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
 /// of data like extrinsics, allowing for them to continue syncing the network through upgrades
@@ -259,8 +258,62 @@ impl template::Trait for Runtime {
 	type Event = Event;
 }
 
+parameter_types! {
+	pub const KittiesMarketEnabled: bool = true;
+	pub const KittiesDisputeWindow: BlockNumber = DAYS;
+	pub const KittiesAchievementBadges: bool = true;
+	pub const KittiesRevealDelay: BlockNumber = MINUTES;
+	pub const KittiesMaxBreedBatch: u32 = 5;
+	pub const KittiesMaxPriceBatch: u32 = 10;
+	pub const KittiesTimestampCooldowns: bool = false;
+	pub const KittiesMaxWatchersPerKitty: u32 = 100;
+	pub const KittiesMaxHeirs: u32 = 10;
+	pub const KittiesMaxRoyaltyBeneficiaries: u32 = 10;
+	pub const KittiesMaxRoyaltyPercent: u32 = 10;
+	pub const KittiesLoyaltyPointsPerTrade: u64 = 10;
+	pub const KittiesMaxTradeKitties: u32 = 10;
+}
+
+/// Keyless account of the kitties index pool, the hash of a fixed seed has no known private key
+pub struct KittiesIndexPoolAccount;
+impl Get<AccountId> for KittiesIndexPoolAccount {
+	fn get() -> AccountId {
+		AccountId::from_raw(runtime_io::blake2_256(b"kitties/index-pool"))
+	}
+}
+
 impl kitties::Trait for Runtime {
+	type Event = Event;
 	type KittyIndex = u32;
+	type Currency = Balances;
+	type MarketEnabled = KittiesMarketEnabled;
+	type Call = Call;
+	type SubmitTransaction = system::offchain::TransactionSubmitter<(), Runtime, UncheckedExtrinsic>;
+	type Signature = Signature;
+	type OnKittyCreated = ();
+	type OnKittyTransfer = ();
+	type OnKittyBurned = ();
+	type GovernanceOrigin = system::EnsureRoot<AccountId>;
+	type EmergencyOrigin = system::EnsureRoot<AccountId>;
+	type RegistrarOrigin = system::EnsureRoot<AccountId>;
+	type ArbiterOrigin = system::EnsureRoot<AccountId>;
+	type ModeratorOrigin = system::EnsureRoot<AccountId>;
+	type DisputeWindow = KittiesDisputeWindow;
+	type SoulboundIssuerOrigin = system::EnsureRoot<AccountId>;
+	type AchievementBadges = KittiesAchievementBadges;
+	type RevealDelay = KittiesRevealDelay;
+	type BridgeOrigin = system::EnsureRoot<AccountId>;
+	type MaxBreedBatch = KittiesMaxBreedBatch;
+	type MaxPriceBatch = KittiesMaxPriceBatch;
+	type IndexPoolAccount = KittiesIndexPoolAccount;
+	type Time = Timestamp;
+	type TimestampCooldowns = KittiesTimestampCooldowns;
+	type MaxWatchersPerKitty = KittiesMaxWatchersPerKitty;
+	type MaxHeirs = KittiesMaxHeirs;
+	type MaxRoyaltyBeneficiaries = KittiesMaxRoyaltyBeneficiaries;
+	type MaxRoyaltyPercent = KittiesMaxRoyaltyPercent;
+	type LoyaltyPointsPerTrade = KittiesLoyaltyPointsPerTrade;
+	type MaxTradeKitties = KittiesMaxTradeKitties;
 }
 
 construct_runtime!(
@@ -279,7 +332,7 @@ construct_runtime!(
 		// Used for the module template in `./template.rs`
 		TemplateModule: template::{Module, Call, Storage, Event<T>},
 		// Substrate Kitties module
-		Kitties: kitties::{Module, Storage, Call},
+		Kitties: kitties::{Module, Storage, Call, Event<T>, ValidateUnsigned},
 	}
 );
 
@@ -306,6 +359,18 @@ pub type SignedExtra = (
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Call, SignedExtra>;
+/// A kitty with its owner, as returned by the kitties runtime API
+fn kitty_details(kitty_id: u32) -> Option<KittyDetails<AccountId, Balance>> {
+	let kitty = Kitties::kitty(kitty_id)?;
+	let owner = Kitties::owner_of(kitty_id)?;
+	Some(KittyDetails {
+		owner,
+		dna: kitty.dna(),
+		price: kitty.price(),
+		generation: kitty.generation(),
+	})
+}
+
 /// Executive: handles dispatch to the various modules.
 pub type Executive = executive::Executive<Runtime, Block, system::ChainContext<Runtime>, Runtime, AllModules>;
 
@@ -408,6 +473,52 @@ impl_runtime_apis! {
 		}
 	}
 
+	impl kitties_runtime_api::KittiesApi<Block, AccountId, u32, Balance> for Runtime {
+		fn kitties_of(account: AccountId) -> Vec<(u32, KittyDetails<AccountId, Balance>)> {
+			Kitties::owned_kitties_iter(&account)
+				.filter_map(|kitty_id| kitty_details(kitty_id).map(|details| (kitty_id, details)))
+				.collect()
+		}
+
+		fn kitty_details(kitty_id: u32) -> Option<KittyDetails<AccountId, Balance>> {
+			kitty_details(kitty_id)
+		}
+
+		fn kitty_art(kitty_id: u32) -> Option<KittyArt> {
+			Kitties::art_of(kitty_id).map(|art| KittyArt { parts: art.parts, palette: art.palette })
+		}
+
+		fn kitty_by_name(name: Vec<u8>) -> Option<u32> {
+			Kitties::kitty_by_name(name)
+		}
+
+		fn market_listings(start_after: Option<u32>, limit: u32) -> (Vec<(u32, Balance)>, Option<u32>) {
+			Kitties::market_listings_page(start_after, limit)
+		}
+
+		fn kitties_snapshot(start_after: Option<u32>, limit: u32) -> (Vec<(u32, KittyDetails<AccountId, Balance>)>, Option<u32>) {
+			let (kitty_ids, cursor) = Kitties::kitties_page(start_after, limit);
+			let kitties = kitty_ids.into_iter()
+				.filter_map(|kitty_id| kitty_details(kitty_id).map(|details| (kitty_id, details)))
+				.collect();
+			(kitties, cursor)
+		}
+
+		fn kitties_with_trait(trait_kind: u8, value: u8) -> Vec<u32> {
+			kitties::TraitKind::ALL.get(trait_kind as usize)
+				.map(|kind| Kitties::kitties_with_trait((*kind, value)))
+				.unwrap_or_default()
+		}
+
+		fn kitties_per_generation(generation: u32) -> u64 {
+			Kitties::kitties_per_generation(generation)
+		}
+
+		fn kitty_owner_key(kitty_id: u32) -> Vec<u8> {
+			Kitties::owner_storage_key(kitty_id)
+		}
+	}
+
 	impl substrate_session::SessionKeys<Block> for Runtime {
 		fn generate_session_keys(seed: Option<Vec<u8>>) -> Vec<u8> {
 			let seed = seed.as_ref().map(|s| rstd::str::from_utf8(&s).expect("Seed is an utf8 string"));
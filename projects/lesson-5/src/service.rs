@@ -69,6 +69,13 @@ macro_rules! new_full_start {
 				tasks_to_spawn = Some(vec![Box::new(pruning_task)]);
 
 				Ok(import_queue)
+			})?
+			.with_rpc_extensions(|client, _pool| {
+				use kitties_rpc::{Kitties, KittiesApi};
+
+				let mut io = jsonrpc_core::IoHandler::<substrate_service::RpcMetadata>::default();
+				io.extend_with(KittiesApi::to_delegate(Kitties::new(client)));
+				io
 			})?;
 
 		(builder, import_setup, inherent_data_providers, tasks_to_spawn)
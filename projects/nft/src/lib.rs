@@ -0,0 +1,27 @@
+//! Traits for non-fungible tokens, so modules can work with the tokens of another module
+//! (e.g. kitties) without depending on its types.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use core::result;
+
+/// Result of a token operation, the error is a static description like a dispatch error
+pub type Result<T> = result::Result<T, &'static str>;
+
+/// A collection of unique tokens owned by accounts
+pub trait NonFungible<AccountId> {
+	/// Identifies one token of the collection
+	type TokenId;
+
+	/// The owner of a token, `None` if it does not exist
+	fn owner_of(token: &Self::TokenId) -> Option<AccountId>;
+
+	/// Move a token owned by `from` to `to`
+	fn transfer(from: &AccountId, to: &AccountId, token: &Self::TokenId) -> Result<()>;
+
+	/// Create a new token owned by `owner`
+	fn mint(owner: &AccountId) -> Result<Self::TokenId>;
+
+	/// Destroy a token owned by `owner`
+	fn burn(owner: &AccountId, token: &Self::TokenId) -> Result<()>;
+}